@@ -1,33 +1,1233 @@
-
-    // Papiweb desarrollos informaticos
-use std::env;
-use std::collections::HashMap;
-
-fn main() {
-    // Lista de palabras clave sospechosas de ser secretos
-    let keywords = vec!["SECRET", "PASSWORD", "KEY", "TOKEN", "AUTH", "PWD"];
-    let mut findings = HashMap::new();
-
-    println!("--- AUDITORÍA DE SEGURIDAD LOCAL (Demo) ---");
-    println!("Buscando posibles credenciales expuestas en entorno...\n");
-
-    // Iteramos sobre las variables de entorno del sistema
-    for (key, value) in env::vars() {
-        for kw in &keywords {
-            if key.to_uppercase().contains(kw) {
-                findings.insert(key.clone(), value.clone());
-            }
-        }
-    }
-
-    if findings.is_empty() {
-        println!("✅ No se detectaron variables críticas expuestas.");
-    } else {
-        println!("⚠️ HALLAZGOS CRÍTICOS ({}):", findings.len());
-        for (key, _) in findings {
-            // No imprimimos el valor por seguridad, solo la clave expuesta
-            println!("   [!] Variable sensible encontrada: {}", key);
-        }
-        println!("\nRECOMENDACIÓN: Mover estos secretos a un Vault o gestor de secretos.");
-    }
-}
+// Papiweb desarrollos informaticos
+#[path = "auditorlocal/accounts.rs"]
+mod accounts;
+#[path = "auditorlocal/base64.rs"]
+mod base64;
+#[path = "auditorlocal/baseline.rs"]
+mod baseline;
+#[path = "auditorlocal/bench.rs"]
+mod bench;
+#[path = "auditorlocal/cmdline.rs"]
+mod cmdline;
+#[path = "auditorlocal/compromised.rs"]
+mod compromised;
+#[path = "auditorlocal/credstore.rs"]
+mod credstore;
+#[path = "auditorlocal/dates.rs"]
+mod dates;
+#[path = "auditorlocal/der.rs"]
+mod der;
+#[path = "auditorlocal/detectors.rs"]
+mod detectors;
+#[path = "auditorlocal/filescan.rs"]
+mod filescan;
+#[path = "auditorlocal/filesystem.rs"]
+mod filesystem;
+#[path = "auditorlocal/finding.rs"]
+mod finding;
+#[path = "auditorlocal/formats.rs"]
+mod formats;
+#[path = "auditorlocal/git.rs"]
+mod git;
+#[path = "auditorlocal/gitignore.rs"]
+mod gitignore;
+#[path = "auditorlocal/hardening.rs"]
+mod hardening;
+#[path = "auditorlocal/history.rs"]
+mod history;
+#[path = "auditorlocal/hook.rs"]
+mod hook;
+#[path = "auditorlocal/image.rs"]
+mod image;
+#[path = "auditorlocal/inflate.rs"]
+mod inflate;
+#[path = "auditorlocal/json.rs"]
+mod json;
+#[path = "auditorlocal/jwt.rs"]
+mod jwt;
+#[path = "auditorlocal/keys.rs"]
+mod keys;
+#[path = "auditorlocal/listeners.rs"]
+mod listeners;
+#[path = "auditorlocal/manifest.rs"]
+mod manifest;
+#[path = "auditorlocal/names.rs"]
+mod names;
+#[path = "auditorlocal/packfile.rs"]
+mod packfile;
+#[path = "auditorlocal/pattern.rs"]
+mod pattern;
+#[path = "auditorlocal/permissions.rs"]
+mod permissions;
+#[path = "auditorlocal/preflight.rs"]
+mod preflight;
+#[path = "auditorlocal/preview.rs"]
+mod preview;
+#[path = "auditorlocal/procenv.rs"]
+mod procenv;
+#[path = "auditorlocal/remediation.rs"]
+mod remediation;
+#[path = "auditorlocal/report.rs"]
+mod report;
+#[path = "auditorlocal/rules.rs"]
+mod rules;
+#[path = "auditorlocal/sha256.rs"]
+mod sha256;
+#[path = "auditorlocal/shellhist.rs"]
+mod shellhist;
+#[path = "auditorlocal/sshd.rs"]
+mod sshd;
+#[path = "auditorlocal/system.rs"]
+mod system;
+#[path = "auditorlocal/tar.rs"]
+mod tar;
+#[path = "auditorlocal/units.rs"]
+mod units;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use accounts::AccountStats;
+use baseline::Baseline;
+use compromised::CompromisedList;
+use credstore::StoreStats;
+use detectors::Detector;
+use filescan::{FileScanner, ScanStats};
+use filesystem::FilesystemStats;
+use finding::{AuditPriority, BaselineStatus, Finding, Location, SecretKind};
+use git::Repository;
+use history::HistoryScanner;
+use image::ImageStats;
+use keys::KeyStats;
+use listeners::ListenerStats;
+use manifest::ManifestStats;
+use names::NameMatcher;
+use permissions::Owners;
+use preflight::{Limits, Scope};
+use preview::Preview;
+use procenv::HostStats;
+use remediation::Patch;
+use report::OutputFormat;
+use rules::RuleSet;
+use shellhist::HistoryStats;
+use sshd::SshdStats;
+use units::UnitStats;
+
+// Códigos de salida para CI: limpio, hallazgos >= --fail-on, error del escáner.
+const EXIT_CLEAN: i32 = 0;
+const EXIT_FINDINGS: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+// Mensajes de progreso: a stdout en modo texto, a stderr cuando stdout lleva JSON/SARIF.
+macro_rules! progress {
+    ($format:expr, $($arg:tt)*) => {
+        if $format == OutputFormat::Text {
+            println!($($arg)*)
+        } else {
+            eprintln!($($arg)*)
+        }
+    };
+}
+
+const USAGE: &str = "Uso: auditorlocal [COMANDO] [OPCIONES]
+
+Comandos:
+  (ninguno)              Audita las variables de entorno del proceso actual
+  scan <RUTA>            Audita recursivamente archivos .env, configuración y código fuente
+  git <REPO>             Audita todo el historial git del repositorio (todas las ramas)
+  image <ARCHIVO.tar>    Audita una imagen guardada con docker save (Docker u OCI, también .tar.gz): Env e
+                         historial de la configuración y todas las capas, incluidos archivos borrados después
+  manifest <RUTA>        Audita manifiestos de Kubernetes (env de contenedores, ConfigMaps, Secrets en base64)
+                         y archivos docker-compose (environment, build.args, command) con la alternativa a usar
+  hook                   Hook pre-commit: analiza solo las líneas nuevas preparadas en el índice git y bloquea
+                         el commit si hay hallazgos fuera de la baseline (compartida con 'scan .' desde la raíz
+                         del repositorio). En .git/hooks/pre-commit: exec auditorlocal hook --baseline ARCHIVO
+  host                   Audita el entorno de todos los procesos legibles (/proc/<pid>/environ)
+  stores                 Inventario de archivos de credenciales (AWS, Docker, kube, netrc, npm, PyPI, git,
+                         gcloud) en el home de todos los usuarios, con sus permisos
+  shell-history          Audita los historiales de shell (bash, zsh, sh, fish) de todos los usuarios:
+                         mysql -pCLAVE, --password=, curl -u usuario:clave, export TOKEN=...
+  keys [RUTA]            Inventario de claves privadas (PEM, OpenSSH, PKCS#12, JKS) en RUTA o, sin ella, en /etc,
+                         /opt, /srv y los homes: tipo y tamaño, contraseña, claves débiles (RSA < 2048, DSA) y
+                         su clave pública o certificado (.pub, authorized_keys, CN) para saber de qué servicio es
+  exposure               Inventario de sockets TCP/UDP en escucha (/proc/net) con el proceso, usuario y unidad de
+                         cada uno; marca servicios pensados para uso local (bases de datos, Redis, API de Docker,
+                         puertos de depuración) atados a 0.0.0.0 o :: o a una interfaz de red
+  sshd [ARCHIVO]         Audita sshd_config (por defecto /etc/ssh/sshd_config) con sus Include y bloques Match:
+                         contraseñas, PermitRootLogin, cifrados/MAC/KEX débiles, MaxAuthTries y X11Forwarding
+  accounts [RAÍZ]        Audita las cuentas (/etc/passwd, /etc/shadow si se puede leer, /etc/group) y sudoers con
+                         sus include: UID 0 además de root, contraseñas vacías o con hash MD5/DES, shells en
+                         cuentas de servicio, NOPASSWD: ALL y sudoers modificables; RAÍZ audita un sistema montado
+  filesystem [RUTA]      Endurecimiento del sistema de archivos en RUTA o en todo el host: SUID/SGID fuera de la
+                         lista conocida de la distribución, archivos y directorios sin sticky bit modificables por
+                         cualquiera y archivos sin dueño; sin RUTA revisa además los directorios del PATH
+  units                  Audita unidades systemd y drop-ins (Environment=, Exec*=, EnvironmentFile=)
+  rules check [ARCHIVO]  Valida packs de reglas (.toml, .yaml) sobre el pack incorporado y muestra el resultado
+  preflight <ALCANCE>    Chequeo previo de alcance antes de la auditoría pesada: conexión TCP a cada puerto
+                         declarado (una línea por destino: host open=80,443 closed=22,3306,8000-8100) y falla
+                         si un destino no responde, falta un servicio o hay abierto un puerto que debía estar cerrado
+  bench [MiB]            Mide el escáner de archivos (MB/s con 1 hilo y con --threads) sobre un árbol sintético
+                         de MiB megabytes (16 por defecto) generado en el directorio temporal
+
+Opciones:
+  --rules ARCHIVO        Pack de reglas TOML/YAML aplicado sobre el incorporado; repetible, en orden
+  --disable-rule ID      Desactiva una regla por id (p. ej. high-entropy-string); repetible
+  --benign NOMBRE        Marca una variable como benigna (no es un secreto)
+  --no-default-benign    Desactiva la lista incorporada de nombres benignos
+  --max-file-size BYTES  Omite archivos YAML/JSON/INI/TOML (se leen enteros) y blobs más grandes (por defecto
+                         2 MiB); .env y código fuente se leen por bloques de 1 MiB, sin límite de tamaño
+  --threads N            Hilos del escaneo de archivos (por defecto, todos los núcleos disponibles)
+  --timeout MS           Timeout de cada conexión de preflight (por defecto 2000)
+  --concurrency N        Conexiones simultáneas de preflight (por defecto 8)
+  --max-commits N        Limita el número de commits analizados en modo git
+  --baseline ARCHIVO     Compara contra una baseline JSON de hallazgos aceptados
+  --update-baseline      Añade los hallazgos actuales a la baseline; conserva las demás entradas
+  --prune                Con --update-baseline, elimina además las entradas que este análisis ya no encuentra
+                         (usarlo solo con el alcance completo: p. ej. 'scan .', no con 'hook')
+  --reason TEXTO         Motivo registrado para las nuevas aceptaciones
+  --expires AAAA-MM-DD   Fecha de vencimiento de las nuevas aceptaciones
+  --compromised ARCHIVO  Lista local de SHA-256 (o prefijos) de secretos filtrados/revocados; repetible
+  --patch ARCHIVO        Escribe un diff que reemplaza los valores hallados en .env y docker-compose por
+                         referencias ${NOMBRE}; aplicarlo con git apply o patch -p1 desde la ruta analizada
+  --show-preview         Muestra una vista enmascarada del valor (2 primeros/últimos caracteres, longitud,
+                         juego de caracteres y entropía) y su tipo probable
+  --format FORMATO       Formato de salida: text (por defecto), json o sarif
+  --fail-on SEVERIDAD    Severidad mínima que hace fallar la auditoría: low (por defecto), medium, high, critical
+
+Códigos de salida:
+  0  sin hallazgos pendientes a partir de --fail-on
+  1  hay hallazgos nuevos o con aceptación vencida a partir de --fail-on
+  2  error de argumentos o del escáner";
+
+enum Command {
+    Env,
+    Scan(PathBuf),
+    Git(PathBuf),
+    Image(PathBuf),
+    Manifest(PathBuf),
+    Hook,
+    Host,
+    Stores,
+    ShellHistory,
+    Units,
+    Keys(Option<PathBuf>),
+    Exposure,
+    Sshd(Option<PathBuf>),
+    Accounts(Option<PathBuf>),
+    Filesystem(Option<PathBuf>),
+    Preflight(PathBuf),
+    RulesCheck,
+    Bench(u64),
+}
+
+struct Options {
+    command: Command,
+    benign: Vec<String>,
+    default_benign: bool,
+    max_file_size: u64,
+    threads: usize,
+    limits: Limits,
+    max_commits: Option<usize>,
+    baseline: Option<PathBuf>,
+    update_baseline: bool,
+    prune: bool,
+    reason: String,
+    expires: Option<String>,
+    format: OutputFormat,
+    fail_on: AuditPriority,
+    show_preview: bool,
+    patch: Option<PathBuf>,
+    compromised: Vec<PathBuf>,
+    rule_packs: Vec<PathBuf>,
+    disabled_rules: Vec<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        command: Command::Env,
+        benign: Vec::new(),
+        default_benign: true,
+        max_file_size: filescan::DEFAULT_MAX_FILE_SIZE,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        limits: Limits::default(),
+        max_commits: None,
+        baseline: None,
+        update_baseline: false,
+        prune: false,
+        reason: baseline::DEFAULT_REASON.to_string(),
+        expires: None,
+        format: OutputFormat::Text,
+        fail_on: AuditPriority::Low,
+        show_preview: false,
+        patch: None,
+        compromised: Vec::new(),
+        rule_packs: Vec::new(),
+        disabled_rules: Vec::new(),
+    };
+    let mut args = env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "scan" => options.command = Command::Scan(args.next().ok_or("scan requiere una ruta")?.into()),
+            "git" => options.command = Command::Git(args.next().ok_or("git requiere la ruta del repositorio")?.into()),
+            "image" => {
+                options.command = Command::Image(args.next().ok_or("image requiere el archivo de la imagen")?.into())
+            }
+            "manifest" => options.command = Command::Manifest(args.next().ok_or("manifest requiere una ruta")?.into()),
+            "hook" => options.command = Command::Hook,
+            "host" => options.command = Command::Host,
+            "stores" => options.command = Command::Stores,
+            "shell-history" => options.command = Command::ShellHistory,
+            "units" => options.command = Command::Units,
+            "exposure" => options.command = Command::Exposure,
+            "accounts" => {
+                let root = args.next_if(|v| !v.starts_with('-'));
+                options.command = Command::Accounts(root.map(PathBuf::from))
+            }
+            "filesystem" => {
+                let root = args.next_if(|v| !v.starts_with('-'));
+                options.command = Command::Filesystem(root.map(PathBuf::from))
+            }
+            "sshd" => {
+                let config = args.next_if(|v| !v.starts_with('-'));
+                options.command = Command::Sshd(config.map(PathBuf::from))
+            }
+            "keys" => {
+                let root = args.next_if(|v| !v.starts_with('-'));
+                options.command = Command::Keys(root.map(PathBuf::from))
+            }
+            "preflight" => {
+                options.command =
+                    Command::Preflight(args.next().ok_or("preflight requiere el archivo de alcance")?.into())
+            }
+            "rules" => match args.next().as_deref() {
+                Some("check") => options.command = Command::RulesCheck,
+                _ => return Err("rules requiere el subcomando check".to_string()),
+            },
+            "bench" => {
+                let size = args.peek().and_then(|v| v.parse().ok());
+                if size.is_some() {
+                    args.next();
+                }
+                options.command = Command::Bench(size.unwrap_or(16))
+            }
+            "--rules" => options
+                .rule_packs
+                .push(args.next().ok_or("--rules requiere un archivo")?.into()),
+            "--disable-rule" => options
+                .disabled_rules
+                .push(args.next().ok_or("--disable-rule requiere el id de una regla")?),
+            "--benign" => options
+                .benign
+                .push(args.next().ok_or("--benign requiere un nombre de variable")?),
+            "--no-default-benign" => options.default_benign = false,
+            "--max-file-size" => {
+                options.max_file_size = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--max-file-size requiere un número de bytes")?
+            }
+            "--threads" => {
+                options.threads = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or("--threads requiere un número de hilos mayor que cero")?
+            }
+            "--timeout" => {
+                options.limits.timeout = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&ms| ms > 0)
+                    .map(Duration::from_millis)
+                    .ok_or("--timeout requiere un número de milisegundos mayor que cero")?
+            }
+            "--concurrency" => {
+                options.limits.concurrency = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or("--concurrency requiere un número de conexiones mayor que cero")?
+            }
+            "--max-commits" => {
+                options.max_commits = Some(
+                    args.next()
+                        .and_then(|v| v.parse().ok())
+                        .ok_or("--max-commits requiere un número")?,
+                )
+            }
+            "--baseline" => options.baseline = Some(args.next().ok_or("--baseline requiere un archivo")?.into()),
+            "--update-baseline" => options.update_baseline = true,
+            "--prune" => options.prune = true,
+            "--reason" => options.reason = args.next().ok_or("--reason requiere un texto")?,
+            "--expires" => {
+                let date = args.next().ok_or("--expires requiere una fecha AAAA-MM-DD")?;
+                if dates::parse_date(&date).is_none() {
+                    return Err(format!("fecha inválida para --expires: {} (AAAA-MM-DD)", date));
+                }
+                options.expires = Some(date);
+            }
+            "--compromised" => options
+                .compromised
+                .push(args.next().ok_or("--compromised requiere un archivo")?.into()),
+            "--show-preview" => options.show_preview = true,
+            "--patch" => options.patch = Some(args.next().ok_or("--patch requiere un archivo")?.into()),
+            "--format" => {
+                let value = args.next().ok_or("--format requiere text, json o sarif")?;
+                options.format = OutputFormat::parse(&value).ok_or(format!("formato desconocido: {}", value))?;
+            }
+            "--fail-on" => {
+                let value = args.next().ok_or("--fail-on requiere una severidad")?;
+                options.fail_on = AuditPriority::parse(&value).ok_or(format!("severidad desconocida: {}", value))?;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(EXIT_CLEAN);
+            }
+            // "rules check a.toml b.yaml": los packs también se pueden pasar como argumentos.
+            other if matches!(options.command, Command::RulesCheck) && !other.starts_with('-') => {
+                options.rule_packs.push(other.into())
+            }
+            other => return Err(format!("argumento desconocido: {}", other)),
+        }
+    }
+    if options.update_baseline && options.baseline.is_none() {
+        return Err("--update-baseline requiere --baseline ARCHIVO".to_string());
+    }
+    if options.prune && !options.update_baseline {
+        return Err("--prune requiere --update-baseline".to_string());
+    }
+    Ok(options)
+}
+
+fn scan_env(detector: &Detector) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    // Iteramos sobre las variables de entorno del sistema: nombre y valor
+    for (key, value) in env::vars() {
+        for detection in detector.scan_pair(&key, &value) {
+            findings.push(detection.into_finding(&value, Location::Env { var: key.clone() }));
+        }
+    }
+    findings
+}
+
+// Pack incorporado, packs del equipo en orden y reglas desactivadas desde la línea de comandos.
+fn load_rules(options: &Options) -> Result<RuleSet, String> {
+    let mut rule_set = RuleSet::builtin();
+    if !options.default_benign {
+        rule_set.benign.clear();
+    }
+    for path in &options.rule_packs {
+        rule_set.layer(rules::load_pack(path)?);
+    }
+    for id in &options.disabled_rules {
+        rule_set.disable(id);
+    }
+    Ok(rule_set)
+}
+
+fn run_preflight(path: &Path, limits: &Limits) -> i32 {
+    let report = Scope::load(path).and_then(|scope| {
+        println!(
+            "🛫 Chequeo previo de {} destino(s) de {} (timeout {:?}, {} conexiones a la vez)...\n",
+            scope.targets.len(),
+            path.display(),
+            limits.timeout,
+            limits.concurrency
+        );
+        preflight::run(&scope, limits)
+    });
+    match report {
+        Ok(report) => {
+            report.print();
+            if report.passed() {
+                EXIT_CLEAN
+            } else {
+                EXIT_FINDINGS
+            }
+        }
+        Err(err) => {
+            eprintln!("❌ Error en el alcance: {}", err);
+            EXIT_ERROR
+        }
+    }
+}
+
+fn check_rules(options: &Options) -> i32 {
+    println!("Validando packs de reglas...\n");
+    let mut rule_set = RuleSet::default();
+    let builtin = rule_set.layer(rules::builtin_pack());
+    println!(
+        "✅ pack incorporado: {} reglas, {} palabras clave, {} nombres benignos",
+        builtin.added,
+        rule_set.keywords.len(),
+        rule_set.benign.len()
+    );
+    let mut valid = true;
+    for path in &options.rule_packs {
+        match rules::load_pack(path) {
+            Ok(pack) => {
+                let name = pack.name.clone();
+                let summary = rule_set.layer(pack);
+                println!(
+                    "✅ {}: {} reglas nuevas, {} reemplazadas, {} desactivadas",
+                    name, summary.added, summary.replaced, summary.disabled
+                );
+            }
+            Err(err) => {
+                valid = false;
+                println!("❌ {}", err);
+            }
+        }
+    }
+    for id in &options.disabled_rules {
+        rule_set.disable(id);
+    }
+    let external = [
+        accounts::rule_ids(),
+        credstore::rule_ids(),
+        filesystem::rule_ids(),
+        keys::rule_ids(),
+        listeners::rule_ids(),
+        sshd::rule_ids(),
+    ]
+    .concat();
+    for id in rule_set.unknown_disabled(&external) {
+        println!("⚠️  se desactiva '{}', que no corresponde a ninguna regla", id);
+    }
+    println!(
+        "\n📋 Reglas activas: {} | desactivadas: {} | umbrales de entropía: base64 {:.2}, hex {:.2}, longitud mínima {}",
+        rule_set.active().count(),
+        rule_set.disabled_count(),
+        rule_set.entropy.base64_threshold,
+        rule_set.entropy.hex_threshold,
+        rule_set.entropy.min_length
+    );
+    if valid {
+        EXIT_CLEAN
+    } else {
+        EXIT_ERROR
+    }
+}
+
+fn print_findings(findings: &[&Finding], baseline: Option<&Baseline>, show_preview: bool, rule_set: &RuleSet) {
+    for finding in findings {
+        // No imprimimos el valor por seguridad, solo la ubicación expuesta y el tipo detectado
+        println!(
+            "   [!] {} {} en {} [{}] (severidad {}, confianza {})",
+            finding.priority.icon(),
+            rule_set.describe(finding),
+            finding.location,
+            finding.rule_id,
+            finding.priority.as_str(),
+            finding.confidence.as_str()
+        );
+        let plan = remediation::plan(finding, rule_set);
+        for note in finding.notes.iter().filter(|n| Some(n.id) != plan.advice_note) {
+            println!("       ↳ {} {}", note.priority.icon(), note.text);
+        }
+        if let Some(compromise) = finding.compromised {
+            println!(
+                "       ↳ ☠️  CONOCIDO COMO COMPROMETIDO: el valor está en la lista de secretos filtrados/revocados ({})",
+                compromise.as_str()
+            );
+        }
+        if finding.status != BaselineStatus::Accepted {
+            match &plan.rotation {
+                Some(rotation) => {
+                    println!("       ↳ 🛠️  {} — rotar: {}", plan.credential, rotation);
+                    println!("       ↳ 🔁 reemplazo: {}", plan.replacement);
+                }
+                None => println!("       ↳ 🛠️  corregir: {}", plan.replacement),
+            }
+        }
+        if show_preview {
+            let value = finding.secret.expose();
+            println!(
+                "       ↳ vista: {} — tipo: {}",
+                Preview::of(value),
+                preview::classify(finding.kind, value)
+            );
+        }
+        if let Some(entry) = baseline.and_then(|b| b.entry(finding)) {
+            println!(
+                "       ↳ aceptado el {}: {} (vence: {})",
+                entry.added,
+                entry.reason,
+                entry.expires.as_deref().unwrap_or("nunca")
+            );
+        }
+    }
+}
+
+// Agrupa los hallazgos de procesos por unidad systemd (o ejecutable) ordenados por antigüedad.
+fn print_service_summary(findings: &[Finding]) {
+    let mut services: BTreeMap<String, (u64, BTreeSet<u32>, BTreeSet<String>)> = BTreeMap::new();
+    for finding in findings {
+        if let Location::Process {
+            pid,
+            exe,
+            unit,
+            uptime_secs,
+            var,
+            ..
+        } = &finding.location
+        {
+            let entry = services.entry(unit.clone().unwrap_or_else(|| exe.clone())).or_default();
+            entry.0 = entry.0.max(*uptime_secs);
+            entry.1.insert(*pid);
+            entry.2.insert(var.clone());
+        }
+    }
+    if services.is_empty() {
+        return;
+    }
+    let mut services: Vec<_> = services.into_iter().collect();
+    services.sort_by_key(|(_, (uptime, _, _))| std::cmp::Reverse(*uptime));
+    println!("🧩 SERVICIOS CON CREDENCIALES EN CLARO EN SU ENTORNO:");
+    for (service, (uptime, pids, vars)) in services {
+        println!(
+            "   {} — activo {} — {} proceso(s) — variables: {}",
+            service,
+            procenv::format_duration(uptime),
+            pids.len(),
+            vars.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    println!();
+}
+
+// Reglas activas, anotaciones (JWT, permisos), lista de comprometidos y baseline sobre un lote de hallazgos;
+// devuelve cuántos coinciden con la lista de comprometidos. Sin `owners` no se leen los permisos del disco (el hook
+// ya anotó el modo del índice).
+fn enrich(
+    findings: &mut Vec<Finding>,
+    rule_set: &RuleSet,
+    owners: Option<&Owners>,
+    compromised: Option<&CompromisedList>,
+    baseline: Option<&Baseline>,
+) -> usize {
+    // Las reglas externas al detector (almacenes de credenciales...) también se pueden desactivar.
+    findings.retain(|f| rule_set.is_enabled(&f.rule_id));
+    jwt::annotate(findings);
+    if let Some(owners) = owners {
+        permissions::annotate(findings, owners);
+    }
+    let marked = compromised.map_or(0, |list| list.mark(findings));
+    if let Some(baseline) = baseline {
+        baseline.classify(findings);
+    }
+    marked
+}
+
+// Hallazgos pendientes de un archivo recién analizado por 'scan'; los aceptados se listan al final.
+fn print_streamed(
+    findings: &[Finding],
+    baseline: Option<&Baseline>,
+    show_preview: bool,
+    rule_set: &RuleSet,
+    header_printed: &mut bool,
+) {
+    for finding in findings.iter().filter(|f| f.status != BaselineStatus::Accepted) {
+        if !*header_printed {
+            println!("⚠️ HALLAZGOS (a medida que termina cada archivo):");
+            *header_printed = true;
+        }
+        let acceptance = baseline.filter(|_| finding.status == BaselineStatus::Expired);
+        print_findings(&[finding], acceptance, show_preview, rule_set);
+    }
+}
+
+// `streamed`: los pendientes ya se mostraron archivo por archivo y aquí solo se resumen.
+fn print_text_report(
+    findings: &[Finding],
+    baseline: Option<&Baseline>,
+    show_preview: bool,
+    rule_set: &RuleSet,
+    patch: &Patch,
+    streamed: bool,
+) {
+    let sorted = report::sorted(findings);
+    let with_status = |status| {
+        sorted
+            .iter()
+            .copied()
+            .filter(|f| f.status == status)
+            .collect::<Vec<_>>()
+    };
+    let new = with_status(BaselineStatus::New);
+    let expired = with_status(BaselineStatus::Expired);
+    let accepted = with_status(BaselineStatus::Accepted);
+
+    if new.is_empty() && expired.is_empty() {
+        println!("✅ No se detectaron credenciales expuestas sin aceptar.");
+    } else {
+        if streamed {
+            println!(
+                "⚠️ HALLAZGOS CRÍTICOS: {} nuevo(s), {} aceptación(es) vencida(s) (detalle arriba)",
+                new.len(),
+                expired.len()
+            );
+        }
+        if !new.is_empty() && !streamed {
+            println!("⚠️ HALLAZGOS CRÍTICOS ({}):", new.len());
+            print_findings(&new, None, show_preview, rule_set);
+        }
+        if !expired.is_empty() && !streamed {
+            println!("⌛ ACEPTACIONES VENCIDAS ({}):", expired.len());
+            print_findings(&expired, baseline, show_preview, rule_set);
+        }
+        let pending = || new.iter().chain(&expired);
+        if pending().any(|f| f.kind != SecretKind::Misconfiguration) {
+            println!("\nPLAN: rotar primero cada credencial (el valor ya expuesto deja de servir) y después aplicar el reemplazo indicado.");
+        }
+        if pending().any(|f| f.kind == SecretKind::Misconfiguration) {
+            println!(
+                "\nPLAN: aplicar cada corrección de configuración y recargar el servicio afectado para que la tome."
+            );
+        }
+        if patch.changes() > 0 {
+            println!(
+                "   {} valor(es) en {} archivo(s) .env/compose se pueden reemplazar por referencias: --patch ARCHIVO genera el diff.",
+                patch.changes(),
+                patch.files()
+            );
+        }
+    }
+    if !accepted.is_empty() {
+        println!("\n☑️  ACEPTADOS EN BASELINE ({}):", accepted.len());
+        print_findings(&accepted, baseline, show_preview, rule_set);
+    }
+}
+
+// Salida compacta del hook: una línea por hallazgo que bloquea el commit y qué hacer con él.
+fn print_hook_report(findings: &[Finding], fail_on: AuditPriority, rule_set: &RuleSet) {
+    let blocking: Vec<&Finding> = report::sorted(findings)
+        .into_iter()
+        .filter(|f| f.status != BaselineStatus::Accepted && f.priority >= fail_on)
+        .collect();
+    if blocking.is_empty() {
+        println!("✅ auditorlocal: sin secretos nuevos en los cambios preparados");
+        return;
+    }
+    println!(
+        "🛑 Commit bloqueado: {} secreto(s) nuevo(s) en los cambios preparados",
+        blocking.len()
+    );
+    for finding in &blocking {
+        println!(
+            "   {} {} — {} [{}]",
+            finding.priority.icon(),
+            finding.location,
+            rule_set.describe(finding),
+            finding.rule_id
+        );
+        let plan = remediation::plan(finding, rule_set);
+        if let Some(rotation) = &plan.rotation {
+            println!("      ↳ rotar: {}", rotation);
+        }
+        println!("      ↳ reemplazo: {}", plan.replacement);
+    }
+    println!(
+        "\nQuitar el valor del archivo y volver a hacer git add (leerlo de una variable de entorno o un gestor de secretos);\n\
+         si ya se compartió, rotarlo. Falso positivo: auditorlocal hook --baseline ARCHIVO --update-baseline --reason \"...\""
+    );
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|err| {
+        eprintln!("❌ {}\n\n{}", err, USAGE);
+        process::exit(EXIT_ERROR);
+    });
+
+    if matches!(options.command, Command::RulesCheck) {
+        process::exit(check_rules(&options));
+    }
+    if let Command::Preflight(path) = &options.command {
+        process::exit(run_preflight(path, &options.limits));
+    }
+    let rule_set = load_rules(&options).unwrap_or_else(|err| {
+        eprintln!("❌ Error en el pack de reglas: {}", err);
+        process::exit(EXIT_ERROR);
+    });
+    let mut names = NameMatcher::new(&rule_set.keywords, &rule_set.benign);
+    for name in &options.benign {
+        names = names.with_benign(name);
+    }
+    let detector = Detector::new(&rule_set, names);
+    if let Command::Bench(size_mib) = options.command {
+        let scanner = FileScanner::new(&detector, options.max_file_size);
+        if let Err(err) = bench::run(&scanner, size_mib, options.threads) {
+            eprintln!("❌ Error del banco de pruebas: {}", err);
+            process::exit(EXIT_ERROR);
+        }
+        process::exit(EXIT_CLEAN);
+    }
+
+    // La lista de comprometidos se valida antes de escanear para fallar rápido.
+    let compromised = (!options.compromised.is_empty()).then(|| {
+        let mut list = CompromisedList::default();
+        let mut loaded = 0;
+        for path in &options.compromised {
+            loaded += list.load(path).unwrap_or_else(|err| {
+                eprintln!("❌ Error en la lista de comprometidos: {}", err);
+                process::exit(EXIT_ERROR);
+            });
+        }
+        (list, loaded)
+    });
+    let compromised_list = compromised.as_ref().map(|(list, _)| list);
+    // Sin --update-baseline la baseline solo se lee: se carga antes de escanear para fallar rápido y para
+    // clasificar cada archivo de 'scan' en cuanto termina.
+    let mut baseline = options
+        .baseline
+        .as_ref()
+        .filter(|_| !options.update_baseline)
+        .map(|path| {
+            Baseline::load(path).unwrap_or_else(|err| {
+                eprintln!("❌ Error de baseline: {}", err);
+                process::exit(EXIT_ERROR);
+            })
+        });
+
+    let format = options.format;
+    // El hook corre en cada commit: sin cabecera ni progreso, solo el resultado.
+    let hook_mode = matches!(options.command, Command::Hook);
+    // 'scan' en texto muestra cada archivo con hallazgos al terminarlo; --update-baseline necesita todos antes.
+    let streaming =
+        format == OutputFormat::Text && matches!(options.command, Command::Scan(_)) && !options.update_baseline;
+    let owners = (!hook_mode).then(Owners::load);
+    let mut marked = 0;
+    if !hook_mode {
+        progress!(format, "--- AUDITORÍA DE SEGURIDAD LOCAL (Demo) ---");
+    }
+    let mut findings = match &options.command {
+        Command::Env => {
+            progress!(format, "Buscando posibles credenciales expuestas en entorno...\n");
+            scan_env(&detector)
+        }
+        Command::Scan(root) => {
+            progress!(
+                format,
+                "Buscando posibles credenciales expuestas en {}...\n",
+                root.display()
+            );
+            let mut stats = ScanStats::default();
+            let scanner = FileScanner::new(&detector, options.max_file_size);
+            let mut findings = Vec::new();
+            let mut header_printed = false;
+            scanner
+                .scan_tree(root, options.threads, &mut stats, |mut batch| {
+                    if streaming {
+                        marked += enrich(
+                            &mut batch,
+                            &rule_set,
+                            owners.as_ref(),
+                            compromised_list,
+                            baseline.as_ref(),
+                        );
+                        print_streamed(
+                            &batch,
+                            baseline.as_ref(),
+                            options.show_preview,
+                            &rule_set,
+                            &mut header_printed,
+                        );
+                    }
+                    findings.extend(batch);
+                })
+                .unwrap_or_else(|err| {
+                    eprintln!("❌ Error de escaneo: {}", err);
+                    process::exit(EXIT_ERROR);
+                });
+            progress!(
+                format,
+                "📂 Archivos analizados: {} ({} bytes) | omitidos: {} binarios, {} grandes, {} ignorados\n",
+                stats.files_scanned,
+                stats.bytes_scanned,
+                stats.skipped_binary,
+                stats.skipped_large,
+                stats.skipped_ignored
+            );
+            findings
+        }
+        Command::Git(path) => {
+            progress!(
+                format,
+                "Buscando credenciales en el historial git de {}...\n",
+                path.display()
+            );
+            let mut stats = ScanStats::default();
+            let scanner = FileScanner::new(&detector, options.max_file_size);
+            let findings = Repository::open(path)
+                .and_then(|repo| HistoryScanner::new(&repo, &scanner, options.max_commits).scan(&mut stats))
+                .unwrap_or_else(|err| {
+                    eprintln!("❌ Error leyendo el repositorio: {}", err);
+                    process::exit(EXIT_ERROR);
+                });
+            progress!(
+                format,
+                "🧬 Commits analizados: {} | blobs modificados: {} ({} bytes)\n",
+                stats.commits_scanned,
+                stats.files_scanned,
+                stats.bytes_scanned
+            );
+            findings
+        }
+        Command::Image(path) => {
+            progress!(format, "Buscando credenciales en la imagen {}...\n", path.display());
+            let mut stats = ImageStats::default();
+            let mut scan_stats = ScanStats::default();
+            let scanner = FileScanner::new(&detector, options.max_file_size);
+            let findings =
+                image::scan_image(path, &detector, &scanner, &mut stats, &mut scan_stats).unwrap_or_else(|err| {
+                    eprintln!("❌ Error leyendo la imagen: {}", err);
+                    process::exit(EXIT_ERROR);
+                });
+            progress!(
+                format,
+                "🐳 Imágenes: {} | capas analizadas: {} (compresión no soportada: {}) | archivos: {} ({} bytes) | omitidos: {} binarios, {} grandes, {} de paquetes\n",
+                stats.images_scanned,
+                stats.layers_scanned,
+                stats.layers_unsupported,
+                scan_stats.files_scanned,
+                scan_stats.bytes_scanned,
+                scan_stats.skipped_binary,
+                scan_stats.skipped_large,
+                scan_stats.skipped_ignored
+            );
+            findings
+        }
+        Command::Manifest(root) => {
+            progress!(
+                format,
+                "Buscando secretos en claro en los manifiestos de {}...\n",
+                root.display()
+            );
+            let scanner = FileScanner::new(&detector, options.max_file_size);
+            let mut stats = ManifestStats::default();
+            let findings = manifest::scan_manifests(root, &detector, &scanner, &mut stats).unwrap_or_else(|err| {
+                eprintln!("❌ Error de escaneo: {}", err);
+                process::exit(EXIT_ERROR);
+            });
+            for invalid in &stats.invalid {
+                progress!(format, "⚠️  YAML no interpretable, se omite: {}", invalid);
+            }
+            progress!(
+                format,
+                "☸️  Manifiestos analizados: {} | recursos y servicios: {} | omitidos: {}\n",
+                stats.files_scanned,
+                stats.resources_scanned,
+                stats.invalid.len()
+            );
+            findings
+        }
+        Command::Hook => {
+            let scanner = FileScanner::new(&detector, options.max_file_size);
+            let mut stats = ScanStats::default();
+            hook::open_repository(Path::new("."))
+                .and_then(|repo| hook::scan_staged(&repo, &scanner, &mut stats))
+                .unwrap_or_else(|err| {
+                    eprintln!("❌ Error leyendo el índice git: {}", err);
+                    process::exit(EXIT_ERROR);
+                })
+        }
+        Command::Host => {
+            progress!(
+                format,
+                "Buscando credenciales en el entorno de los procesos del host...\n"
+            );
+            let mut stats = HostStats::default();
+            let findings = procenv::scan_processes(&detector, &mut stats).unwrap_or_else(|err| {
+                eprintln!("❌ Error leyendo procesos: {}", err);
+                process::exit(EXIT_ERROR);
+            });
+            progress!(
+                format,
+                "🖥️  Procesos analizados: {} | sin permiso de lectura: {}\n",
+                stats.processes_scanned,
+                stats.processes_denied
+            );
+            if format == OutputFormat::Text {
+                print_service_summary(&findings);
+            }
+            findings
+        }
+        Command::Stores => {
+            progress!(
+                format,
+                "Buscando archivos de credenciales en el home de cada usuario...\n"
+            );
+            let mut stats = StoreStats::default();
+            let findings = credstore::scan_stores(&mut stats);
+            progress!(
+                format,
+                "🗝️  Homes analizados: {} | almacenes encontrados: {} (con secretos en claro: {}, sin permiso de lectura: {})\n",
+                stats.homes_scanned,
+                stats.stores_found,
+                stats.stores_with_secrets,
+                stats.stores_denied
+            );
+            findings
+        }
+        Command::ShellHistory => {
+            progress!(
+                format,
+                "Buscando contraseñas tecleadas en los historiales de shell de cada usuario...\n"
+            );
+            let mut stats = HistoryStats::default();
+            let findings = shellhist::scan_histories(&detector, &mut stats);
+            progress!(
+                format,
+                "🐚 Historiales analizados: {} | comandos: {} | sin permiso de lectura: {}\n",
+                stats.files_scanned,
+                stats.commands_scanned,
+                stats.files_denied
+            );
+            findings
+        }
+        Command::Units => {
+            progress!(format, "Buscando credenciales en las unidades systemd...\n");
+            let scanner = FileScanner::new(&detector, options.max_file_size);
+            let mut stats = UnitStats::default();
+            let findings = units::scan_units(&detector, &scanner, &mut stats);
+            progress!(
+                format,
+                "⚙️  Unidades analizadas: {} | EnvironmentFile: {} | sin permiso de lectura: {}\n",
+                stats.units_scanned,
+                stats.env_files_scanned,
+                stats.files_denied
+            );
+            findings
+        }
+        Command::Keys(root) => {
+            progress!(
+                format,
+                "Buscando claves privadas, claves públicas y certificados en {}...\n",
+                root.as_ref()
+                    .map_or("/etc, /opt, /srv y los homes".to_string(), |r| r.display().to_string())
+            );
+            let mut stats = KeyStats::default();
+            let findings = keys::scan_keys(root.as_deref(), &mut stats).unwrap_or_else(|err| {
+                eprintln!("❌ Error leyendo {}", err);
+                process::exit(EXIT_ERROR);
+            });
+            progress!(
+                format,
+                "🔑 Archivos analizados: {} | claves privadas: {} (sin contraseña: {}, débiles: {}) | claves públicas y certificados: {} | sin permiso de lectura: {}\n",
+                stats.files_scanned,
+                stats.private_keys,
+                stats.unprotected,
+                stats.weak,
+                stats.public_keys,
+                stats.files_denied
+            );
+            findings
+        }
+        Command::Exposure => {
+            progress!(
+                format,
+                "Inventario de sockets en escucha (/proc/net) y del proceso de cada uno...\n"
+            );
+            let mut stats = ListenerStats::default();
+            let (sockets, findings) = listeners::scan_listeners(&mut stats).unwrap_or_else(|err| {
+                eprintln!("❌ Error leyendo las tablas de sockets: {}", err);
+                process::exit(EXIT_ERROR);
+            });
+            progress!(
+                format,
+                "🌐 Sockets en escucha: {} (TCP: {}, UDP: {}) | sin proceso visible: {} | procesos sin permiso de lectura: {}\n",
+                sockets.len(),
+                stats.tcp,
+                stats.udp,
+                stats.without_owner,
+                stats.processes_denied
+            );
+            if format == OutputFormat::Text {
+                listeners::print_inventory(&sockets);
+            }
+            findings
+        }
+        Command::Sshd(config) => {
+            let config = config.clone().unwrap_or_else(|| PathBuf::from(sshd::DEFAULT_CONFIG));
+            progress!(
+                format,
+                "Auditando {} con sus Include y bloques Match...\n",
+                config.display()
+            );
+            let mut stats = SshdStats::default();
+            let findings = sshd::scan_sshd(&config, &mut stats).unwrap_or_else(|err| {
+                eprintln!("❌ Error leyendo {}", err);
+                process::exit(EXIT_ERROR);
+            });
+            progress!(
+                format,
+                "🔐 Archivos leídos: {} | directivas: {} | bloques Match: {} | sin permiso de lectura: {}\n",
+                stats.files_read,
+                stats.directives,
+                stats.match_blocks,
+                stats.files_denied
+            );
+            findings
+        }
+        Command::Accounts(root) => {
+            let root = root.clone().unwrap_or_else(|| PathBuf::from("/"));
+            progress!(format, "Auditando cuentas y sudoers en {}...\n", root.display());
+            let mut stats = AccountStats::default();
+            let findings = accounts::scan_accounts(&root, &mut stats).unwrap_or_else(|err| {
+                eprintln!("❌ Error leyendo {}", err);
+                process::exit(EXIT_ERROR);
+            });
+            progress!(
+                format,
+                "👤 Cuentas: {} | shadow: {} | archivos de sudoers: {} | sin permiso de lectura: {}\n",
+                stats.accounts,
+                if stats.shadow_readable {
+                    "leído"
+                } else {
+                    "sin leer, se omiten contraseñas vacías y hashes débiles"
+                },
+                stats.sudoers_files,
+                stats.files_denied
+            );
+            findings
+        }
+        Command::Filesystem(root) => {
+            progress!(
+                format,
+                "Recorriendo {} en busca de SUID/SGID, permisos abiertos y archivos sin dueño...\n",
+                root.as_ref().map_or("/".to_string(), |r| r.display().to_string())
+            );
+            let mut stats = FilesystemStats::default();
+            let findings = filesystem::scan_filesystem(root.as_deref(), &mut stats).unwrap_or_else(|err| {
+                eprintln!("❌ Error leyendo {}", err);
+                process::exit(EXIT_ERROR);
+            });
+            progress!(
+                format,
+                "🗂️  Archivos: {} | directorios: {} | SUID/SGID conocidos ({}): {} | sin permiso de lectura: {}\n",
+                stats.files_scanned,
+                stats.dirs_scanned,
+                stats.distro.as_deref().unwrap_or("distribución desconocida"),
+                stats.known_setid,
+                stats.files_denied
+            );
+            findings
+        }
+        Command::RulesCheck | Command::Preflight(_) | Command::Bench(_) => {
+            unreachable!("rules check, preflight y bench terminan antes de escanear")
+        }
+    };
+    if !streaming {
+        marked = enrich(
+            &mut findings,
+            &rule_set,
+            owners.as_ref(),
+            compromised_list,
+            baseline.as_ref(),
+        );
+    }
+    if let Some((_, loaded)) = &compromised {
+        progress!(
+            format,
+            "☠️  Lista de comprometidos: {} hashes cargados | {} hallazgos coinciden\n",
+            loaded,
+            marked
+        );
+    }
+
+    if let Some(path) = options.baseline.as_ref().filter(|_| options.update_baseline) {
+        let updated = Baseline::load_or_new(path).and_then(|mut baseline| {
+            let summary = baseline.update(&findings, &options.reason, options.expires.as_deref(), options.prune);
+            baseline.save(path)?;
+            progress!(
+                format,
+                "📝 Baseline {} actualizada: {} nuevas aceptaciones, {} conservadas, {} eliminadas\n",
+                path.display(),
+                summary.added,
+                summary.kept,
+                summary.removed
+            );
+            Ok(baseline)
+        });
+        let updated = updated.unwrap_or_else(|err| {
+            eprintln!("❌ Error de baseline: {}", err);
+            process::exit(EXIT_ERROR);
+        });
+        updated.classify(&mut findings);
+        baseline = Some(updated);
+    }
+
+    // El parche se aplica desde la ruta analizada; los modos del host usan rutas absolutas y se aplica desde /.
+    let patch_root = match &options.command {
+        Command::Scan(root) | Command::Manifest(root) => root.as_path(),
+        Command::Hook => Path::new("."),
+        _ => Path::new("/"),
+    };
+    let patch = Patch::build(&findings, patch_root);
+    if let Some(path) = &options.patch {
+        if patch.changes() == 0 {
+            progress!(
+                format,
+                "🩹 Ningún valor de .env o docker-compose que reemplazar: no se escribió {}\n",
+                path.display()
+            );
+        } else if let Err(err) = fs::write(path, patch.diff()) {
+            eprintln!("❌ No se pudo escribir el parche {}: {}", path.display(), err);
+            process::exit(EXIT_ERROR);
+        } else {
+            progress!(
+                format,
+                "🩹 Parche con {} reemplazo(s) en {} archivo(s) escrito en {} (git apply {} o patch -p1 < {})\n",
+                patch.changes(),
+                patch.files(),
+                path.display(),
+                path.display(),
+                path.display()
+            );
+        }
+    }
+
+    match format {
+        OutputFormat::Text if hook_mode => print_hook_report(&findings, options.fail_on, &rule_set),
+        OutputFormat::Text => print_text_report(
+            &findings,
+            baseline.as_ref(),
+            options.show_preview,
+            &rule_set,
+            &patch,
+            streaming,
+        ),
+        OutputFormat::Json => print!(
+            "{}",
+            report::json_report(
+                &findings,
+                baseline.as_ref(),
+                options.fail_on,
+                options.show_preview,
+                &rule_set
+            )
+            .to_pretty()
+        ),
+        OutputFormat::Sarif => {
+            let root = match &options.command {
+                Command::Scan(root) | Command::Git(root) | Command::Manifest(root) => Some(root.as_path()),
+                Command::Hook => Some(Path::new(".")),
+                _ => None,
+            };
+            print!(
+                "{}",
+                report::sarif_report(&findings, baseline.as_ref(), root, options.show_preview, &rule_set).to_pretty()
+            )
+        }
+    }
+    process::exit(if report::failing(&findings, options.fail_on) > 0 {
+        EXIT_FINDINGS
+    } else {
+        EXIT_CLEAN
+    });
+}
@@ -0,0 +1,796 @@
+// Papiweb desarrollos informaticos
+// Auditoría de cuentas: /etc/passwd, /etc/shadow (si se puede leer), /etc/group y sudoers con sus include. Cuentas
+// UID 0, contraseñas vacías o con hash débil, shells en cuentas de servicio, NOPASSWD: ALL y sudoers modificables.
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::finding::{AuditPriority, Finding, Location};
+use crate::hardening;
+
+const UID_ZERO_RULE: &str = "account-uid-zero";
+const EMPTY_PASSWORD_RULE: &str = "account-empty-password";
+const WEAK_HASH_RULE: &str = "account-weak-hash";
+const SERVICE_SHELL_RULE: &str = "service-account-shell";
+const NOPASSWD_RULE: &str = "sudo-nopasswd-all";
+const SUDOERS_WRITABLE_RULE: &str = "sudoers-writable";
+
+// UID_MIN de login.defs cuando no se puede leer: por debajo están las cuentas de sistema.
+const DEFAULT_UID_MIN: u32 = 1000;
+const NOBODY_UID: u32 = 65534;
+const NON_INTERACTIVE_SHELLS: &[&str] = &["nologin", "false", "sync", "shutdown", "halt"];
+// Corta ciclos de include entre archivos de sudoers.
+const MAX_INCLUDE_DEPTH: usize = 16;
+const SUDO_TAGS: &[&str] = &[
+    "NOPASSWD",
+    "PASSWD",
+    "NOEXEC",
+    "EXEC",
+    "SETENV",
+    "NOSETENV",
+    "LOG_INPUT",
+    "NOLOG_INPUT",
+    "LOG_OUTPUT",
+    "NOLOG_OUTPUT",
+    "MAIL",
+    "NOMAIL",
+    "FOLLOW",
+    "NOFOLLOW",
+    "INTERCEPT",
+    "NOINTERCEPT",
+];
+
+#[derive(Debug, Default)]
+pub struct AccountStats {
+    pub accounts: u64,
+    pub shadow_readable: bool,
+    pub sudoers_files: u64,
+    pub files_denied: u64,
+}
+
+pub fn rule_ids() -> Vec<String> {
+    [
+        UID_ZERO_RULE,
+        EMPTY_PASSWORD_RULE,
+        WEAK_HASH_RULE,
+        SERVICE_SHELL_RULE,
+        NOPASSWD_RULE,
+        SUDOERS_WRITABLE_RULE,
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+struct Account {
+    name: String,
+    // Campo de contraseña de passwd: "x" remite a shadow.
+    password: String,
+    uid: u32,
+    gid: u32,
+    shell: String,
+    line: usize,
+}
+
+struct Shadow {
+    hash: String,
+    line: usize,
+}
+
+// `root` permite auditar un sistema montado (imagen, chroot) en lugar del propio host.
+pub fn scan_accounts(root: &Path, stats: &mut AccountStats) -> Result<Vec<Finding>, String> {
+    let passwd_path = under(root, Path::new("/etc/passwd"));
+    let text = fs::read_to_string(&passwd_path).map_err(|e| format!("{}: {}", passwd_path.display(), e))?;
+    let accounts = parse_passwd(&text);
+    stats.accounts = accounts.len() as u64;
+
+    let shadow_path = under(root, Path::new("/etc/shadow"));
+    let shadow = match fs::read_to_string(&shadow_path) {
+        Ok(text) => {
+            stats.shadow_readable = true;
+            parse_shadow(&text)
+        }
+        Err(err) => {
+            if err.kind() == ErrorKind::PermissionDenied {
+                stats.files_denied += 1;
+            }
+            HashMap::new()
+        }
+    };
+    let uid_min = uid_min(root);
+
+    let mut findings = Vec::new();
+    for account in &accounts {
+        findings.extend(account_findings(account, &passwd_path, uid_min));
+        findings.extend(password_findings(account, &passwd_path, &shadow_path, &shadow));
+    }
+
+    let members = group_members(root, &accounts);
+    let mut sudoers = Sudoers {
+        root: root.to_path_buf(),
+        members: &members,
+        command_aliases: HashSet::new(),
+        findings: Vec::new(),
+        stats,
+    };
+    let main = under(root, Path::new("/etc/sudoers"));
+    if let Some(finding) = writable_finding(&main, &main, 1, "/etc/sudoers") {
+        sudoers.findings.push(finding);
+    }
+    sudoers.read(&main, 0);
+    findings.append(&mut sudoers.findings);
+    Ok(findings)
+}
+
+fn under(root: &Path, path: &Path) -> PathBuf {
+    root.join(path.strip_prefix("/").unwrap_or(path))
+}
+
+fn parse_passwd(text: &str) -> Vec<Account> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.starts_with('#'))
+        .filter_map(|(index, line)| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some(Account {
+                name: fields.first()?.to_string(),
+                password: fields.get(1)?.to_string(),
+                uid: fields.get(2)?.parse().ok()?,
+                gid: fields.get(3)?.parse().ok()?,
+                shell: fields.get(6).copied().unwrap_or_default().to_string(),
+                line: index + 1,
+            })
+        })
+        .collect()
+}
+
+fn parse_shadow(text: &str) -> HashMap<String, Shadow> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (name, rest) = line.split_once(':')?;
+            let hash = rest.split(':').next().unwrap_or_default();
+            Some((
+                name.to_string(),
+                Shadow {
+                    hash: hash.to_string(),
+                    line: index + 1,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn uid_min(root: &Path) -> u32 {
+    let Ok(text) = fs::read_to_string(under(root, Path::new("/etc/login.defs"))) else {
+        return DEFAULT_UID_MIN;
+    };
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("UID_MIN"))
+        .find_map(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_UID_MIN)
+}
+
+// Miembros de cada grupo: los listados en /etc/group y las cuentas que lo tienen como grupo primario.
+fn group_members(root: &Path, accounts: &[Account]) -> HashMap<String, Vec<String>> {
+    let mut members = HashMap::new();
+    let Ok(text) = fs::read_to_string(under(root, Path::new("/etc/group"))) else {
+        return members;
+    };
+    for line in text.lines().filter(|line| !line.starts_with('#')) {
+        let fields: Vec<&str> = line.split(':').collect();
+        let (Some(name), Some(gid)) = (fields.first(), fields.get(2).and_then(|g| g.parse::<u32>().ok())) else {
+            continue;
+        };
+        let mut list: Vec<String> = fields
+            .get(3)
+            .map(|m| m.split(',').filter(|m| !m.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+        for account in accounts.iter().filter(|a| a.gid == gid) {
+            if !list.contains(&account.name) {
+                list.push(account.name.clone());
+            }
+        }
+        members.insert(name.to_string(), list);
+    }
+    members
+}
+
+fn account_location(path: &Path, line: usize, name: &str) -> Location {
+    Location::File {
+        path: path.to_path_buf(),
+        line,
+        column: 1,
+        key: Some(format!("usuario {}", name)),
+    }
+}
+
+fn account_findings(account: &Account, passwd: &Path, uid_min: u32) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let location = || account_location(passwd, account.line, &account.name);
+    if account.uid == 0 && account.name != "root" {
+        findings.push(hardening::finding(
+            UID_ZERO_RULE,
+            AuditPriority::Critical,
+            location(),
+            &format!("{}:uid 0", account.name),
+            format!(
+                "{} tiene UID 0: es root con otro nombre, fuera de sudo y de los registros que buscan a root",
+                account.name
+            ),
+            format!(
+                "userdel {} o usermod -u UID_LIBRE {}; los accesos privilegiados van por sudo",
+                account.name, account.name
+            ),
+        ));
+    }
+    let service = account.uid != 0 && (account.uid < uid_min || account.uid == NOBODY_UID);
+    let shell = if account.shell.is_empty() {
+        "/bin/sh"
+    } else {
+        account.shell.as_str()
+    };
+    let interactive = !NON_INTERACTIVE_SHELLS
+        .iter()
+        .any(|s| shell.rsplit('/').next() == Some(s));
+    if service && interactive {
+        findings.push(hardening::finding(
+            SERVICE_SHELL_RULE,
+            AuditPriority::Medium,
+            location(),
+            &format!("{}:{}", account.name, shell),
+            format!(
+                "cuenta de servicio {} (UID {}) con shell interactiva {}: quien tome el servicio obtiene una sesión",
+                account.name, account.uid, shell
+            ),
+            format!("usermod -s /usr/sbin/nologin {}", account.name),
+        ));
+    }
+    findings
+}
+
+fn password_findings(
+    account: &Account,
+    passwd: &Path,
+    shadow_path: &Path,
+    shadow: &HashMap<String, Shadow>,
+) -> Vec<Finding> {
+    // El hash está en shadow salvo en sistemas sin shadow, donde sigue en el propio passwd.
+    let (hash, path, line) = match (account.password.as_str(), shadow.get(&account.name)) {
+        ("x", Some(entry)) => (entry.hash.as_str(), shadow_path, entry.line),
+        ("x", None) => return Vec::new(),
+        (hash, _) => (hash, passwd, account.line),
+    };
+    let location = account_location(path, line, &account.name);
+    if hash.is_empty() {
+        return vec![hardening::finding(
+            EMPTY_PASSWORD_RULE,
+            AuditPriority::Critical,
+            location,
+            &format!("{}:sin contraseña", account.name),
+            format!(
+                "{} no tiene contraseña: cualquiera entra con solo el nombre de usuario",
+                account.name
+            ),
+            format!(
+                "passwd -l {} (bloquearla) o asignarle una con passwd {}",
+                account.name, account.name
+            ),
+        )];
+    }
+    let locked = hash.starts_with('!') || hash.starts_with('*');
+    let Some(algorithm) = weak_hash(hash.trim_start_matches('!')) else {
+        return Vec::new();
+    };
+    let (priority, state) = if path == passwd && !locked {
+        (
+            AuditPriority::Critical,
+            "está en /etc/passwd, que cualquier usuario puede leer y crackear en minutos",
+        )
+    } else if locked {
+        (
+            AuditPriority::Low,
+            "bloqueada, el hash sigue siendo crackeable si shadow se filtra",
+        )
+    } else {
+        (AuditPriority::High, "crackeable en minutos si shadow se filtra")
+    };
+    vec![hardening::finding(
+        WEAK_HASH_RULE,
+        priority,
+        location,
+        &format!("{}:{}", account.name, algorithm),
+        format!("contraseña de {} con hash {} ({})", account.name, algorithm, state),
+        format!(
+            "passwd {} para regenerarla con ENCRYPT_METHOD SHA512 o YESCRYPT de /etc/login.defs",
+            account.name
+        ),
+    )]
+}
+
+// MD5-crypt ("$1$") y DES clásico (13 caracteres sin prefijo).
+fn weak_hash(hash: &str) -> Option<&'static str> {
+    if hash.starts_with("$1$") {
+        return Some("MD5");
+    }
+    let des = hash.len() == 13 && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '/');
+    des.then_some("DES")
+}
+
+struct Sudoers<'a> {
+    root: PathBuf,
+    members: &'a HashMap<String, Vec<String>>,
+    // Cmnd_Alias que incluyen ALL.
+    command_aliases: HashSet<String>,
+    findings: Vec<Finding>,
+    stats: &'a mut AccountStats,
+}
+
+impl Sudoers<'_> {
+    fn read(&mut self, path: &Path, depth: usize) {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                if err.kind() == ErrorKind::PermissionDenied {
+                    self.stats.files_denied += 1;
+                }
+                return;
+            }
+        };
+        self.stats.sudoers_files += 1;
+        for (line, entry) in logical_lines(&text) {
+            if let Some((directive, target)) = include(&entry) {
+                if depth < MAX_INCLUDE_DEPTH {
+                    self.include(path, line, directive, target, depth);
+                }
+                continue;
+            }
+            if entry.starts_with('#') {
+                continue;
+            }
+            self.entry(path, line, &entry);
+        }
+    }
+
+    // "@include archivo" / "@includedir dir" (o con #): rutas relativas al archivo que las incluye.
+    fn include(&mut self, parent: &Path, line: usize, directive: &str, target: &str, depth: usize) {
+        let target = Path::new(target);
+        let resolved = if target.is_absolute() {
+            under(&self.root, target)
+        } else {
+            parent.parent().unwrap_or(Path::new("/")).join(target)
+        };
+        let label = format!("{} {}", directive, target.display());
+        if let Some(finding) = writable_finding(&resolved, parent, line, &label) {
+            self.findings.push(finding);
+        }
+        if directive.ends_with("includedir") {
+            // sudo omite los archivos con punto o terminados en ~ (copias de editores, .dpkg-old...).
+            let mut files: Vec<PathBuf> = fs::read_dir(&resolved)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|entry| {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    !name.contains('.') && !name.ends_with('~')
+                })
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect();
+            files.sort();
+            for file in files {
+                if let Some(finding) = writable_finding(&file, &file, 1, &file.display().to_string()) {
+                    self.findings.push(finding);
+                }
+                self.read(&file, depth + 1);
+            }
+        } else {
+            self.read(&resolved, depth + 1);
+        }
+    }
+
+    fn entry(&mut self, path: &Path, line: usize, entry: &str) {
+        let (head, rest) = entry.split_once(char::is_whitespace).unwrap_or((entry, ""));
+        if head == "Cmnd_Alias" || head == "Cmd_Alias" {
+            // "Cmnd_Alias NOMBRE = cmd, cmd : OTRO = cmd"
+            for definition in rest.split(':') {
+                if let Some((name, commands)) = definition.split_once('=') {
+                    if commands.split(',').any(|c| c.trim() == "ALL") {
+                        self.command_aliases.insert(name.trim().to_string());
+                    }
+                }
+            }
+            return;
+        }
+        if head.ends_with("_Alias") {
+            return;
+        }
+        // Defaults!comando solo afecta a ese comando.
+        if head.starts_with("Defaults") && !head.contains('!') {
+            if rest.split(',').any(|option| option.trim() == "!authenticate") {
+                self.push_nopasswd(path, line, entry, head.split_once([':', '>', '@']).map(|(_, who)| who));
+            }
+            return;
+        }
+        let Some((left, commands)) = entry.split_once('=') else {
+            return;
+        };
+        // "usuarios hosts": la última palabra es la lista de hosts.
+        let users = left
+            .trim()
+            .rsplit_once(char::is_whitespace)
+            .map_or("", |(users, _)| users.trim());
+        if users.is_empty() {
+            return;
+        }
+        if self.grants_all_without_password(commands) {
+            self.push_nopasswd(path, line, entry, Some(users));
+        }
+    }
+
+    // Lista de comandos con sus "(runas)" y etiquetas: una etiqueta vale para los comandos siguientes hasta que otra
+    // la cambie.
+    fn grants_all_without_password(&self, commands: &str) -> bool {
+        let mut nopasswd = false;
+        for spec in split_top_level(commands) {
+            let mut spec = spec.trim();
+            if spec.starts_with('(') {
+                spec = spec.split_once(')').map_or("", |(_, rest)| rest.trim_start());
+            }
+            while let Some((tag, rest)) = spec.split_once(':') {
+                if !SUDO_TAGS.contains(&tag.trim()) {
+                    break;
+                }
+                match tag.trim() {
+                    "NOPASSWD" => nopasswd = true,
+                    "PASSWD" => nopasswd = false,
+                    _ => {}
+                }
+                spec = rest.trim_start();
+            }
+            if nopasswd && (spec == "ALL" || self.command_aliases.contains(spec)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn push_nopasswd(&mut self, path: &Path, line: usize, entry: &str, users: Option<&str>) {
+        let users = users.unwrap_or("ALL");
+        let groups: Vec<String> = users
+            .split(',')
+            .filter_map(|u| u.trim().strip_prefix('%'))
+            .map(|group| {
+                let members = self.members.get(group).map_or(String::new(), |m| m.join(", "));
+                format!(
+                    "%{}: {}",
+                    group,
+                    if members.is_empty() { "sin miembros" } else { &members }
+                )
+            })
+            .collect();
+        let who = if users == "ALL" { "cualquier usuario" } else { users };
+        let mut text = format!("{} puede ejecutar cualquier comando como root sin contraseña", who);
+        if !groups.is_empty() {
+            text.push_str(&format!(" ({})", groups.join("; ")));
+        }
+        let priority = if users == "ALL" {
+            AuditPriority::Critical
+        } else {
+            AuditPriority::High
+        };
+        self.findings.push(hardening::finding(
+            NOPASSWD_RULE,
+            priority,
+            Location::File {
+                path: path.to_path_buf(),
+                line,
+                column: 1,
+                key: Some(entry.to_string()),
+            },
+            entry,
+            text,
+            format!(
+                "visudo -f {}: quitar NOPASSWD de ALL y dejarlo solo en los comandos concretos que lo necesitan",
+                path.display()
+            ),
+        ));
+    }
+}
+
+// Líneas con las continuaciones ("\" al final) unidas y el número de la primera.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (index, raw) in text.lines().enumerate() {
+        let (continued, content) = match raw.strip_suffix('\\') {
+            Some(content) => (true, content),
+            None => (false, raw),
+        };
+        let (start, mut joined) = pending.take().unwrap_or((index + 1, String::new()));
+        joined.push_str(content.trim());
+        joined.push(' ');
+        if continued {
+            pending = Some((start, joined));
+        } else if !joined.trim().is_empty() {
+            lines.push((start, joined.trim().to_string()));
+        }
+    }
+    lines.extend(pending.map(|(start, joined)| (start, joined.trim().to_string())));
+    lines
+}
+
+fn include(entry: &str) -> Option<(&str, &str)> {
+    let (directive, target) = entry.split_once(char::is_whitespace)?;
+    matches!(directive, "#include" | "@include" | "#includedir" | "@includedir").then(|| (directive, target.trim()))
+}
+
+// Comas fuera de paréntesis: "(root, admin) NOPASSWD: ALL, /usr/bin/ls".
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&list[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts
+}
+
+// Quien pueda escribir en sudoers o en lo que incluye se da root a sí mismo. El hallazgo apunta a `declared_in`:
+// la línea del include para un directorio, el propio archivo para el resto.
+fn writable_finding(path: &Path, declared_in: &Path, line: usize, label: &str) -> Option<Finding> {
+    let metadata = fs::metadata(path).ok()?;
+    let mode = metadata.mode() & 0o7777;
+    let priority = if mode & 0o002 != 0 {
+        AuditPriority::Critical
+    } else if mode & 0o020 != 0 && metadata.gid() != 0 {
+        AuditPriority::High
+    } else {
+        return None;
+    };
+    let who = if priority == AuditPriority::Critical {
+        "cualquier usuario"
+    } else {
+        "el grupo"
+    };
+    let kind = if metadata.is_dir() { "directorio" } else { "archivo" };
+    let fixed_mode = if metadata.is_dir() { "0750" } else { "0440" };
+    let key = format!("{}: modo {:04o}", label, mode);
+    Some(hardening::finding(
+        SUDOERS_WRITABLE_RULE,
+        priority,
+        Location::File {
+            path: declared_in.to_path_buf(),
+            line,
+            column: 1,
+            key: Some(key.clone()),
+        },
+        &key,
+        format!(
+            "{} {} de sudoers modificable por {}: puede darse root a sí mismo",
+            kind,
+            path.display(),
+            who
+        ),
+        format!(
+            "chown root:root {} && chmod {} {}",
+            path.display(),
+            fixed_mode,
+            path.display()
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::{env, process};
+
+    const PASSWD: &str = "root:x:0:0:root:/root:/bin/bash
+toor:x:0:0::/root:/bin/sh
+respaldo:x:0:0::/root:/bin/bash
+daemon:x:1:1::/usr/sbin:/usr/sbin/nologin
+www-data:x:33:33::/var/www:/bin/bash
+postgres:x:110:117::/var/lib/postgresql:
+ana:x:1000:1000::/home/ana:/bin/bash
+invitado::1001:1001::/home/invitado:/bin/bash
+legado:Xy1.abcdefghi:1002:1002::/home/legado:/bin/bash
+nobody:x:65534:65534::/nonexistent:/usr/sbin/nologin
+";
+    const SHADOW: &str = "root:$6$sal$hash:19000:0:99999:7:::
+daemon:!$1$sal$hash:19000:0:99999:7:::
+www-data:*:19000:0:99999:7:::
+ana::19000:0:99999:7:::
+";
+    const SUDOERS: &str = "Defaults env_reset
+Cmnd_Alias TODO = ALL
+root ALL=(ALL:ALL) ALL
+%admin ALL=(ALL) NOPASSWD: ALL
+ana ALL=(ALL) NOPASSWD: /usr/bin/systemctl, PASSWD: ALL
+deploy ALL=(root) NOPASSWD: /usr/bin/apt, \\
+    TODO
+@includedir /etc/sudoers.d
+";
+
+    // Sistema de prueba montado en un directorio temporal, como con --root.
+    struct Root(PathBuf);
+
+    impl Root {
+        fn new(name: &str) -> Self {
+            let root = env::temp_dir().join(format!("auditorlocal-accounts-{}-{}", name, process::id()));
+            fs::create_dir_all(root.join("etc/sudoers.d")).unwrap();
+            Root(root)
+        }
+
+        fn write(&self, path: &str, text: &str, mode: u32) {
+            let path = self.0.join(path);
+            fs::write(&path, text).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        fn scan(&self, stats: &mut AccountStats) -> Vec<Finding> {
+            fs::set_permissions(self.0.join("etc/sudoers.d"), fs::Permissions::from_mode(0o755)).unwrap();
+            scan_accounts(&self.0, stats).unwrap()
+        }
+    }
+
+    impl Drop for Root {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // Regla, archivo, línea, clave y prioridad.
+    fn summary(findings: &[Finding], rule_id: &str) -> Vec<(String, usize, String, AuditPriority)> {
+        findings
+            .iter()
+            .filter(|finding| finding.rule_id == rule_id)
+            .map(|finding| match &finding.location {
+                Location::File { path, line, key, .. } => (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    *line,
+                    key.clone().unwrap(),
+                    finding.priority,
+                ),
+                other => panic!("ubicación inesperada: {}", other),
+            })
+            .collect()
+    }
+
+    fn row(file: &str, line: usize, key: &str, priority: AuditPriority) -> (String, usize, String, AuditPriority) {
+        (file.to_string(), line, key.to_string(), priority)
+    }
+
+    #[test]
+    fn passwd_and_shadow_findings() {
+        let root = Root::new("passwd");
+        root.write("etc/passwd", PASSWD, 0o644);
+        root.write("etc/shadow", SHADOW, 0o640);
+        let mut stats = AccountStats::default();
+        let findings = root.scan(&mut stats);
+        assert_eq!((stats.accounts, stats.shadow_readable), (10, true));
+
+        // Varias cuentas con UID 0 además de root: una por cada una.
+        assert_eq!(
+            summary(&findings, UID_ZERO_RULE),
+            [
+                row("passwd", 2, "usuario toor", AuditPriority::Critical),
+                row("passwd", 3, "usuario respaldo", AuditPriority::Critical),
+            ]
+        );
+        // Vacía en shadow o directamente en passwd.
+        assert_eq!(
+            summary(&findings, EMPTY_PASSWORD_RULE),
+            [
+                row("shadow", 4, "usuario ana", AuditPriority::Critical),
+                row("passwd", 8, "usuario invitado", AuditPriority::Critical),
+            ]
+        );
+        assert_eq!(
+            summary(&findings, WEAK_HASH_RULE),
+            [
+                row("shadow", 2, "usuario daemon", AuditPriority::Low),
+                row("passwd", 9, "usuario legado", AuditPriority::Critical),
+            ]
+        );
+        // Sin shell en passwd se usa /bin/sh; nologin y las cuentas normales no cuentan.
+        assert_eq!(
+            summary(&findings, SERVICE_SHELL_RULE),
+            [
+                row("passwd", 5, "usuario www-data", AuditPriority::Medium),
+                row("passwd", 6, "usuario postgres", AuditPriority::Medium),
+            ]
+        );
+    }
+
+    #[test]
+    fn uid_min_comes_from_login_defs() {
+        let root = Root::new("uidmin");
+        root.write("etc/passwd", PASSWD, 0o644);
+        root.write("etc/login.defs", "# UID_MIN 10\nUID_MIN\t\t 1001\n", 0o644);
+        let findings = root.scan(&mut AccountStats::default());
+        let service: Vec<String> = summary(&findings, SERVICE_SHELL_RULE)
+            .into_iter()
+            .map(|(_, _, key, _)| key)
+            .collect();
+        assert_eq!(service, ["usuario www-data", "usuario postgres", "usuario ana"]);
+    }
+
+    #[test]
+    fn sudoers_nopasswd_and_includes() {
+        let root = Root::new("sudoers");
+        root.write("etc/passwd", PASSWD, 0o644);
+        root.write("etc/group", "admin:x:27:luis\nana:x:1000:\n", 0o644);
+        root.write("etc/sudoers", SUDOERS, 0o440);
+        root.write(
+            "etc/sudoers.d/90-ci",
+            "Defaults:ci !authenticate\nALL ALL=(ALL) NOPASSWD: ALL\n",
+            0o440,
+        );
+        root.write("etc/sudoers.d/README.txt", "otro ALL=(ALL) NOPASSWD: ALL\n", 0o444);
+        root.write("etc/sudoers.d/99-abierto", "", 0o666);
+        let mut stats = AccountStats::default();
+        let findings = root.scan(&mut stats);
+        assert_eq!(stats.sudoers_files, 3);
+
+        assert_eq!(
+            summary(&findings, NOPASSWD_RULE),
+            [
+                row("sudoers", 4, "%admin ALL=(ALL) NOPASSWD: ALL", AuditPriority::High),
+                // La etiqueta sigue vigente tras la continuación y el alias incluye ALL.
+                row(
+                    "sudoers",
+                    6,
+                    "deploy ALL=(root) NOPASSWD: /usr/bin/apt, TODO",
+                    AuditPriority::High
+                ),
+                row("90-ci", 1, "Defaults:ci !authenticate", AuditPriority::High),
+                row("90-ci", 2, "ALL ALL=(ALL) NOPASSWD: ALL", AuditPriority::Critical),
+            ]
+        );
+        let admin = findings.iter().find(|f| f.rule_id == NOPASSWD_RULE).unwrap();
+        assert!(
+            admin.notes[0].text.contains("(%admin: luis)"),
+            "{}",
+            admin.notes[0].text
+        );
+
+        let writable = summary(&findings, SUDOERS_WRITABLE_RULE);
+        assert_eq!(writable.len(), 1);
+        assert_eq!(
+            (writable[0].0.as_str(), writable[0].3),
+            ("99-abierto", AuditPriority::Critical)
+        );
+    }
+
+    #[test]
+    fn sudo_command_lists() {
+        let members = HashMap::new();
+        let mut stats = AccountStats::default();
+        let sudoers = Sudoers {
+            root: PathBuf::from("/"),
+            members: &members,
+            command_aliases: HashSet::from(["TODO".to_string()]),
+            findings: Vec::new(),
+            stats: &mut stats,
+        };
+        let grants = |commands: &str| sudoers.grants_all_without_password(commands);
+        assert!(grants("(ALL) NOPASSWD: ALL"));
+        assert!(grants("(root, admin) NOPASSWD:SETENV: /bin/ls, ALL"));
+        assert!(grants("NOPASSWD: TODO"));
+        assert!(!grants("(ALL) ALL"));
+        assert!(!grants("NOPASSWD: /usr/bin/systemctl, PASSWD: ALL"));
+        assert!(!grants("NOPASSWD: /usr/bin/ALL"));
+    }
+}
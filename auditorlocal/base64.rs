@@ -0,0 +1,70 @@
+// Papiweb desarrollos informaticos
+// Decodificación base64 (alfabeto estándar y URL-safe, con o sin relleno).
+
+fn sextet(byte: u8) -> Option<u32> {
+    match byte {
+        b'A'..=b'Z' => Some((byte - b'A') as u32),
+        b'a'..=b'z' => Some((byte - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((byte - b'0') as u32 + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+pub fn decode(text: &str) -> Option<Vec<u8>> {
+    let input: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    let input = input
+        .strip_suffix(b"==")
+        .or_else(|| input.strip_suffix(b"="))
+        .unwrap_or(&input);
+    if input.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut acc = 0u32;
+        for &byte in chunk {
+            acc = (acc << 6) | sextet(byte)?;
+        }
+        acc <<= 6 * (4 - chunk.len() as u32);
+        let bytes = acc.to_be_bytes();
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padding_and_alphabets() {
+        // RFC 4648: con relleno, sin él (como en JWT) y con saltos de línea de PEM.
+        let cases = [
+            ("", ""),
+            ("Zg==", "f"),
+            ("Zg", "f"),
+            ("Zm8=", "fo"),
+            ("Zm8", "fo"),
+            ("Zm9v", "foo"),
+            ("Zm9vYg==", "foob"),
+            ("Zm9vYmE", "fooba"),
+            ("Zm9v\nYmFy\n", "foobar"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(decode(text).as_deref(), Some(expected.as_bytes()), "{:?}", text);
+        }
+        // Los mismos bytes en el alfabeto estándar y en el URL-safe.
+        assert_eq!(decode("+/+/"), Some(vec![0xfb, 0xff, 0xbf]));
+        assert_eq!(decode("-_-_"), Some(vec![0xfb, 0xff, 0xbf]));
+        assert_eq!(decode("_-8"), Some(vec![0xff, 0xef]));
+    }
+
+    #[test]
+    fn invalid_input() {
+        for text in ["Z", "Zm9vY", "Zm9vY===", "Zg=a", "Z=g=", "Zm9v!", "ñañañaña"] {
+            assert_eq!(decode(text), None, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Papiweb desarrollos informaticos
+// Banco de pruebas del escáner de archivos: genera un árbol sintético (código, .env, YAML y JSON con un secreto
+// por archivo) y mide el rendimiento con un hilo y con todos. El árbol se borra al terminar.
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+use crate::filescan::{FileScanner, ScanStats};
+
+const FILE_SIZE: usize = 64 * 1024;
+const FILES_PER_DIR: usize = 32;
+const EXTENSIONS: &[&str] = &["rs", "py", "js", "env", "yaml", "json"];
+
+pub fn run(scanner: &FileScanner, size_mib: u64, threads: usize) -> Result<(), String> {
+    let root = env::temp_dir().join(format!("auditorlocal-bench-{}", process::id()));
+    let files = (size_mib as usize * 1024 * 1024).div_ceil(FILE_SIZE).max(1);
+    println!(
+        "🏁 Generando árbol sintético en {}: {} archivos de {} KiB...",
+        root.display(),
+        files,
+        FILE_SIZE / 1024
+    );
+    let result = generate(&root, files).and_then(|()| measure(scanner, &root, files, threads));
+    let _ = fs::remove_dir_all(&root);
+    result
+}
+
+fn measure(scanner: &FileScanner, root: &Path, files: usize, threads: usize) -> Result<(), String> {
+    let mut runs = vec![1];
+    if threads > 1 {
+        runs.push(threads);
+    }
+    let mut baseline = None;
+    for threads in runs {
+        let mut stats = ScanStats::default();
+        let mut findings = 0;
+        let start = Instant::now();
+        scanner.scan_tree(root, threads, &mut stats, |found| findings += found.len())?;
+        let seconds = start.elapsed().as_secs_f64().max(f64::EPSILON);
+        let throughput = stats.bytes_scanned as f64 / 1e6 / seconds;
+        let speedup = baseline.map_or(String::new(), |single: f64| {
+            format!(" — x{:.1} frente a 1 hilo", throughput / single)
+        });
+        baseline.get_or_insert(throughput);
+        println!(
+            "   {:>3} hilo(s): {:>8.1} MB/s | {:.0} archivos/s | {:.2} s | {} hallazgos{}",
+            threads,
+            throughput,
+            stats.files_scanned as f64 / seconds,
+            seconds,
+            findings,
+            speedup
+        );
+        if findings != files {
+            return Err(format!(
+                "se esperaba un hallazgo por archivo ({}) y hubo {}",
+                files, findings
+            ));
+        }
+    }
+    println!("   (lectura desde la caché de páginas: el árbol se acaba de escribir)");
+    Ok(())
+}
+
+fn generate(root: &Path, files: usize) -> Result<(), String> {
+    for index in 0..files {
+        let dir = root
+            .join(format!("pkg{:03}", index / (FILES_PER_DIR * 8)))
+            .join(format!("mod{:03}", index / FILES_PER_DIR));
+        fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let extension = EXTENSIONS[index % EXTENSIONS.len()];
+        let path = dir.join(format!("file{:05}.{}", index, extension));
+        fs::write(&path, content(index, extension)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+// Líneas sin secretos hasta FILE_SIZE y una clave de Stripe de prueba distinta en cada archivo.
+fn content(index: usize, extension: &str) -> String {
+    let mut text = String::with_capacity(FILE_SIZE + 128);
+    let secret = format!("sk_live_{:024x}", (index as u128 + 1) * 0x9e37_79b9_7f4a_7c15);
+    text.push_str(&match extension {
+        "env" => format!("STRIPE_KEY={}\n", secret),
+        "yaml" => format!("stripe:\n  key: {}\n", secret),
+        "json" => format!("{{\"stripe_key\": \"{}\",\n", secret),
+        _ => format!("const STRIPE = \"{}\";\n", secret),
+    });
+    let mut line = 0;
+    while text.len() < FILE_SIZE {
+        text.push_str(&match extension {
+            "env" => format!(
+                "# ajuste {} del servicio de pruebas\nAPP_PORT={}\n",
+                line,
+                1000 + line % 97
+            ),
+            "yaml" => format!("  item_{}: valor de prueba {}\n", line, line % 97),
+            "json" => format!("\"item_{}\": {},\n", line, line % 97),
+            _ => format!("    let value_{} = compute({}, \"texto {}\");\n", line, line % 97, line),
+        });
+        line += 1;
+    }
+    text
+}
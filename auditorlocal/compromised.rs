@@ -0,0 +1,81 @@
+// Papiweb desarrollos informaticos
+// Lista local de secretos filtrados o revocados, como hashes SHA-256 (nunca en claro).
+// Una línea por entrada: hash completo de 64 hex, o un prefijo (k-anonimato) de al menos 5 hex.
+// Acepta la salida de `printf %s VALOR | sha256sum` y el formato HASH:CUENTA. Todo es local, sin red.
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::finding::{AuditPriority, Compromise, Confidence, Finding};
+use crate::sha256;
+
+const MIN_PREFIX_LEN: usize = 5;
+const HASH_LEN: usize = 64;
+
+#[derive(Debug, Default)]
+pub struct CompromisedList {
+    exact: HashSet<String>,
+    // Prefijos agrupados por longitud para buscar con un único corte del hash.
+    prefixes: BTreeMap<usize, HashSet<String>>,
+}
+
+impl CompromisedList {
+    pub fn load(&mut self, path: &Path) -> Result<usize, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("no se pudo leer {}: {}", path.display(), e))?;
+        let mut loaded = 0;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let hash = line
+                .split(|c: char| c.is_whitespace() || c == ':')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if hash.len() < MIN_PREFIX_LEN || hash.len() > HASH_LEN || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(format!(
+                    "{}:{}: se esperaba un SHA-256 en hexadecimal o un prefijo de al menos {} caracteres",
+                    path.display(),
+                    index + 1,
+                    MIN_PREFIX_LEN
+                ));
+            }
+            if hash.len() == HASH_LEN {
+                self.exact.insert(hash);
+            } else {
+                self.prefixes.entry(hash.len()).or_default().insert(hash);
+            }
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    pub fn lookup(&self, value: &str) -> Option<Compromise> {
+        let hash = sha256::hex_digest(value.as_bytes());
+        if self.exact.contains(&hash) {
+            return Some(Compromise::Exact);
+        }
+        self.prefixes
+            .iter()
+            .any(|(len, set)| set.contains(&hash[..*len]))
+            .then_some(Compromise::Prefix)
+    }
+
+    // Marca los hallazgos cuyo valor está en la lista y eleva su severidad.
+    pub fn mark(&self, findings: &mut [Finding]) -> usize {
+        let mut marked = 0;
+        for finding in findings {
+            let Some(compromise) = self.lookup(finding.secret.expose()) else {
+                continue;
+            };
+            finding.compromised = Some(compromise);
+            finding.priority = AuditPriority::Critical;
+            if compromise == Compromise::Exact {
+                finding.confidence = Confidence::High;
+            }
+            marked += 1;
+        }
+        marked
+    }
+}
@@ -0,0 +1,69 @@
+// Papiweb desarrollos informaticos
+// Fechas civiles (UTC) sin dependencias: conversión entre días desde 1970 y AAAA-MM-DD.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Algoritmo civil_from_days de Howard Hinnant.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+pub fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn today() -> i64 {
+    now_unix().div_euclid(86_400)
+}
+
+pub fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// "AAAA-MM-DD" -> días desde 1970-01-01.
+pub fn parse_date(text: &str) -> Option<i64> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok().filter(|m| (1..=12).contains(m))?;
+    let day = parts.next()?.parse().ok().filter(|d| (1..=31).contains(d))?;
+    Some(days_from_civil(year, month, day))
+}
+
+// Fecha y hora "AAAA-MM-DD HH:MM:SS ±HHMM" a partir de un timestamp unix y su zona horaria.
+pub fn format_timestamp(timestamp: i64, tz_offset_minutes: i32) -> String {
+    let local = timestamp + tz_offset_minutes as i64 * 60;
+    let secs = local.rem_euclid(86_400);
+    let sign = if tz_offset_minutes < 0 { '-' } else { '+' };
+    let tz = tz_offset_minutes.abs();
+    format!(
+        "{} {:02}:{:02}:{:02} {}{:02}{:02}",
+        format_date(local.div_euclid(86_400)),
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+        sign,
+        tz / 60,
+        tz % 60
+    )
+}
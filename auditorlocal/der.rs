@@ -0,0 +1,184 @@
+// Papiweb desarrollos informaticos
+// Lector DER (ASN.1) mínimo para claves, certificados y almacenes PKCS#12: elementos TLV con longitud definida.
+// No valida esquemas: quien lo usa recorre la estructura que espera y abandona si no coincide.
+
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OID: u8 = 0x06;
+pub const SEQUENCE: u8 = 0x30;
+// Etiquetas de contexto construidas [0] y [1].
+pub const CONTEXT_0: u8 = 0xa0;
+pub const CONTEXT_1: u8 = 0xa1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Der<'a> {
+    pub tag: u8,
+    pub body: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    // Primer elemento de `data` y lo que queda detrás.
+    pub fn parse(data: &'a [u8]) -> Option<(Der<'a>, &'a [u8])> {
+        let (&tag, rest) = data.split_first()?;
+        let (&first, mut rest) = rest.split_first()?;
+        let len = if first & 0x80 == 0 {
+            usize::from(first)
+        } else {
+            // Forma larga; la longitud indefinida (0x80) es BER y no se admite.
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > 4 || rest.len() < count {
+                return None;
+            }
+            let len = rest[..count].iter().fold(0usize, |n, &b| n << 8 | usize::from(b));
+            rest = &rest[count..];
+            len
+        };
+        if rest.len() < len {
+            return None;
+        }
+        Some((
+            Der {
+                tag,
+                body: &rest[..len],
+            },
+            &rest[len..],
+        ))
+    }
+
+    pub fn decode(data: &'a [u8]) -> Option<Der<'a>> {
+        Der::parse(data).map(|(der, _)| der)
+    }
+
+    // Elementos contenidos (SEQUENCE, SET o etiqueta de contexto construida).
+    pub fn children(&self) -> Vec<Der<'a>> {
+        let mut children = Vec::new();
+        let mut rest = self.body;
+        while let Some((child, next)) = Der::parse(rest) {
+            children.push(child);
+            rest = next;
+        }
+        children
+    }
+
+    pub fn is(&self, tag: u8) -> bool {
+        self.tag == tag
+    }
+
+    // Identificador de objeto en notación con puntos ("1.2.840.113549.1.1.1").
+    pub fn oid(&self) -> Option<String> {
+        if self.tag != OID || self.body.is_empty() {
+            return None;
+        }
+        let first = self.body[0];
+        let mut parts = vec![u64::from(first / 40), u64::from(first % 40)];
+        let mut value = 0u64;
+        for &byte in &self.body[1..] {
+            value = value << 7 | u64::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                parts.push(value);
+                value = 0;
+            }
+        }
+        Some(parts.iter().map(u64::to_string).collect::<Vec<_>>().join("."))
+    }
+
+    // Entero sin signo sin los ceros de relleno (módulo RSA, primo DSA...).
+    pub fn unsigned(&self) -> &'a [u8] {
+        let start = self.body.iter().position(|&b| b != 0).unwrap_or(self.body.len());
+        &self.body[start..]
+    }
+
+    pub fn bits(&self) -> usize {
+        bit_length(self.unsigned())
+    }
+
+    // Contenido de un BIT STRING sin el byte de bits sobrantes.
+    pub fn bit_string(&self) -> Option<&'a [u8]> {
+        if self.tag != BIT_STRING {
+            return None;
+        }
+        self.body.get(1..)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(self.body).to_string()
+    }
+}
+
+// Bits significativos de un entero big-endian sin ceros iniciales.
+pub fn bit_length(bytes: &[u8]) -> usize {
+    match bytes.first() {
+        Some(&first) => (bytes.len() - 1) * 8 + (8 - first.leading_zeros() as usize),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_and_long_lengths() {
+        let (der, rest) = Der::parse(&[INTEGER, 0x01, 0x05, 0xff]).unwrap();
+        assert_eq!((der.tag, der.body, rest), (INTEGER, &[0x05][..], &[0xff][..]));
+        let mut long = vec![OCTET_STRING, 0x81, 0x80];
+        long.extend([0x11; 0x80]);
+        assert_eq!(Der::decode(&long).unwrap().body.len(), 0x80);
+        let sequence = [SEQUENCE, 0x06, INTEGER, 0x01, 0x00, INTEGER, 0x01, 0x2a];
+        let children = Der::decode(&sequence).unwrap().children();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[1].body, [0x2a]);
+    }
+
+    #[test]
+    fn truncated_and_malformed_elements_are_rejected() {
+        let cases: [&[u8]; 8] = [
+            &[],
+            &[SEQUENCE],
+            &[SEQUENCE, 0x03, 0x02, 0x01],
+            // Longitud indefinida (BER) y forma larga de más de 4 bytes.
+            &[SEQUENCE, 0x80, 0x00, 0x00],
+            &[SEQUENCE, 0x85, 0x01, 0x00, 0x00, 0x00, 0x00],
+            &[SEQUENCE, 0x82, 0x01],
+            &[SEQUENCE, 0x84, 0xff, 0xff, 0xff, 0xff, 0x00],
+            &[OCTET_STRING, 0x81, 0x80, 0x00],
+        ];
+        for data in cases {
+            assert!(Der::parse(data).is_none(), "{:02x?}", data);
+        }
+        // Un hijo truncado corta la lista sin descartar los anteriores.
+        let sequence = [SEQUENCE, 0x05, INTEGER, 0x01, 0x07, INTEGER, 0x09];
+        assert_eq!(Der::decode(&sequence).unwrap().children().len(), 1);
+    }
+
+    #[test]
+    fn oids_and_integers() {
+        let rsa = [OID, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+        assert_eq!(
+            Der::decode(&rsa).unwrap().oid().as_deref(),
+            Some("1.2.840.113549.1.1.1")
+        );
+        assert_eq!(
+            Der::decode(&[OID, 0x03, 0x2b, 0x65, 0x70]).unwrap().oid().as_deref(),
+            Some("1.3.101.112")
+        );
+        assert_eq!(Der::decode(&[OID, 0x00]).unwrap().oid(), None);
+        assert_eq!(Der::decode(&[INTEGER, 0x01, 0x2a]).unwrap().oid(), None);
+        // Arcos desmesurados o sin terminar: sin pánico.
+        let mut huge = vec![OID, 0x14, 0x2a];
+        huge.extend([0xff; 19]);
+        assert!(Der::decode(&huge).unwrap().oid().is_some());
+
+        let modulus = Der::decode(&[INTEGER, 0x04, 0x00, 0x00, 0x80, 0x01]).unwrap();
+        assert_eq!(modulus.unsigned(), [0x80, 0x01]);
+        assert_eq!(modulus.bits(), 16);
+        assert_eq!(Der::decode(&[INTEGER, 0x02, 0x00, 0x00]).unwrap().bits(), 0);
+        assert_eq!(bit_length(&[0x01, 0x00]), 9);
+
+        let bits = Der::decode(&[BIT_STRING, 0x03, 0x00, 0x04, 0x05]).unwrap();
+        assert_eq!(bits.bit_string(), Some(&[0x04, 0x05][..]));
+        assert_eq!(Der::decode(&[BIT_STRING, 0x00]).unwrap().bit_string(), None);
+        assert_eq!(modulus.bit_string(), None);
+    }
+}
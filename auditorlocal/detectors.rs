@@ -0,0 +1,331 @@
+// Papiweb desarrollos informaticos
+// Detectores de secretos: por nombre de variable y por contenido del valor.
+use crate::finding::{AuditPriority, BaselineStatus, Confidence, Finding, Location, Secret, SecretKind};
+use crate::names::NameMatcher;
+use crate::pattern::Pattern;
+use crate::rules::{Entropy, Rule, RuleSet};
+
+// Las coincidencias hexadecimales más cortas suelen ser ids o hashes abreviados, no secretos.
+const ENTROPY_MIN_HEX_LEN: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct Detection {
+    pub rule_id: String,
+    pub kind: SecretKind,
+    pub priority: AuditPriority,
+    pub confidence: Confidence,
+    // Rango en bytes del secreto dentro del valor analizado.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Detection {
+    // `value` es el texto analizado del que salió la detección (start..end).
+    pub fn into_finding(self, value: &str, location: Location) -> Finding {
+        Finding {
+            secret: Secret::new(&value[self.start..self.end]),
+            rule_id: self.rule_id,
+            kind: self.kind,
+            priority: self.priority,
+            confidence: self.confidence,
+            location,
+            status: BaselineStatus::New,
+            compromised: None,
+            notes: Vec::new(),
+        }
+    }
+}
+
+pub struct Detector {
+    names: NameMatcher,
+    rules: Vec<Rule>,
+    // Reglas con motor propio: (severidad, confianza), o None si están desactivadas.
+    sensitive_name: Option<(AuditPriority, Confidence)>,
+    high_entropy: Option<AuditPriority>,
+    command_line: Option<(AuditPriority, Confidence)>,
+    entropy: Entropy,
+    entropy_candidate: Pattern,
+}
+
+impl Detector {
+    pub fn new(rules: &RuleSet, names: NameMatcher) -> Self {
+        let engine = |id: &str| rules.active().find(|r| r.id == id).map(|r| (r.severity, r.confidence));
+        Self {
+            names,
+            rules: rules.active().filter(|r| !r.is_engine()).cloned().collect(),
+            sensitive_name: engine("sensitive-name"),
+            high_entropy: engine("high-entropy-string").map(|(severity, _)| severity),
+            command_line: engine("cmdline-password"),
+            entropy: rules.entropy,
+            // Candidatos para la regla de entropía: secuencias largas de caracteres tipo base64/hex.
+            entropy_candidate: Pattern::new(&format!("[A-Za-z0-9+/=_-]{{{},}}", rules.entropy.min_length))
+                .expect("patrón interno inválido"),
+        }
+    }
+
+    pub fn is_sensitive_name(&self, name: &str) -> bool {
+        self.names.is_sensitive(name)
+    }
+
+    // Severidad y confianza de "cmdline-password", o None si la regla está desactivada.
+    pub fn command_line_rule(&self) -> Option<(AuditPriority, Confidence)> {
+        self.command_line
+    }
+
+    // Analiza un par nombre/valor: reglas de nombre, palabra clave en el nombre y contenido del valor.
+    pub fn scan_pair(&self, name: &str, value: &str) -> Vec<Detection> {
+        let mut detections = self.scan(Some(name), value);
+        let sensitive = self.names.is_sensitive(name);
+        if sensitive {
+            // Nombre sensible + valor de alta entropía: evidencia mucho más fuerte.
+            for detection in detections.iter_mut().filter(|d| d.kind == SecretKind::HighEntropy) {
+                detection.priority = AuditPriority::High;
+                detection.confidence = Confidence::High;
+            }
+        }
+        if let Some((priority, confidence)) = self.sensitive_name.filter(|_| sensitive) {
+            if detections.is_empty() && !is_trivial_value(value) {
+                detections.push(Detection {
+                    rule_id: "sensitive-name".to_string(),
+                    kind: SecretKind::SensitiveName,
+                    priority,
+                    confidence,
+                    start: 0,
+                    end: value.len(),
+                });
+            }
+        }
+        detections
+    }
+
+    // Analiza solo el contenido: formatos conocidos primero, luego entropía.
+    pub fn scan_value(&self, value: &str) -> Vec<Detection> {
+        self.scan(None, value)
+    }
+
+    fn scan(&self, name: Option<&str>, value: &str) -> Vec<Detection> {
+        let mut detections: Vec<Detection> = Vec::new();
+        for rule in &self.rules {
+            // Las reglas con name_pattern solo aplican a pares cuyo nombre coincide; sin pattern,
+            // cualquier valor no trivial de esa variable es el secreto.
+            let spans = match (&rule.name_pattern, &rule.pattern) {
+                (Some(name_pattern), _) if !name.is_some_and(|n| name_pattern.is_match(n)) => continue,
+                (_, Some(pattern)) => pattern.find_all(value),
+                (_, None) if is_trivial_value(value) => continue,
+                (_, None) => vec![(0, value.len())],
+            };
+            for (start, end) in spans {
+                let (start, end) = if rule.kind == SecretKind::UrlCredentials {
+                    url_password_span(value, start, end)
+                } else {
+                    (start, end)
+                };
+                // Los rangos vienen de reglas de packs del usuario: uno que no cae en límites de carácter se descarta
+                // en lugar de tumbar la auditoría.
+                let Some(span) = value.get(start..end) else {
+                    continue;
+                };
+                if is_placeholder(span) || overlaps(&detections, start, end) {
+                    continue;
+                }
+                if rule.min_entropy.is_some_and(|min| shannon_entropy(span) < min) {
+                    continue;
+                }
+                detections.push(Detection {
+                    rule_id: rule.id.clone(),
+                    kind: rule.kind,
+                    priority: rule.severity,
+                    confidence: rule.confidence,
+                    start,
+                    end,
+                });
+            }
+        }
+
+        if let Some(priority) = self.high_entropy {
+            for (start, end) in self.entropy_candidate.find_all(value) {
+                if overlaps(&detections, start, end) {
+                    continue;
+                }
+                if let Some(confidence) = entropy_confidence(&value[start..end], &self.entropy) {
+                    detections.push(Detection {
+                        rule_id: "high-entropy-string".to_string(),
+                        kind: SecretKind::HighEntropy,
+                        priority: if confidence == Confidence::Low {
+                            AuditPriority::Low
+                        } else {
+                            priority
+                        },
+                        confidence,
+                        start,
+                        end,
+                    });
+                }
+            }
+        }
+
+        detections.sort_by_key(|d| d.start);
+        detections
+    }
+}
+
+// Entropía de Shannon en bits por carácter.
+pub fn shannon_entropy(text: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in text.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = text.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn entropy_confidence(candidate: &str, settings: &Entropy) -> Option<Confidence> {
+    let has_digit = candidate.bytes().any(|b| b.is_ascii_digit());
+    let has_alpha = candidate.bytes().any(|b| b.is_ascii_alphabetic());
+    if !has_digit || !has_alpha {
+        return None;
+    }
+    let entropy = shannon_entropy(candidate);
+    let is_hex = candidate.bytes().all(|b| b.is_ascii_hexdigit());
+    let threshold = if is_hex {
+        if candidate.len() < ENTROPY_MIN_HEX_LEN {
+            return None;
+        }
+        settings.hex_threshold
+    } else {
+        settings.base64_threshold
+    };
+    if entropy < threshold {
+        None
+    } else if entropy >= threshold + 0.5 {
+        Some(Confidence::Medium)
+    } else {
+        Some(Confidence::Low)
+    }
+}
+
+// En una URL "esquema://usuario:<clave>@host" el secreto es solo la clave.
+fn url_password_span(value: &str, start: usize, end: usize) -> (usize, usize) {
+    let url = &value[start..end];
+    let Some(authority) = url.find("://").map(|i| i + 3) else {
+        return (start, end);
+    };
+    match (url[authority..].find(':'), url.rfind('@')) {
+        (Some(colon), Some(at)) if authority + colon < at => (start + authority + colon + 1, start + at),
+        _ => (start, end),
+    }
+}
+
+// Valores que no pueden ser un secreto aunque el nombre sea sensible (TOKEN_TTL=3600, AUTH_ENABLED=true,
+// textos descriptivos con espacios).
+pub fn is_trivial_value(value: &str) -> bool {
+    let value = value.trim();
+    value.is_empty()
+        || is_placeholder(value)
+        || value.contains(' ')
+        || value.bytes().all(|b| b.is_ascii_digit())
+        || ["true", "false", "yes", "no", "on", "off", "null", "~"].contains(&value.to_lowercase().as_str())
+}
+
+// Valores de ejemplo o referencias a variables no son secretos reales.
+fn is_placeholder(secret: &str) -> bool {
+    secret.starts_with('$')
+        || secret.starts_with('<')
+        || secret.starts_with('{')
+        || secret.bytes().all(|b| b == b'*' || b == b'x' || b == b'X')
+}
+
+fn overlaps(detections: &[Detection], start: usize, end: usize) -> bool {
+    detections.iter().any(|d| start < d.end && d.start < end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(extra: Option<Rule>) -> Detector {
+        let mut rules = RuleSet::builtin();
+        rules.rules.extend(extra);
+        let names = NameMatcher::new(&rules.keywords, &rules.benign);
+        Detector::new(&rules, names)
+    }
+
+    fn rule_ids(detections: &[Detection]) -> Vec<&str> {
+        detections.iter().map(|d| d.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn placeholders_and_trivial_values_are_ignored() {
+        let detector = detector(None);
+        for value in [
+            "${API_TOKEN}",
+            "<tu-token>",
+            "{{ token }}",
+            "xxxxxxxx",
+            "********",
+            "true",
+            "3600",
+            "",
+        ] {
+            assert!(detector.scan_pair("API_TOKEN", value).is_empty(), "{:?}", value);
+        }
+        let token = format!("ghp_{}", "a1B2c3D4e5".repeat(4));
+        assert_eq!(rule_ids(&detector.scan_value(&token)), ["github-token"]);
+        assert!(detector.scan_value("ghp_$GITHUB_TOKEN").is_empty());
+    }
+
+    #[test]
+    fn sensitive_name_needs_a_non_trivial_value() {
+        let detector = detector(None);
+        assert_eq!(
+            rule_ids(&detector.scan_pair("DB_PASSWORD", "hunter2secret")),
+            ["sensitive-name"]
+        );
+        assert!(detector.scan_pair("DB_PASSWORD", "changeme later").is_empty());
+        assert!(detector.scan_pair("KEYBOARD_LAYOUT", "hunter2secret").is_empty());
+    }
+
+    #[test]
+    fn entropy_edge_cases() {
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert_eq!(shannon_entropy("abab"), 1.0);
+        let settings = Entropy::default();
+        // Hexadecimal corto: ids o hashes abreviados.
+        assert!(entropy_confidence("3f9a1c7e5b2d4f6a8c0e", &settings).is_none());
+        // Sin dígitos o sin letras no es un candidato.
+        assert!(entropy_confidence("abcdefghijklmnopqrstuvwxyzABCD", &settings).is_none());
+        assert!(entropy_confidence("12345678901234567890123", &settings).is_none());
+        assert!(entropy_confidence("Zq8vK2mN4pR7tX1wY5sB9cD3fG6hJ0L", &settings).is_some());
+        let detector = detector(None);
+        let detections = detector.scan_value("k: Zq8vK2mN4pR7tX1wY5sB9cD3fG6hJ0L");
+        assert_eq!(rule_ids(&detections), ["high-entropy-string"]);
+        assert_eq!((detections[0].start, detections[0].end), (3, 34));
+    }
+
+    // Regresión: una regla de pack con '.' sobre texto no ASCII tumbaba la auditoría al cortar el valor.
+    #[test]
+    fn custom_rule_spans_respect_utf8() {
+        let detector = detector(Some(Rule {
+            id: "corp-token".to_string(),
+            kind: SecretKind::Custom,
+            pattern: Some(Pattern::new("corp_.{3}").unwrap()),
+            name_pattern: None,
+            min_entropy: None,
+            severity: AuditPriority::High,
+            confidence: Confidence::High,
+            description: "token corporativo".to_string(),
+            remediation: None,
+        }));
+        let value = "X=corp_abñcdef";
+        let detections = detector.scan_value(value);
+        assert_eq!(rule_ids(&detections), ["corp-token"]);
+        assert_eq!(&value[detections[0].start..detections[0].end], "corp_abñ");
+    }
+}
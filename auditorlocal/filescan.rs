@@ -0,0 +1,353 @@
+// Papiweb desarrollos informaticos
+// Escaneo recursivo de archivos: .env, configuración y código fuente.
+// El árbol se recorre en paralelo con robo de trabajo y los formatos línea a línea se leen por bloques.
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::detectors::Detector;
+use crate::finding::{Finding, Location};
+use crate::formats::{Format, Parsers};
+use crate::gitignore::IgnoreStack;
+
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+// Memoria por archivo en los formatos línea a línea, sea cual sea su tamaño.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+// Archivos generados con hashes de integridad: solo producen falsos positivos de entropía.
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.sum",
+    "poetry.lock",
+    "composer.lock",
+    "Gemfile.lock",
+];
+
+#[derive(Debug, Default)]
+pub struct ScanStats {
+    pub files_scanned: u64,
+    pub bytes_scanned: u64,
+    pub skipped_binary: u64,
+    pub skipped_large: u64,
+    pub skipped_ignored: u64,
+    pub commits_scanned: u64,
+}
+
+impl ScanStats {
+    fn add(&mut self, other: &ScanStats) {
+        self.files_scanned += other.files_scanned;
+        self.bytes_scanned += other.bytes_scanned;
+        self.skipped_binary += other.skipped_binary;
+        self.skipped_large += other.skipped_large;
+        self.skipped_ignored += other.skipped_ignored;
+        self.commits_scanned += other.commits_scanned;
+    }
+}
+
+// Trabajo del recorrido: un directorio con los .gitignore que le aplican, o un archivo a analizar.
+enum Job {
+    Dir(PathBuf, usize, IgnoreStack),
+    File(PathBuf),
+}
+
+// Una cola por hilo: cada uno toma de la suya por detrás (lo último que descubrió) y, si se vacía,
+// roba por delante de las demás, donde quedan los directorios más cercanos a la raíz.
+struct WorkQueues {
+    queues: Vec<Mutex<VecDeque<Job>>>,
+    // Trabajos encolados o en curso: en cero ningún hilo puede generar más y el recorrido terminó.
+    pending: AtomicUsize,
+}
+
+impl WorkQueues {
+    fn new(threads: usize) -> Self {
+        Self {
+            queues: (0..threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+            pending: AtomicUsize::new(0),
+        }
+    }
+
+    fn push(&self, worker: usize, job: Job) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.queue(worker).push_back(job);
+    }
+
+    fn next(&self, worker: usize) -> Option<Job> {
+        loop {
+            if let Some(job) = self.queue(worker).pop_back() {
+                return Some(job);
+            }
+            let count = self.queues.len();
+            if let Some(job) = (1..count).find_map(|offset| self.queue((worker + offset) % count).pop_front()) {
+                return Some(job);
+            }
+            if self.pending.load(Ordering::SeqCst) == 0 {
+                return None;
+            }
+            thread::yield_now();
+        }
+    }
+
+    // Se llama después de encolar los hijos del trabajo terminado.
+    fn done(&self) {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn queue(&self, worker: usize) -> std::sync::MutexGuard<'_, VecDeque<Job>> {
+        self.queues[worker].lock().expect("cola de trabajo del escáner")
+    }
+}
+
+pub struct FileScanner<'a> {
+    detector: &'a Detector,
+    parsers: Parsers,
+    max_file_size: u64,
+}
+
+impl<'a> FileScanner<'a> {
+    pub fn new(detector: &'a Detector, max_file_size: u64) -> Self {
+        Self {
+            detector,
+            parsers: Parsers::new(),
+            max_file_size,
+        }
+    }
+
+    // Recorre el árbol respetando .gitignore y omitiendo .git con `threads` hilos; los hallazgos de cada archivo
+    // se entregan juntos a `emit` en cuanto termina, mientras el resto del árbol sigue en curso.
+    pub fn scan_tree(
+        &self,
+        root: &Path,
+        threads: usize,
+        stats: &mut ScanStats,
+        mut emit: impl FnMut(Vec<Finding>),
+    ) -> Result<(), String> {
+        let metadata = fs::symlink_metadata(root).map_err(|e| format!("{}: {}", root.display(), e))?;
+        if metadata.is_file() {
+            let findings = self.scan_file(root, stats);
+            if !findings.is_empty() {
+                emit(findings);
+            }
+            return Ok(());
+        }
+
+        let threads = threads.max(1);
+        let work = WorkQueues::new(threads);
+        work.push(0, Job::Dir(root.to_path_buf(), 0, IgnoreStack::default()));
+        let (sender, receiver) = mpsc::channel();
+        let worker_stats = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|worker| {
+                    let sender = sender.clone();
+                    let work = &work;
+                    scope.spawn(move || self.work(work, worker, &sender))
+                })
+                .collect();
+            drop(sender);
+            for batch in receiver {
+                emit(batch);
+            }
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("hilo del escáner"))
+                .collect::<Vec<_>>()
+        });
+        for worker in &worker_stats {
+            stats.add(worker);
+        }
+        Ok(())
+    }
+
+    fn work(&self, work: &WorkQueues, worker: usize, sender: &Sender<Vec<Finding>>) -> ScanStats {
+        let mut stats = ScanStats::default();
+        while let Some(job) = work.next(worker) {
+            match job {
+                Job::Dir(dir, depth, ignores) => expand(work, worker, &dir, depth, ignores, &mut stats),
+                Job::File(path) => {
+                    let findings = self.scan_file(&path, &mut stats);
+                    if !findings.is_empty() {
+                        // El receptor solo se va si el llamador entró en pánico: no hay a quién avisar.
+                        let _ = sender.send(findings);
+                    }
+                }
+            }
+            work.done();
+        }
+        stats
+    }
+
+    pub fn scan_file(&self, path: &Path, stats: &mut ScanStats) -> Vec<Finding> {
+        let Ok(mut file) = fs::File::open(path) else {
+            return Vec::new();
+        };
+        // Los formatos línea a línea usan memoria acotada sea cual sea el tamaño: max_file_size no aplica.
+        let format = Format::detect(path);
+        if matches!(format, Format::Dotenv | Format::Source) {
+            return self.scan_stream(path, format, file, stats);
+        }
+        // Formatos estructurados (YAML, JSON, secciones INI/TOML): necesitan el archivo entero.
+        if file.metadata().is_ok_and(|meta| meta.len() > self.max_file_size) {
+            stats.skipped_large += 1;
+            return Vec::new();
+        }
+        let mut bytes = Vec::new();
+        if file.read_to_end(&mut bytes).is_err() {
+            return Vec::new();
+        }
+        self.scan_bytes(path, &bytes, stats)
+    }
+
+    // Bloques de hasta CHUNK_SIZE cortados en el último salto de línea; una línea más larga que el bloque
+    // se parte donde cae (sus columnas se cuentan desde el corte).
+    fn scan_stream(&self, path: &Path, format: Format, mut file: fs::File, stats: &mut ScanStats) -> Vec<Finding> {
+        if is_lockfile(path) {
+            stats.skipped_ignored += 1;
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+        let mut first_line = 0;
+        let mut bytes_scanned = 0;
+        loop {
+            let wanted = (CHUNK_SIZE - buffer.len()) as u64;
+            if file.by_ref().take(wanted).read_to_end(&mut buffer).is_err() {
+                return Vec::new();
+            }
+            if bytes_scanned == 0 && is_binary(&buffer) {
+                stats.skipped_binary += 1;
+                return Vec::new();
+            }
+            let eof = buffer.len() < CHUNK_SIZE;
+            let cut = if eof {
+                buffer.len()
+            } else {
+                buffer.iter().rposition(|&b| b == b'\n').map_or(buffer.len(), |i| i + 1)
+            };
+            let text = String::from_utf8_lossy(&buffer[..cut]);
+            for mut finding in self.scan_text(path, format, &text) {
+                if let Location::File { line, .. } = &mut finding.location {
+                    *line += first_line;
+                }
+                findings.push(finding);
+            }
+            first_line += buffer[..cut].iter().filter(|&&b| b == b'\n').count();
+            bytes_scanned += cut as u64;
+            buffer.drain(..cut);
+            if eof {
+                break;
+            }
+        }
+        stats.files_scanned += 1;
+        stats.bytes_scanned += bytes_scanned;
+        findings
+    }
+
+    // Contenido ya cargado (archivo en disco o blob de git) identificado por su ruta.
+    pub fn scan_bytes(&self, path: &Path, bytes: &[u8], stats: &mut ScanStats) -> Vec<Finding> {
+        if is_lockfile(path) {
+            stats.skipped_ignored += 1;
+            return Vec::new();
+        }
+        if bytes.len() as u64 > self.max_file_size {
+            stats.skipped_large += 1;
+            return Vec::new();
+        }
+        if is_binary(bytes) {
+            stats.skipped_binary += 1;
+            return Vec::new();
+        }
+        stats.files_scanned += 1;
+        stats.bytes_scanned += bytes.len() as u64;
+        let text = String::from_utf8_lossy(bytes);
+        self.scan_text(path, Format::detect(path), &text)
+    }
+
+    // Pares clave/valor del formato más una pasada de valor sobre cada línea completa.
+    pub fn scan_text(&self, path: &Path, format: Format, text: &str) -> Vec<Finding> {
+        let pairs = self.parsers.parse(format, text);
+        let mut pairs = pairs.iter().peekable();
+        let mut findings = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_no = index + 1;
+            let mut covered: Vec<(usize, usize)> = Vec::new();
+            while let Some(pair) = pairs.next_if(|p| p.line == line_no) {
+                // El valor del par ya se analizó con su clave: la pasada de línea no lo repite con la clave pegada.
+                covered.push((pair.offset, pair.offset + pair.value.len()));
+                for detection in self.detector.scan_pair(&pair.key, &pair.value) {
+                    let (start, end) = (pair.offset + detection.start, pair.offset + detection.end);
+                    covered.push((start, end));
+                    let location = file_location(path, (line_no, line), start, Some(pair.path.clone()));
+                    findings.push(detection.into_finding(&pair.value, location));
+                }
+            }
+            for detection in self.detector.scan_value(line) {
+                if covered.iter().any(|&(s, e)| detection.start < e && s < detection.end) {
+                    continue;
+                }
+                let location = file_location(path, (line_no, line), detection.start, None);
+                findings.push(detection.into_finding(line, location));
+            }
+        }
+        findings
+    }
+}
+
+// Hijos de un directorio: subdirectorios y archivos pasan a la cola del hilo que lo leyó.
+fn expand(work: &WorkQueues, worker: usize, dir: &Path, depth: usize, mut ignores: IgnoreStack, stats: &mut ScanStats) {
+    ignores.enter(depth, dir);
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("⚠️ No se pudo leer {}: {}", dir.display(), err);
+            return;
+        }
+    };
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_symlink() || entry.file_name() == ".git" {
+            continue;
+        }
+        if ignores.is_ignored(&path, file_type.is_dir()) {
+            stats.skipped_ignored += 1;
+            continue;
+        }
+        if file_type.is_dir() {
+            work.push(worker, Job::Dir(path, depth + 1, ignores.clone()));
+        } else if file_type.is_file() {
+            work.push(worker, Job::File(path));
+        }
+    }
+}
+
+fn is_lockfile(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| LOCKFILES.iter().any(|lock| name == *lock))
+}
+
+fn file_location(path: &Path, line: (usize, &str), start: usize, key: Option<String>) -> Location {
+    let (line_no, line_text) = line;
+    Location::File {
+        path: path.to_path_buf(),
+        line: line_no,
+        // Con escapes JSON el valor decodificado no mide lo mismo que el texto: se cuenta hasta donde se pueda.
+        column: line_text.get(..start).map_or(start, |before| before.chars().count()) + 1,
+        key,
+    }
+}
+
+// Heurística de git: un byte NUL en los primeros 8000 bytes indica binario.
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8000).any(|&b| b == 0)
+}
@@ -0,0 +1,704 @@
+// Papiweb desarrollos informaticos
+// Endurecimiento del sistema de archivos: binarios SUID/SGID fuera de la lista conocida de la distribución, archivos
+// y directorios modificables por cualquiera, archivos sin dueño y entradas modificables en los directorios del PATH.
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::finding::{AuditPriority, Finding, Location};
+use crate::hardening;
+use crate::system;
+
+const SUID_RULE: &str = "suid-unexpected";
+const SGID_RULE: &str = "sgid-unexpected";
+const WORLD_WRITABLE_RULE: &str = "world-writable-file";
+const STICKY_RULE: &str = "world-writable-dir-without-sticky";
+const UNOWNED_RULE: &str = "unowned-file";
+const PATH_RULE: &str = "path-entry-writable";
+
+const SETUID: u32 = 0o4000;
+const SETGID: u32 = 0o2000;
+const STICKY: u32 = 0o1000;
+
+// Sistemas de archivos virtuales o remotos: no son del disco del host o recorrerlos no termina.
+const SKIPPED_FS: &[&str] = &[
+    "proc",
+    "sysfs",
+    "devtmpfs",
+    "devpts",
+    "cgroup",
+    "cgroup2",
+    "securityfs",
+    "debugfs",
+    "tracefs",
+    "pstore",
+    "bpf",
+    "mqueue",
+    "hugetlbfs",
+    "configfs",
+    "fusectl",
+    "binfmt_misc",
+    "autofs",
+    "efivarfs",
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "fuse.sshfs",
+];
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin", "/sbin", "/usr", "/lib", "/lib32", "/lib64", "/libx32", "/etc", "/boot",
+];
+// PATH seguro de sudo y de root: se revisa aunque el PATH de quien ejecuta no lo incluya.
+const SECURE_PATH: &[&str] = &[
+    "/usr/local/sbin",
+    "/usr/local/bin",
+    "/usr/sbin",
+    "/usr/bin",
+    "/sbin",
+    "/bin",
+];
+
+// Instalaciones locales y directorios de usuarios: un SUID/SGID ahí se informa siempre, aunque cuelgue de /usr.
+const LOCAL_DIRS: &[&str] = &["/usr/local", "/opt", "/home", "/tmp"];
+// Con /usr unificado estos directorios son enlaces a su equivalente en /usr; las listas conocidas usan solo la ruta
+// en /usr y la ruta real se lleva a ella antes de buscarla.
+const MERGED_DIRS: &[&str] = &["/bin", "/sbin", "/lib", "/lib32", "/lib64", "/libx32"];
+
+// SUID/SGID presentes en casi cualquier distribución, por ruta completa.
+const COMMON_SUID: &[&str] = &[
+    "/usr/bin/su",
+    "/usr/bin/sudo",
+    "/usr/bin/sudoedit",
+    "/usr/bin/passwd",
+    "/usr/bin/chsh",
+    "/usr/bin/chfn",
+    "/usr/bin/newgrp",
+    "/usr/bin/gpasswd",
+    "/usr/bin/mount",
+    "/usr/bin/umount",
+    "/usr/bin/pkexec",
+    "/usr/lib/policykit-1/polkit-agent-helper-1",
+    "/usr/lib/polkit-1/polkit-agent-helper-1",
+    "/usr/libexec/polkit-agent-helper-1",
+    "/usr/bin/fusermount",
+    "/usr/bin/fusermount3",
+    "/usr/lib/openssh/ssh-keysign",
+    "/usr/libexec/openssh/ssh-keysign",
+    "/usr/lib/ssh/ssh-keysign",
+    "/usr/lib/dbus-1.0/dbus-daemon-launch-helper",
+    "/usr/libexec/dbus-1/dbus-daemon-launch-helper",
+    "/usr/bin/newuidmap",
+    "/usr/bin/newgidmap",
+    "/usr/bin/ping",
+    "/usr/bin/ping6",
+    "/usr/sbin/mount.nfs",
+    "/usr/sbin/mount.cifs",
+    "/usr/lib/xorg/Xorg.wrap",
+    "/usr/libexec/Xorg.wrap",
+    "/usr/lib/snapd/snap-confine",
+    "/usr/libexec/snapd/snap-confine",
+    "/usr/bin/at",
+];
+const COMMON_SGID: &[&str] = &[
+    "/usr/bin/chage",
+    "/usr/bin/expiry",
+    "/usr/bin/wall",
+    "/usr/bin/write",
+    "/usr/bin/crontab",
+    "/usr/bin/ssh-agent",
+    "/usr/libexec/utempter/utempter",
+    "/usr/bin/locate",
+    "/usr/bin/plocate",
+    "/usr/bin/mlocate",
+    "/usr/bin/dotlockfile",
+    "/usr/bin/at",
+];
+
+struct Distro {
+    ids: &'static [&'static str],
+    suid: &'static [&'static str],
+    sgid: &'static [&'static str],
+}
+
+// Se elige por ID o ID_LIKE de /etc/os-release.
+const DISTROS: &[Distro] = &[
+    Distro {
+        ids: &["debian", "ubuntu"],
+        suid: &[
+            "/usr/lib/pt_chown",
+            "/usr/lib/eject/dmcrypt-get-device",
+            "/usr/sbin/exim4",
+            "/usr/bin/ntfs-3g",
+            "/usr/bin/traceroute6.iputils",
+        ],
+        sgid: &[
+            "/usr/sbin/unix_chkpwd",
+            "/usr/sbin/pam_extrausers_chkpwd",
+            "/usr/bin/bsd-write",
+            "/usr/bin/mail-lock",
+            "/usr/bin/mail-unlock",
+            "/usr/bin/mail-touchlock",
+            "/usr/lib/x86_64-linux-gnu/utempter/utempter",
+            "/usr/lib/aarch64-linux-gnu/utempter/utempter",
+        ],
+    },
+    Distro {
+        ids: &["rhel", "fedora", "centos", "rocky", "almalinux"],
+        suid: &[
+            "/usr/sbin/unix_chkpwd",
+            "/usr/sbin/pam_timestamp_check",
+            "/usr/sbin/userhelper",
+            "/usr/sbin/usernetctl",
+            "/usr/bin/staprun",
+            "/usr/sbin/grub2-set-bootflag",
+            "/usr/libexec/sssd/krb5_child",
+            "/usr/libexec/sssd/ldap_child",
+            "/usr/libexec/sssd/selinux_child",
+            "/usr/libexec/sssd/proxy_child",
+        ],
+        sgid: &["/usr/bin/lockfile"],
+    },
+    Distro {
+        ids: &["alpine"],
+        suid: &["/usr/bin/bbsuid"],
+        sgid: &[],
+    },
+    Distro {
+        ids: &["arch"],
+        suid: &[
+            "/usr/bin/unix_chkpwd",
+            "/usr/bin/suexec",
+            "/usr/bin/mount.nfs",
+            "/usr/bin/mount.cifs",
+            "/usr/lib/dbus-daemon-launch-helper",
+        ],
+        sgid: &[],
+    },
+    Distro {
+        ids: &["suse", "opensuse"],
+        suid: &["/usr/sbin/unix2_chkpwd", "/usr/sbin/unix_chkpwd"],
+        sgid: &["/usr/bin/lockfile"],
+    },
+];
+
+// Con SUID de root dan una shell de root directa (GTFOBins).
+const SHELL_ESCAPES: &[&str] = &[
+    "bash", "sh", "dash", "zsh", "ksh", "csh", "tcsh", "fish", "python", "python2", "python3", "perl", "ruby", "php",
+    "node", "lua", "find", "vim", "vi", "nano", "less", "more", "env", "awk", "gawk", "tar", "cp", "mv", "dd", "tee",
+    "chmod", "chown", "nmap", "busybox",
+];
+
+#[derive(Debug, Default)]
+pub struct FilesystemStats {
+    pub files_scanned: u64,
+    pub dirs_scanned: u64,
+    pub known_setid: u64,
+    pub files_denied: u64,
+    pub distro: Option<String>,
+}
+
+pub fn rule_ids() -> Vec<String> {
+    [
+        SUID_RULE,
+        SGID_RULE,
+        WORLD_WRITABLE_RULE,
+        STICKY_RULE,
+        UNOWNED_RULE,
+        PATH_RULE,
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+struct Known {
+    suid: HashSet<&'static str>,
+    sgid: HashSet<&'static str>,
+}
+
+// El directorio cuelga de uno público con sticky bit (/tmp, /var/tmp) en `shared`: ahí los archivos modificables por
+// todos son cosa de su dueño. `orphan` es el UID/GID sin cuenta ya informado en un directorio superior.
+struct Visit {
+    dir: PathBuf,
+    shared: bool,
+    orphan: Option<(u32, u32)>,
+}
+
+struct Scan<'a> {
+    known: Known,
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+    findings: Vec<Finding>,
+    stats: &'a mut FilesystemStats,
+}
+
+// Sin `root` se recorre todo el host y además se revisa el PATH; con `root` solo ese subárbol.
+pub fn scan_filesystem(root: Option<&Path>, stats: &mut FilesystemStats) -> Result<Vec<Finding>, String> {
+    if let Some(root) = root {
+        fs::symlink_metadata(root).map_err(|e| format!("{}: {}", root.display(), e))?;
+    }
+    let known = known_setid(stats);
+    let mut scan = Scan {
+        known,
+        users: system::user_names(),
+        groups: system::group_names(),
+        findings: Vec::new(),
+        stats,
+    };
+    scan.walk(root.unwrap_or(Path::new("/")));
+    if root.is_none() {
+        scan.check_path();
+    }
+    Ok(scan.findings)
+}
+
+fn known_setid(stats: &mut FilesystemStats) -> Known {
+    let mut known = Known {
+        suid: COMMON_SUID.iter().copied().collect(),
+        sgid: COMMON_SGID.iter().copied().collect(),
+    };
+    let release = fs::read_to_string("/etc/os-release").unwrap_or_default();
+    let field = |name: &str| {
+        release
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+            .map(|v| v.trim_matches('"').to_string())
+            .unwrap_or_default()
+    };
+    let id = field("ID");
+    let like = field("ID_LIKE");
+    let ids: Vec<&str> = std::iter::once(id.as_str()).chain(like.split_whitespace()).collect();
+    if let Some(distro) = DISTROS.iter().find(|d| ids.iter().any(|id| d.ids.contains(id))) {
+        known.suid.extend(distro.suid);
+        known.sgid.extend(distro.sgid);
+    }
+    stats.distro = (!id.is_empty()).then_some(id);
+    known
+}
+
+// Puntos de montaje que no se recorren según /proc/self/mounts.
+fn skipped_mounts() -> HashSet<PathBuf> {
+    let mounts = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let target = fields.nth(1)?;
+            let fstype = fields.next()?;
+            SKIPPED_FS
+                .contains(&fstype)
+                .then(|| PathBuf::from(target.replace("\\040", " ")))
+        })
+        .collect()
+}
+
+fn in_system_dir(path: &Path) -> bool {
+    SYSTEM_DIRS.iter().any(|dir| path.starts_with(dir)) && !LOCAL_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+// Ruta con la que se busca en las listas conocidas: la real (sin enlaces) y, si está en /bin, /sbin o /lib*, la
+// equivalente en /usr. Si no se puede resolver se usa tal cual.
+fn setid_key(path: &Path) -> PathBuf {
+    let real = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if MERGED_DIRS.iter().any(|dir| real.starts_with(dir)) {
+        Path::new("/usr").join(real.strip_prefix("/").unwrap_or(&real))
+    } else {
+        real
+    }
+}
+
+fn file_location(path: &Path, key: String) -> Location {
+    Location::File {
+        path: path.to_path_buf(),
+        line: 1,
+        column: 1,
+        key: Some(key),
+    }
+}
+
+impl Scan<'_> {
+    fn walk(&mut self, root: &Path) {
+        let skipped = skipped_mounts();
+        let mut pending = vec![Visit {
+            dir: root.to_path_buf(),
+            shared: false,
+            orphan: None,
+        }];
+        while let Some(Visit { dir, shared, orphan }) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    if err.kind() == ErrorKind::PermissionDenied {
+                        self.stats.files_denied += 1;
+                    }
+                    continue;
+                }
+            };
+            self.stats.dirs_scanned += 1;
+            for entry in entries.filter_map(Result::ok) {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                let path = entry.path();
+                if metadata.is_symlink() {
+                    continue;
+                }
+                // El contenido de un directorio sin dueño con el mismo UID/GID va en el hallazgo del directorio.
+                let owner = (metadata.uid(), metadata.gid());
+                let orphan = if orphan == Some(owner) {
+                    orphan
+                } else {
+                    self.check_owner(&path, &metadata).then_some(owner)
+                };
+                let mode = metadata.mode() & 0o7777;
+                if metadata.is_dir() {
+                    if skipped.contains(&path) {
+                        continue;
+                    }
+                    let public = mode & 0o002 != 0;
+                    if public && mode & STICKY == 0 {
+                        self.push_sticky(&path, mode);
+                    }
+                    pending.push(Visit {
+                        dir: path,
+                        shared: shared || (public && mode & STICKY != 0),
+                        orphan,
+                    });
+                } else if metadata.is_file() {
+                    self.stats.files_scanned += 1;
+                    if mode & (SETUID | SETGID) != 0 {
+                        self.check_setid(&path, &metadata);
+                    }
+                    if mode & 0o002 != 0 {
+                        self.push_world_writable(&path, mode, shared);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_setid(&mut self, path: &Path, metadata: &fs::Metadata) {
+        let mode = metadata.mode() & 0o7777;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let key = setid_key(path);
+        let system = in_system_dir(&key);
+        let known = |list: &HashSet<&str>| system && key.to_str().is_some_and(|key| list.contains(key));
+        let owner = self.owner(metadata.uid());
+        if mode & SETUID != 0 {
+            if known(&self.known.suid) {
+                self.stats.known_setid += 1;
+            } else {
+                let escape = metadata.uid() == 0 && SHELL_ESCAPES.contains(&name.as_str());
+                let (priority, text) = if escape {
+                    (
+                        AuditPriority::Critical,
+                        format!(
+                            "{} es SUID de root y abre una shell: cualquier usuario local obtiene root",
+                            name
+                        ),
+                    )
+                } else if !system {
+                    (
+                        AuditPriority::High,
+                        format!("binario SUID de {} fuera de los directorios del sistema", owner),
+                    )
+                } else if metadata.uid() == 0 {
+                    (
+                        AuditPriority::High,
+                        "binario SUID de root que no está en la lista conocida de la distribución".to_string(),
+                    )
+                } else {
+                    (
+                        AuditPriority::Medium,
+                        format!(
+                            "binario SUID de {} que no está en la lista conocida de la distribución",
+                            owner
+                        ),
+                    )
+                };
+                self.findings.push(hardening::finding(
+                    SUID_RULE,
+                    priority,
+                    file_location(path, format!("SUID {}, modo {:04o}", owner, mode)),
+                    &format!("{}:suid:{}", path.display(), owner),
+                    text,
+                    format!(
+                        "chmod u-s {} si no hace falta (o desinstalar el paquete); si es legítimo, aceptarlo en la baseline",
+                        path.display()
+                    ),
+                ));
+            }
+        }
+        if mode & SETGID != 0 {
+            if known(&self.known.sgid) {
+                self.stats.known_setid += 1;
+                return;
+            }
+            let group = self.group(metadata.gid());
+            self.findings.push(hardening::finding(
+                SGID_RULE,
+                AuditPriority::Medium,
+                file_location(path, format!("SGID {}, modo {:04o}", group, mode)),
+                &format!("{}:sgid:{}", path.display(), group),
+                format!(
+                    "binario SGID del grupo {} que no está en la lista conocida de la distribución",
+                    group
+                ),
+                format!(
+                    "chmod g-s {} si no hace falta; si es legítimo, aceptarlo en la baseline",
+                    path.display()
+                ),
+            ));
+        }
+    }
+
+    fn push_sticky(&mut self, path: &Path, mode: u32) {
+        let priority = if in_system_dir(path) {
+            AuditPriority::Critical
+        } else {
+            AuditPriority::High
+        };
+        self.findings.push(hardening::finding(
+            STICKY_RULE,
+            priority,
+            file_location(path, format!("modo {:04o}", mode)),
+            &format!("{}:{:04o}", path.display(), mode),
+            "directorio modificable por cualquier usuario y sin sticky bit: cualquiera puede borrar o reemplazar los archivos de otros".to_string(),
+            format!(
+                "chmod o-w {} o, si debe ser compartido como /tmp, chmod +t {}",
+                path.display(),
+                path.display()
+            ),
+        ));
+    }
+
+    fn push_world_writable(&mut self, path: &Path, mode: u32, shared: bool) {
+        let executable = mode & 0o111 != 0;
+        let (priority, text) = if in_system_dir(path) || executable {
+            (
+                AuditPriority::High,
+                "archivo modificable por cualquier usuario: quien lo use o lo ejecute corre lo que otro escriba",
+            )
+        } else if shared {
+            (
+                AuditPriority::Low,
+                "archivo modificable por cualquier usuario dentro de un directorio público",
+            )
+        } else {
+            (AuditPriority::Medium, "archivo modificable por cualquier usuario")
+        };
+        self.findings.push(hardening::finding(
+            WORLD_WRITABLE_RULE,
+            priority,
+            file_location(path, format!("modo {:04o}", mode)),
+            &format!("{}:{:04o}", path.display(), mode),
+            text.to_string(),
+            format!("chmod o-w {}", path.display()),
+        ));
+    }
+
+    // UID o GID sin cuenta ni grupo: si se crea uno con ese número hereda los archivos.
+    fn check_owner(&mut self, path: &Path, metadata: &fs::Metadata) -> bool {
+        let missing_user = !self.users.contains_key(&metadata.uid());
+        let missing_group = !self.groups.contains_key(&metadata.gid());
+        if !missing_user && !missing_group {
+            return false;
+        }
+        let owner = match (missing_user, missing_group) {
+            (true, true) => format!("UID {} y GID {}", metadata.uid(), metadata.gid()),
+            (true, false) => format!("UID {}", metadata.uid()),
+            _ => format!("GID {}", metadata.gid()),
+        };
+        let executable = metadata.is_file() && metadata.mode() & 0o111 != 0;
+        let priority = if in_system_dir(path) || executable {
+            AuditPriority::Medium
+        } else {
+            AuditPriority::Low
+        };
+        let (contents, recursive) = if metadata.is_dir() {
+            (" junto con su contenido", " -R")
+        } else {
+            ("", "")
+        };
+        self.findings.push(hardening::finding(
+            UNOWNED_RULE,
+            priority,
+            file_location(path, format!("{} sin cuenta", owner)),
+            &format!("{}:{}", path.display(), owner),
+            format!(
+                "{} sin cuenta en el sistema: el próximo usuario o grupo creado con ese número pasa a ser su dueño{}",
+                owner, contents
+            ),
+            format!(
+                "chown{} root:root {} (o al usuario que corresponda) o borrarlo si quedó de una cuenta eliminada",
+                recursive,
+                path.display()
+            ),
+        ));
+        true
+    }
+
+    // Directorios del PATH de quien ejecuta y del PATH seguro de root: modificables por otros, o con entradas
+    // modificables, permiten reemplazar comandos que root acaba ejecutando.
+    fn check_path(&mut self) {
+        let own_uid = fs::metadata("/proc/self").map_or(0, |m| m.uid());
+        let variable = env::var("PATH").unwrap_or_default();
+        let mut seen = HashSet::new();
+        let dirs = variable
+            .split(':')
+            .map(str::to_string)
+            .chain(SECURE_PATH.iter().map(|d| d.to_string()));
+        for dir in dirs {
+            if !seen.insert(dir.clone()) {
+                continue;
+            }
+            let path = PathBuf::from(&dir);
+            if !path.is_absolute() {
+                let shown = if dir.is_empty() {
+                    "vacía (directorio actual)"
+                } else {
+                    dir.as_str()
+                };
+                self.findings.push(hardening::finding(
+                    PATH_RULE,
+                    AuditPriority::High,
+                    Location::Env { var: "PATH".to_string() },
+                    &format!("PATH:{}", dir),
+                    format!(
+                        "el PATH incluye la entrada relativa {}: se ejecuta lo que haya en el directorio de trabajo",
+                        shown
+                    ),
+                    "quitar la entrada relativa del PATH en los perfiles de shell (/etc/profile, ~/.profile, ~/.bashrc)".to_string(),
+                ));
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            self.check_path_entry(&path, &metadata, own_uid);
+            let Ok(entries) = fs::read_dir(&path) else {
+                continue;
+            };
+            for entry in entries.filter_map(Result::ok) {
+                if let Ok(metadata) = fs::metadata(entry.path()) {
+                    self.check_path_entry(&entry.path(), &metadata, own_uid);
+                }
+            }
+        }
+    }
+
+    fn check_path_entry(&mut self, path: &Path, metadata: &fs::Metadata, own_uid: u32) {
+        let mode = metadata.mode() & 0o7777;
+        let (priority, who) = if mode & 0o002 != 0 && !(metadata.is_dir() && mode & STICKY != 0) {
+            (AuditPriority::Critical, "cualquier usuario".to_string())
+        } else if mode & 0o020 != 0 && metadata.gid() != 0 {
+            (AuditPriority::High, format!("el grupo {}", self.group(metadata.gid())))
+        } else if metadata.uid() != 0 && metadata.uid() != own_uid {
+            (AuditPriority::High, format!("su dueño {}", self.owner(metadata.uid())))
+        } else {
+            return;
+        };
+        let kind = if metadata.is_dir() {
+            "directorio del PATH"
+        } else {
+            "comando del PATH"
+        };
+        self.findings.push(hardening::finding(
+            PATH_RULE,
+            priority,
+            file_location(path, format!("{}, modo {:04o}", kind, mode)),
+            &format!("{}:{:04o}:{}", path.display(), mode, metadata.uid()),
+            format!(
+                "{} {} modificable por {}: puede reemplazar un comando que otro usuario o root ejecute",
+                kind,
+                path.display(),
+                who
+            ),
+            format!("chown root:root {} && chmod go-w {}", path.display(), path.display()),
+        ));
+    }
+
+    fn owner(&self, uid: u32) -> String {
+        self.users.get(&uid).cloned().unwrap_or_else(|| format!("UID {}", uid))
+    }
+
+    fn group(&self, gid: u32) -> String {
+        self.groups.get(&gid).cloned().unwrap_or_else(|| format!("GID {}", gid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process;
+
+    #[test]
+    fn known_lists_hold_full_paths_under_usr() {
+        let distros = DISTROS.iter().flat_map(|d| d.suid.iter().chain(d.sgid));
+        for path in COMMON_SUID.iter().chain(COMMON_SGID).chain(distros) {
+            let path = Path::new(path);
+            assert!(path.starts_with("/usr") && in_system_dir(path), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn merged_dirs_map_to_usr() {
+        assert_eq!(
+            setid_key(Path::new("/bin/no-existe-su")),
+            Path::new("/usr/bin/no-existe-su")
+        );
+        assert_eq!(
+            setid_key(Path::new("/sbin/no-existe.nfs")),
+            Path::new("/usr/sbin/no-existe.nfs")
+        );
+        assert_eq!(
+            setid_key(Path::new("/usr/lib/no-existe")),
+            Path::new("/usr/lib/no-existe")
+        );
+        assert_eq!(setid_key(Path::new("/binario/su")), Path::new("/binario/su"));
+    }
+
+    #[test]
+    fn local_dirs_are_never_system() {
+        assert!(in_system_dir(Path::new("/usr/bin/su")));
+        assert!(in_system_dir(Path::new("/bin/su")));
+        assert!(in_system_dir(Path::new("/usr/localidad/su")));
+        for path in ["/usr/local/bin/su", "/opt/app/bin/su", "/home/ana/su", "/tmp/su"] {
+            assert!(!in_system_dir(Path::new(path)), "{}", path);
+        }
+    }
+
+    #[test]
+    fn known_basename_outside_the_list_is_reported() {
+        let dir = env::temp_dir().join(format!("auditorlocal-setid-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("su");
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o4755)).unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let mut stats = FilesystemStats::default();
+        let mut scan = Scan {
+            known: Known {
+                suid: COMMON_SUID.iter().copied().collect(),
+                sgid: COMMON_SGID.iter().copied().collect(),
+            },
+            users: HashMap::new(),
+            groups: HashMap::new(),
+            findings: Vec::new(),
+            stats: &mut stats,
+        };
+        scan.check_setid(&path, &metadata);
+        let findings = scan.findings;
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule_id, SUID_RULE);
+        assert_eq!(stats.known_setid, 0);
+    }
+}
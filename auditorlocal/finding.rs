@@ -0,0 +1,399 @@
+// Papiweb desarrollos informaticos
+// Modelo de hallazgos del auditor local: severidad, confianza, tipo y ubicación.
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AuditPriority {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl AuditPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditPriority::Low => "BAJA",
+            AuditPriority::Medium => "MEDIA",
+            AuditPriority::High => "ALTA",
+            AuditPriority::Critical => "CRÍTICA",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            AuditPriority::Low => "🔵",
+            AuditPriority::Medium => "🟡",
+            AuditPriority::High => "🟠",
+            AuditPriority::Critical => "🔴",
+        }
+    }
+
+    // Identificador estable para salidas JSON/SARIF y para --fail-on.
+    pub fn id(&self) -> &'static str {
+        match self {
+            AuditPriority::Low => "low",
+            AuditPriority::Medium => "medium",
+            AuditPriority::High => "high",
+            AuditPriority::Critical => "critical",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "low" | "baja" => Some(AuditPriority::Low),
+            "medium" | "media" => Some(AuditPriority::Medium),
+            "high" | "alta" => Some(AuditPriority::High),
+            "critical" | "critica" | "crítica" => Some(AuditPriority::Critical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::Low => "BAJA",
+            Confidence::Medium => "MEDIA",
+            Confidence::High => "ALTA",
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecretKind {
+    SensitiveName,
+    GithubToken,
+    AwsAccessKey,
+    SlackToken,
+    StripeKey,
+    Jwt,
+    PrivateKey,
+    UrlCredentials,
+    HighEntropy,
+    CredentialStore,
+    CommandLine,
+    Custom,
+    // Configuración del host (servicio expuesto, sshd_config...): no hay secreto que rotar sino un ajuste que corregir.
+    Misconfiguration,
+}
+
+impl SecretKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecretKind::SensitiveName => "nombre sensible",
+            SecretKind::GithubToken => "token de GitHub",
+            SecretKind::AwsAccessKey => "clave de acceso AWS",
+            SecretKind::SlackToken => "token de Slack",
+            SecretKind::StripeKey => "clave de Stripe",
+            SecretKind::Jwt => "JWT",
+            SecretKind::PrivateKey => "clave privada",
+            SecretKind::UrlCredentials => "credenciales en URL",
+            SecretKind::HighEntropy => "cadena de alta entropía",
+            SecretKind::CredentialStore => "almacén de credenciales",
+            SecretKind::CommandLine => "secreto en línea de comandos",
+            SecretKind::Custom => "secreto de regla personalizada",
+            SecretKind::Misconfiguration => "configuración insegura",
+        }
+    }
+
+    // Identificador usado en los packs de reglas (campo "kind").
+    pub fn id(&self) -> &'static str {
+        match self {
+            SecretKind::SensitiveName => "sensitive-name",
+            SecretKind::GithubToken => "github-token",
+            SecretKind::AwsAccessKey => "aws-access-key",
+            SecretKind::SlackToken => "slack-token",
+            SecretKind::StripeKey => "stripe-key",
+            SecretKind::Jwt => "jwt",
+            SecretKind::PrivateKey => "private-key",
+            SecretKind::UrlCredentials => "url-credentials",
+            SecretKind::HighEntropy => "high-entropy",
+            SecretKind::CredentialStore => "credential-store",
+            SecretKind::CommandLine => "command-line",
+            SecretKind::Custom => "custom",
+            SecretKind::Misconfiguration => "misconfiguration",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        [
+            SecretKind::SensitiveName,
+            SecretKind::GithubToken,
+            SecretKind::AwsAccessKey,
+            SecretKind::SlackToken,
+            SecretKind::StripeKey,
+            SecretKind::Jwt,
+            SecretKind::PrivateKey,
+            SecretKind::UrlCredentials,
+            SecretKind::HighEntropy,
+            SecretKind::CredentialStore,
+            SecretKind::CommandLine,
+            SecretKind::Custom,
+            SecretKind::Misconfiguration,
+        ]
+        .into_iter()
+        .find(|kind| kind.id() == text)
+    }
+}
+
+// Valor secreto retenido solo para análisis local: nunca se imprime.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: &str) -> Self {
+        Secret(value.to_string())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Location {
+    Env {
+        var: String,
+    },
+    File {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        key: Option<String>,
+    },
+    Commit {
+        commit: String,
+        author: String,
+        date: String,
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        at_head: bool,
+    },
+    Process {
+        pid: u32,
+        exe: String,
+        user: String,
+        unit: Option<String>,
+        uptime_secs: u64,
+        var: String,
+    },
+    // Imagen de contenedor: archivo de una capa, o configuración (Env, Cmd, historial) si no hay capa.
+    Image {
+        image: String,
+        layer: Option<String>,
+        path: String,
+        line: usize,
+        column: usize,
+        key: Option<String>,
+        // false si una capa posterior borra (whiteout) o reemplaza el archivo: sigue en el tar de la capa.
+        in_final: bool,
+    },
+    // Socket en escucha: dirección(es), descripción del proceso dueño y servicio estable (unidad o ejecutable).
+    Socket {
+        address: String,
+        owner: String,
+        service: String,
+    },
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Env { var } => write!(f, "entorno:{}", var),
+            Location::File {
+                path,
+                line,
+                column,
+                key: Some(key),
+            } => write!(f, "{}:{}:{} ({})", path.display(), line, column, key),
+            Location::File { path, line, column, .. } => write!(f, "{}:{}:{}", path.display(), line, column),
+            Location::Commit {
+                commit,
+                author,
+                date,
+                path,
+                line,
+                column,
+                at_head,
+            } => write!(
+                f,
+                "commit {} {}:{}:{} ({}, {}) {}",
+                &commit[..commit.len().min(10)],
+                path.display(),
+                line,
+                column,
+                author,
+                date,
+                if *at_head {
+                    "[sigue en HEAD]"
+                } else {
+                    "[eliminado de HEAD]"
+                }
+            ),
+            Location::Process {
+                pid,
+                exe,
+                user,
+                unit,
+                var,
+                ..
+            } => match unit {
+                Some(unit) => write!(f, "pid {} ({}, usuario {}, unidad {}):{}", pid, exe, user, unit, var),
+                None => write!(f, "pid {} ({}, usuario {}):{}", pid, exe, user, var),
+            },
+            Location::Image {
+                image,
+                layer,
+                path,
+                line,
+                column,
+                key,
+                in_final,
+            } => {
+                match layer {
+                    Some(layer) => write!(
+                        f,
+                        "imagen {} capa {} /{}:{}:{}",
+                        image,
+                        &layer[..layer.len().min(19)],
+                        path,
+                        line,
+                        column
+                    )?,
+                    None => write!(f, "imagen {} {}:{}:{}", image, path, line, column)?,
+                }
+                if let Some(key) = key {
+                    write!(f, " ({})", key)?;
+                }
+                if !in_final {
+                    write!(f, " [borrado o reemplazado en una capa posterior]")?;
+                }
+                Ok(())
+            }
+            Location::Socket { address, owner, .. } => write!(f, "{} ({})", address, owner),
+        }
+    }
+}
+
+impl Location {
+    // Identidad estable de la ubicación para la baseline: sin pid ni columna, que cambian entre ejecuciones.
+    pub fn fingerprint_key(&self) -> String {
+        match self {
+            Location::Env { var } => format!("env:{}", var),
+            Location::File {
+                path, key: Some(key), ..
+            } => format!("file:{}:{}", path.display(), key),
+            Location::File { path, line, .. } => format!("file:{}:{}", path.display(), line),
+            Location::Commit { commit, path, line, .. } => format!("commit:{}:{}:{}", commit, path.display(), line),
+            Location::Process { exe, unit, var, .. } => {
+                format!("process:{}:{}", unit.as_deref().unwrap_or(exe), var)
+            }
+            // Sin digest de capa: cambia en cada build aunque el secreto sea el mismo.
+            Location::Image {
+                image,
+                path,
+                key: Some(key),
+                ..
+            } => format!("image:{}:{}:{}", image, path, key),
+            Location::Image { image, path, line, .. } => format!("image:{}:{}:{}", image, path, line),
+            // Sin pid: el mismo servicio expuesto sigue siendo el mismo hallazgo tras un reinicio.
+            Location::Socket { address, service, .. } => format!("socket:{}:{}", address, service),
+        }
+    }
+
+    // Columna donde empieza el secreto, en las ubicaciones que la tienen.
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            Location::File { column, .. } | Location::Commit { column, .. } | Location::Image { column, .. } => {
+                Some(*column)
+            }
+            Location::Env { .. } | Location::Process { .. } | Location::Socket { .. } => None,
+        }
+    }
+}
+
+// Estado del hallazgo frente a la baseline de aceptaciones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaselineStatus {
+    New,
+    Accepted,
+    Expired,
+}
+
+impl BaselineStatus {
+    pub fn id(&self) -> &'static str {
+        match self {
+            BaselineStatus::New => "new",
+            BaselineStatus::Accepted => "accepted",
+            BaselineStatus::Expired => "expired",
+        }
+    }
+}
+
+// Coincidencia del valor con la lista local de secretos filtrados o revocados.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compromise {
+    Exact,
+    Prefix,
+}
+
+impl Compromise {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Compromise::Exact => "coincidencia exacta",
+            Compromise::Prefix => "coincidencia por prefijo",
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Compromise::Exact => "exact",
+            Compromise::Prefix => "prefix",
+        }
+    }
+}
+
+// Observación adicional sobre un hallazgo (p. ej. propiedades de riesgo de un JWT).
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub id: &'static str,
+    pub priority: AuditPriority,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule_id: String,
+    pub kind: SecretKind,
+    pub priority: AuditPriority,
+    pub confidence: Confidence,
+    pub location: Location,
+    pub secret: Secret,
+    pub status: BaselineStatus,
+    pub compromised: Option<Compromise>,
+    pub notes: Vec<Note>,
+}
@@ -0,0 +1,381 @@
+// Papiweb desarrollos informaticos
+// Extracción de pares clave/valor desde archivos de configuración y código fuente.
+use std::path::Path;
+
+use crate::json;
+use crate::pattern::Pattern;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dotenv,
+    Properties,
+    Ini,
+    Toml,
+    Yaml,
+    Json,
+    Source,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyValue {
+    // Clave completa (sección.clave, ruta YAML) y clave final usada para las palabras clave.
+    pub path: String,
+    pub key: String,
+    pub value: String,
+    // Línea (1-based) y desplazamiento en bytes del valor dentro de esa línea.
+    pub line: usize,
+    pub offset: usize,
+}
+
+impl Format {
+    pub fn detect(path: &Path) -> Format {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name == ".env" || name.starts_with(".env.") || ext == "env" {
+            return Format::Dotenv;
+        }
+        match ext.as_str() {
+            "properties" => Format::Properties,
+            "ini" | "cfg" | "conf" | "cnf" => Format::Ini,
+            "toml" => Format::Toml,
+            "yml" | "yaml" => Format::Yaml,
+            "json" => Format::Json,
+            _ => Format::Source,
+        }
+    }
+}
+
+pub struct Parsers {
+    json_pair: Pattern,
+    assignment: Pattern,
+}
+
+impl Parsers {
+    pub fn new() -> Self {
+        Self {
+            json_pair: Pattern::new(r#""[^"]+"\s*:\s*"([^"\\]|\\.)*""#).expect("patrón interno inválido"),
+            assignment: Pattern::new(r#"[A-Za-z_][A-Za-z0-9_.-]*["']?\s*(:=|=>|=|:)\s*("[^"]*"|'[^']*'|`[^`]*`)"#)
+                .expect("patrón interno inválido"),
+        }
+    }
+
+    pub fn parse(&self, format: Format, text: &str) -> Vec<KeyValue> {
+        match format {
+            Format::Dotenv | Format::Properties => parse_flat(text, &["#", "!"]),
+            Format::Ini | Format::Toml => parse_sections(text),
+            Format::Yaml => parse_yaml(text),
+            Format::Json => parse_json(text).unwrap_or_else(|| self.parse_with(&self.json_pair, text)),
+            Format::Source => self.parse_source(text),
+        }
+    }
+
+    // Asignaciones entre comillas del código y, en las líneas sin ninguna, "CLAVE=valor" sin comillas (archivos de
+    // configuración sin extensión): la clave queda fuera del valor analizado.
+    fn parse_source(&self, text: &str) -> Vec<KeyValue> {
+        let mut pairs = self.parse_with(&self.assignment, text);
+        let mut quoted: Vec<usize> = pairs.iter().map(|p| p.line).collect();
+        quoted.dedup();
+        for (index, line) in text.lines().enumerate() {
+            if quoted.binary_search(&(index + 1)).is_ok() {
+                continue;
+            }
+            if let Some(pair) = plain_assignment(line, index + 1) {
+                pairs.push(pair);
+            }
+        }
+        pairs.sort_by_key(|p| (p.line, p.offset));
+        pairs
+    }
+
+    // Formatos sin estructura de líneas: cada coincidencia del patrón es "clave <op> valor".
+    fn parse_with(&self, pattern: &Pattern, text: &str) -> Vec<KeyValue> {
+        let mut pairs = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            for (start, end) in pattern.find_all(line) {
+                let found = &line[start..end];
+                let Some(op) = found.find([':', '=']) else {
+                    continue;
+                };
+                let key = found[..op].trim().trim_matches(|c| c == '"' || c == '\'');
+                let rest = &found[op..];
+                let value_start = rest.find(['"', '\'', '`']).map(|i| start + op + i).unwrap_or(end);
+                pairs.push(KeyValue {
+                    path: key.to_string(),
+                    key: leaf(key).to_string(),
+                    value: line[value_start + 1..end.saturating_sub(1).max(value_start + 1)].to_string(),
+                    line: index + 1,
+                    offset: value_start + 1,
+                });
+            }
+        }
+        pairs
+    }
+}
+
+// JSON con el parser del árbol: rutas "padre.clave" como en YAML, valores partidos en varias líneas y listas. None si
+// no es JSON válido (comentarios, comas finales): se vuelve al patrón por línea.
+fn parse_json(text: &str) -> Option<Vec<KeyValue>> {
+    let leaves = json::string_leaves(text).ok()?;
+    let mut pairs = Vec::new();
+    let (mut line, mut line_start, mut scanned) = (1, 0, 0);
+    for string in leaves {
+        if string.path.is_empty() || string.value.is_empty() {
+            continue;
+        }
+        for (i, b) in text.as_bytes()[scanned..string.offset].iter().enumerate() {
+            if *b == b'\n' {
+                line += 1;
+                line_start = scanned + i + 1;
+            }
+        }
+        scanned = string.offset;
+        pairs.push(KeyValue {
+            key: leaf(&string.path).to_string(),
+            path: string.path,
+            value: string.value,
+            line,
+            offset: string.offset - line_start,
+        });
+    }
+    Some(pairs)
+}
+
+// "CLAVE=valor" o "export CLAVE=valor" ocupando toda la línea, con clave de identificador y valor sin espacios.
+fn plain_assignment(line: &str, line_no: usize) -> Option<KeyValue> {
+    let trimmed = line.trim_start();
+    let body_start = line.len() - trimmed.len() + if trimmed.starts_with("export ") { 7 } else { 0 };
+    let (key, value) = line[body_start..].trim_end().split_once('=')?;
+    let mut chars = key.chars();
+    let identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !identifier || value.is_empty() || value.contains(char::is_whitespace) {
+        return None;
+    }
+    split_pair(line, body_start, &['='], line_no, "")
+}
+
+// dotenv y .properties: "export CLAVE=valor" o "clave: valor".
+fn parse_flat(text: &str, comments: &[&str]) -> Vec<KeyValue> {
+    let mut pairs = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || comments.iter().any(|c| trimmed.starts_with(c)) {
+            continue;
+        }
+        let body_start = line.len() - trimmed.len();
+        let body_start = body_start + if trimmed.starts_with("export ") { 7 } else { 0 };
+        if let Some(pair) = split_pair(line, body_start, &['=', ':'], index + 1, "") {
+            pairs.push(pair);
+        }
+    }
+    pairs
+}
+
+// INI y TOML: "[seccion]" seguido de "clave = valor"; la ruta es "seccion.clave".
+fn parse_sections(text: &str) -> Vec<KeyValue> {
+    let mut pairs = Vec::new();
+    let mut section = String::new();
+    for (index, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            section = trimmed.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+            continue;
+        }
+        let body_start = line.len() - line.trim_start().len();
+        if let Some(pair) = split_pair(line, body_start, &['='], index + 1, &section) {
+            pairs.push(pair);
+        }
+    }
+    pairs
+}
+
+// YAML por indentación: mapas anidados, listas "- CLAVE=valor" (docker-compose) y "- clave: valor".
+fn parse_yaml(text: &str) -> Vec<KeyValue> {
+    let mut pairs = Vec::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let content = strip_yaml_comment(line);
+        let trimmed = content.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with("---") || trimmed.starts_with("...") {
+            continue;
+        }
+        let mut indent = content.len() - trimmed.len();
+        let mut body_start = indent;
+        let mut in_list = false;
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            in_list = true;
+            body_start += 2 + (item.len() - item.trim_start().len());
+            indent = body_start;
+        }
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        let parent = stack.iter().map(|(_, key)| key.as_str()).collect::<Vec<_>>().join(".");
+        let body = &content[body_start..];
+
+        if let Some(colon) = yaml_key_end(body) {
+            let key = body[..colon].trim().trim_matches(|c| c == '"' || c == '\'');
+            let value = body[colon + 1..].trim();
+            if value.is_empty() || value == "|" || value == ">" || value.starts_with("|-") || value.starts_with(">-") {
+                stack.push((indent, key.to_string()));
+            } else if let Some(pair) = split_pair(&content, body_start, &[':'], index + 1, &parent) {
+                pairs.push(pair);
+            }
+        } else if in_list {
+            if let Some(pair) = split_pair(&content, body_start, &['='], index + 1, &parent) {
+                pairs.push(pair);
+            }
+        }
+    }
+    pairs
+}
+
+// Posición del ':' que termina una clave YAML ("clave:" o "clave: valor"), ignorando URLs.
+fn yaml_key_end(body: &str) -> Option<usize> {
+    let colon = body.find(':')?;
+    let after = body[colon + 1..].chars().next();
+    let key = &body[..colon];
+    if key.is_empty() || key.contains(' ') && !key.starts_with(['"', '\'']) {
+        return None;
+    }
+    match after {
+        None | Some(' ') | Some('\t') => Some(colon),
+        _ => None,
+    }
+}
+
+fn strip_yaml_comment(line: &str) -> String {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match c {
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '#' if !in_single && !in_double && prev.is_whitespace() => return line[..i].trim_end().to_string(),
+            _ => {}
+        }
+        prev = c;
+    }
+    line.trim_end().to_string()
+}
+
+// Separa "clave <sep> valor" a partir de body_start, quitando comillas y comentarios finales.
+fn split_pair(line: &str, body_start: usize, separators: &[char], line_no: usize, parent: &str) -> Option<KeyValue> {
+    let body = &line[body_start..];
+    let sep = body.find(separators)?;
+    let key = body[..sep].trim().trim_matches(|c| c == '"' || c == '\'');
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    let raw = &body[sep + 1..];
+    let lead = raw.len() - raw.trim_start().len();
+    let mut value = raw.trim();
+    let mut offset = body_start + sep + 1 + lead;
+    if let Some(q) = value.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        if let Some(close) = value[1..].find(q) {
+            value = &value[1..close + 1];
+            offset += 1;
+        }
+    } else if let Some(hash) = value.find(" #") {
+        value = value[..hash].trim_end();
+    }
+    if value.is_empty() {
+        return None;
+    }
+    let path = if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    };
+    Some(KeyValue {
+        path,
+        key: key.to_string(),
+        value: value.to_string(),
+        line: line_no,
+        offset,
+    })
+}
+
+fn leaf(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(format: Format, text: &str) -> Vec<(String, String, usize, usize)> {
+        Parsers::new()
+            .parse(format, text)
+            .into_iter()
+            .map(|p| (p.path, p.value, p.line, p.offset))
+            .collect()
+    }
+
+    fn pair(path: &str, value: &str, line: usize, offset: usize) -> (String, String, usize, usize) {
+        (path.to_string(), value.to_string(), line, offset)
+    }
+
+    #[test]
+    fn json_paths_are_dotted_like_yaml() {
+        let text = "{\n  \"api\": {\"token\": \"abc123\"},\n  \"db\": {\n    \"password\":\n      \"clave\\\"larga\"\n  },\n  \"hosts\": [\"uno\", {\"secret\": \"dos\"}],\n  \"puerto\": 5432\n}";
+        assert_eq!(
+            pairs(Format::Json, text),
+            [
+                pair("api.token", "abc123", 2, 20),
+                pair("db.password", "clave\"larga", 5, 7),
+                pair("hosts", "uno", 7, 13),
+                pair("hosts.secret", "dos", 7, 31),
+            ]
+        );
+        let keys: Vec<String> = Parsers::new()
+            .parse(Format::Json, text)
+            .into_iter()
+            .map(|p| p.key)
+            .collect();
+        assert_eq!(keys, ["token", "password", "hosts", "secret"]);
+    }
+
+    #[test]
+    fn invalid_json_falls_back_to_lines() {
+        let text = "{\n  // comentario\n  \"token\": \"abc123\",\n}";
+        assert_eq!(pairs(Format::Json, text), [pair("token", "abc123", 3, 12)]);
+    }
+
+    #[test]
+    fn source_splits_unquoted_assignments() {
+        let text = "PASSWORD=valor_ignorado1\nexport API_KEY=abc\nconnect(password=pw)\nlet x = 1\ntoken = \"def\"  # PASSWORD=otro\n";
+        assert_eq!(
+            pairs(Format::Source, text),
+            [
+                pair("PASSWORD", "valor_ignorado1", 1, 9),
+                pair("API_KEY", "abc", 2, 15),
+                pair("token", "def", 5, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn yaml_and_sections_keep_their_paths() {
+        let yaml = "db:\n  password: \"s3creto\"\nservices:\n  web:\n    environment:\n      - TOKEN=abc\n";
+        assert_eq!(
+            pairs(Format::Yaml, yaml),
+            [
+                pair("db.password", "s3creto", 2, 13),
+                pair("services.web.environment.TOKEN", "abc", 6, 14),
+            ]
+        );
+        let ini = "[mysql]\npassword = 'clave' \n; comentario\n";
+        assert_eq!(pairs(Format::Ini, ini), [pair("mysql.password", "clave", 2, 12)]);
+    }
+}
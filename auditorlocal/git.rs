@@ -0,0 +1,855 @@
+// Papiweb desarrollos informaticos
+// Lectura directa de un repositorio git en disco (objetos sueltos y packfiles), sin red ni binario git.
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::inflate::zlib_decompress;
+
+pub type Oid = [u8; 20];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub tree: Oid,
+    pub parents: Vec<Oid>,
+    pub author: String,
+    pub timestamp: i64,
+    pub tz_offset_minutes: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeEntry {
+    pub mode: u32,
+    pub oid: Oid,
+}
+
+impl TreeEntry {
+    pub fn is_tree(&self) -> bool {
+        self.mode == 0o40000
+    }
+
+    // Solo blobs regulares: se excluyen enlaces simbólicos (120000) y submódulos (160000).
+    pub fn is_file(&self) -> bool {
+        self.mode & 0o170000 == 0o100000
+    }
+}
+
+struct Pack {
+    path: PathBuf,
+    names: Vec<Oid>,
+    offsets: Vec<u64>,
+    // Offsets ordenados para conocer dónde termina cada objeto comprimido.
+    sorted_offsets: Vec<u64>,
+    data_end: u64,
+}
+
+// Objetos ya resueltos por (índice de pack, offset): evita reconstruir cadenas de deltas.
+type PackCache = HashMap<(usize, u64), (ObjectKind, Vec<u8>)>;
+
+pub struct Repository {
+    git_dir: PathBuf,
+    // En un worktree enlazado, el .git del repositorio principal: objetos, packed-refs y refs compartidas.
+    // HEAD y el índice siguen siendo propios de cada worktree.
+    common_dir: PathBuf,
+    packs: Vec<Pack>,
+    cache: RefCell<PackCache>,
+}
+
+const CACHE_LIMIT: usize = 512;
+
+impl Repository {
+    // Acepta la raíz de un working tree, un directorio .git o un repositorio bare.
+    pub fn open(path: &Path) -> Result<Repository, String> {
+        let git_dir = find_git_dir(path).ok_or_else(|| format!("{}: no es un repositorio git", path.display()))?;
+        let common_dir = match fs::read_to_string(git_dir.join("commondir")) {
+            Ok(text) => git_dir.join(text.trim()),
+            Err(_) => git_dir.clone(),
+        };
+        let mut packs = Vec::new();
+        if let Ok(entries) = fs::read_dir(common_dir.join("objects/pack")) {
+            let mut idx_files: Vec<PathBuf> = entries
+                .filter_map(Result::ok)
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "idx"))
+                .collect();
+            idx_files.sort();
+            for idx in idx_files {
+                packs.push(Pack::load(&idx)?);
+            }
+        }
+        Ok(Repository {
+            git_dir,
+            common_dir,
+            packs,
+            cache: RefCell::new(HashMap::new()),
+        })
+    }
+
+    // Todas las referencias: ramas locales, remotas y tags (sueltas y en packed-refs).
+    pub fn refs(&self) -> Vec<(String, Oid)> {
+        let mut refs = BTreeMap::new();
+        if let Ok(text) = fs::read_to_string(self.common_dir.join("packed-refs")) {
+            for line in text.lines() {
+                if line.starts_with('#') || line.starts_with('^') {
+                    continue;
+                }
+                if let Some((hex, name)) = line.split_once(' ') {
+                    if let Some(oid) = parse_hex(hex) {
+                        refs.insert(name.to_string(), oid);
+                    }
+                }
+            }
+        }
+        // Las refs propias del worktree (refs/bisect, refs/worktree) viven en su gitdir y tienen prioridad.
+        let mut roots = vec![&self.common_dir];
+        if self.git_dir != self.common_dir {
+            roots.push(&self.git_dir);
+        }
+        for root in roots {
+            Self::loose_refs(root, &mut refs);
+        }
+        refs.into_iter().collect()
+    }
+
+    fn loose_refs(root: &Path, refs: &mut BTreeMap<String, Oid>) {
+        let mut pending = vec![root.join("refs")];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(Result::ok) {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if let Some(oid) = fs::read_to_string(&path).ok().and_then(|t| parse_hex(t.trim())) {
+                    let name = path.strip_prefix(root).unwrap_or(&path);
+                    refs.insert(name.to_string_lossy().replace('\\', "/"), oid);
+                }
+            }
+        }
+    }
+
+    pub fn head(&self) -> Option<Oid> {
+        let text = fs::read_to_string(self.git_dir.join("HEAD")).ok()?;
+        let text = text.trim();
+        match text.strip_prefix("ref: ") {
+            Some(name) => self.refs().into_iter().find(|(n, _)| n == name).map(|(_, oid)| oid),
+            None => parse_hex(text),
+        }
+    }
+
+    // Contenido preparado con "git add": entradas del índice en etapa 0 (sin conflictos) por ruta completa.
+    // Respeta GIT_INDEX_FILE, que git define en el hook de "git commit -a" o "git commit <rutas>".
+    pub fn index(&self) -> Result<BTreeMap<String, TreeEntry>, String> {
+        let path = env::var_os("GIT_INDEX_FILE").map_or_else(|| self.git_dir.join("index"), PathBuf::from);
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            // Repositorio recién creado: todavía no hay índice.
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(format!("{}: {}", path.display(), err)),
+        };
+        let error = |message: &str| format!("{}: {}", path.display(), message);
+        if data.len() < 12 || &data[..4] != b"DIRC" {
+            return Err(error("índice inválido"));
+        }
+        let version = be32(&data[4..8]);
+        if !(2..=4).contains(&version) {
+            return Err(error(&format!("versión de índice {} no soportada", version)));
+        }
+        let mut entries = BTreeMap::new();
+        let mut previous: Vec<u8> = Vec::new();
+        let mut pos = 12;
+        for _ in 0..be32(&data[8..12]) {
+            let header = data.get(pos..pos + 62).ok_or_else(|| error("entrada truncada"))?;
+            let mode = be32(&header[24..28]);
+            let mut oid = [0u8; 20];
+            oid.copy_from_slice(&header[40..60]);
+            let flags = u16::from_be_bytes([header[60], header[61]]);
+            let mut name_start = pos + 62;
+            let mut intent_to_add = false;
+            if version >= 3 && flags & 0x4000 != 0 {
+                let extended = data
+                    .get(name_start..name_start + 2)
+                    .ok_or_else(|| error("entrada truncada"))?;
+                // "git add -N": la entrada existe pero su blob (vacío) no se escribió.
+                intent_to_add = u16::from_be_bytes([extended[0], extended[1]]) & 0x2000 != 0;
+                name_start += 2;
+            }
+            let mut name = Vec::new();
+            if version == 4 {
+                // Ruta comprimida respecto a la anterior: bytes a quitar (varint) y sufijo terminado en NUL.
+                let strip = offset_varint(&data, &mut name_start).map_err(|e| error(&e))?;
+                let keep = usize::try_from(strip)
+                    .ok()
+                    .and_then(|strip| previous.len().checked_sub(strip))
+                    .ok_or_else(|| error("ruta comprimida inválida"))?;
+                name.extend_from_slice(&previous[..keep]);
+            }
+            let end = data[name_start..]
+                .iter()
+                .position(|&b| b == 0)
+                .map(|i| name_start + i)
+                .ok_or_else(|| error("entrada truncada"))?;
+            name.extend_from_slice(&data[name_start..end]);
+            // Hasta la versión 3 cada entrada se rellena con NUL hasta un múltiplo de 8 bytes.
+            pos = if version == 4 {
+                end + 1
+            } else {
+                pos + (end - pos + 8) / 8 * 8
+            };
+            if (flags >> 12) & 3 == 0 && !intent_to_add {
+                entries.insert(String::from_utf8_lossy(&name).to_string(), TreeEntry { mode, oid });
+            }
+            previous = name;
+        }
+        Ok(entries)
+    }
+
+    pub fn read_object(&self, oid: &Oid) -> Result<(ObjectKind, Vec<u8>), String> {
+        let hex = to_hex(oid);
+        let loose = self.common_dir.join("objects").join(&hex[..2]).join(&hex[2..]);
+        if let Ok(compressed) = fs::read(&loose) {
+            let raw = zlib_decompress(&compressed).map_err(|e| format!("objeto {}: {}", hex, e))?;
+            let nul = raw.iter().position(|&b| b == 0).ok_or("objeto suelto sin cabecera")?;
+            let header = String::from_utf8_lossy(&raw[..nul]);
+            let kind = match header.split(' ').next() {
+                Some("commit") => ObjectKind::Commit,
+                Some("tree") => ObjectKind::Tree,
+                Some("blob") => ObjectKind::Blob,
+                Some("tag") => ObjectKind::Tag,
+                _ => return Err(format!("objeto {}: tipo desconocido '{}'", hex, header)),
+            };
+            return Ok((kind, raw[nul + 1..].to_vec()));
+        }
+        for (index, pack) in self.packs.iter().enumerate() {
+            if let Ok(pos) = pack.names.binary_search(oid) {
+                return self.read_packed(index, pack.offsets[pos]);
+            }
+        }
+        Err(format!("objeto {} no encontrado", hex))
+    }
+
+    pub fn commit(&self, oid: &Oid) -> Result<Commit, String> {
+        let (kind, data) = self.read_object(oid)?;
+        if kind != ObjectKind::Commit {
+            return Err(format!("{} no es un commit", to_hex(oid)));
+        }
+        let text = String::from_utf8_lossy(&data);
+        let mut commit = Commit {
+            tree: [0; 20],
+            parents: Vec::new(),
+            author: String::new(),
+            timestamp: 0,
+            tz_offset_minutes: 0,
+        };
+        for line in text.lines() {
+            if line.is_empty() {
+                break;
+            }
+            if let Some(hex) = line.strip_prefix("tree ") {
+                commit.tree = parse_hex(hex).ok_or("commit con árbol inválido")?;
+            } else if let Some(hex) = line.strip_prefix("parent ") {
+                commit.parents.extend(parse_hex(hex));
+            } else if let Some(author) = line.strip_prefix("author ") {
+                // "Nombre <email> 1700000000 -0300"
+                let mut parts = author.rsplitn(3, ' ');
+                let tz = parts.next().unwrap_or("+0000");
+                commit.timestamp = parts.next().and_then(|t| t.parse().ok()).unwrap_or(0);
+                commit.author = parts.next().unwrap_or("").to_string();
+                let sign = if tz.starts_with('-') { -1 } else { 1 };
+                let digits: i32 = tz.trim_start_matches(['+', '-']).parse().unwrap_or(0);
+                commit.tz_offset_minutes = sign * (digits / 100 * 60 + digits % 100);
+            }
+        }
+        Ok(commit)
+    }
+
+    pub fn tree(&self, oid: &Oid) -> Result<BTreeMap<String, TreeEntry>, String> {
+        let (kind, data) = self.read_object(oid)?;
+        if kind != ObjectKind::Tree {
+            return Err(format!("{} no es un árbol", to_hex(oid)));
+        }
+        let mut entries = BTreeMap::new();
+        let mut pos = 0;
+        while pos < data.len() {
+            let space = data[pos..].iter().position(|&b| b == b' ').ok_or("árbol corrupto")? + pos;
+            let nul = data[space..].iter().position(|&b| b == 0).ok_or("árbol corrupto")? + space;
+            let mode =
+                u32::from_str_radix(&String::from_utf8_lossy(&data[pos..space]), 8).map_err(|e| e.to_string())?;
+            let name = String::from_utf8_lossy(&data[space + 1..nul]).to_string();
+            let oid: Oid = data
+                .get(nul + 1..nul + 21)
+                .ok_or("árbol corrupto")?
+                .try_into()
+                .map_err(|_| "árbol corrupto")?;
+            entries.insert(name, TreeEntry { mode, oid });
+            pos = nul + 21;
+        }
+        Ok(entries)
+    }
+
+    // Sigue tags anotados hasta el objeto apuntado.
+    pub fn peel(&self, oid: Oid) -> Result<(ObjectKind, Oid), String> {
+        let mut current = oid;
+        for _ in 0..16 {
+            let (kind, data) = self.read_object(&current)?;
+            if kind != ObjectKind::Tag {
+                return Ok((kind, current));
+            }
+            let text = String::from_utf8_lossy(&data);
+            current = text
+                .lines()
+                .find_map(|l| l.strip_prefix("object ").and_then(parse_hex))
+                .ok_or("tag sin objeto")?;
+        }
+        Err("cadena de tags demasiado larga".to_string())
+    }
+
+    fn read_packed(&self, pack_index: usize, offset: u64) -> Result<(ObjectKind, Vec<u8>), String> {
+        if let Some(hit) = self.cache.borrow().get(&(pack_index, offset)) {
+            return Ok(hit.clone());
+        }
+        let pack = &self.packs[pack_index];
+        let raw = pack.read_raw(offset)?;
+        let mut pos = 0;
+        let mut byte = *raw.first().ok_or("objeto de pack vacío")?;
+        pos += 1;
+        let type_id = (byte >> 4) & 0x07;
+        while byte & 0x80 != 0 {
+            byte = *raw.get(pos).ok_or("packfile truncado")?;
+            pos += 1;
+        }
+        let object = match type_id {
+            1..=4 => {
+                let kind =
+                    [ObjectKind::Commit, ObjectKind::Tree, ObjectKind::Blob, ObjectKind::Tag][type_id as usize - 1];
+                (kind, zlib_decompress(&raw[pos..])?)
+            }
+            6 => {
+                // OFS_DELTA: la base está a una distancia negativa dentro del mismo pack.
+                let distance = offset_varint(&raw, &mut pos)?;
+                let base_offset = offset.checked_sub(distance).ok_or("delta con base inválida")?;
+                let (kind, base) = self.read_packed(pack_index, base_offset)?;
+                (kind, apply_delta(&base, &zlib_decompress(&raw[pos..])?)?)
+            }
+            7 => {
+                // REF_DELTA: la base se identifica por su hash.
+                let base_oid: Oid = raw.get(pos..pos + 20).ok_or("packfile truncado")?.try_into().unwrap();
+                let (kind, base) = self.read_object(&base_oid)?;
+                (kind, apply_delta(&base, &zlib_decompress(&raw[pos + 20..])?)?)
+            }
+            other => return Err(format!("tipo de objeto de pack desconocido: {}", other)),
+        };
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert((pack_index, offset), object.clone());
+        Ok(object)
+    }
+}
+
+impl Pack {
+    fn load(idx_path: &Path) -> Result<Pack, String> {
+        let idx = fs::read(idx_path).map_err(|e| format!("{}: {}", idx_path.display(), e))?;
+        if idx.len() < 8 + 256 * 4 || idx[..4] != [0xff, b't', b'O', b'c'] || be32(&idx[4..]) != 2 {
+            return Err(format!("{}: solo se soporta el formato idx v2", idx_path.display()));
+        }
+        let count = be32(&idx[8 + 255 * 4..]) as usize;
+        let names_start = 8 + 256 * 4;
+        let offsets_start = names_start + count * 20 + count * 4;
+        let large_start = offsets_start + count * 4;
+        if idx.len() < large_start {
+            return Err(format!("{}: idx truncado", idx_path.display()));
+        }
+        let names: Vec<Oid> = (0..count)
+            .map(|i| idx[names_start + i * 20..names_start + i * 20 + 20].try_into().unwrap())
+            .collect();
+        let mut offsets = Vec::with_capacity(count);
+        for i in 0..count {
+            let small = be32(&idx[offsets_start + i * 4..]);
+            let offset = if small & 0x8000_0000 != 0 {
+                let at = large_start + (small & 0x7fff_ffff) as usize * 8;
+                let bytes = idx.get(at..at + 8).ok_or("idx con offset de 64 bits truncado")?;
+                u64::from_be_bytes(bytes.try_into().unwrap())
+            } else {
+                small as u64
+            };
+            offsets.push(offset);
+        }
+        let path = idx_path.with_extension("pack");
+        let pack_len = fs::metadata(&path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .len();
+        let mut sorted_offsets = offsets.clone();
+        sorted_offsets.sort_unstable();
+        Ok(Pack {
+            path,
+            names,
+            offsets,
+            sorted_offsets,
+            // Los últimos 20 bytes del pack son su checksum.
+            data_end: pack_len.saturating_sub(20),
+        })
+    }
+
+    // Lee los bytes crudos de un objeto: desde su offset hasta el siguiente objeto del pack.
+    fn read_raw(&self, offset: u64) -> Result<Vec<u8>, String> {
+        let next = match self.sorted_offsets.binary_search(&offset) {
+            Ok(i) => self.sorted_offsets.get(i + 1).copied().unwrap_or(self.data_end),
+            Err(_) => return Err(format!("offset {} no pertenece al pack", offset)),
+        };
+        let mut file = fs::File::open(&self.path).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        let mut raw = vec![0u8; (next - offset) as usize];
+        file.read_exact(&mut raw).map_err(|e| e.to_string())?;
+        if raw.is_empty() {
+            return Err("objeto de pack vacío".to_string());
+        }
+        Ok(raw)
+    }
+}
+
+// Desplazamiento a la izquierda que falla en lugar de perder bits: los varints vienen de archivos del repositorio
+// auditado y uno malformado no puede tumbar la auditoría.
+fn shl_exact(value: u64, shift: u32) -> Option<u64> {
+    value.checked_shl(shift).filter(|shifted| shifted >> shift == value)
+}
+
+// Varint de git con "offset encoding" (distancia de OFS_DELTA, prefijo de ruta del índice v4): 7 bits por byte,
+// el grupo más significativo primero, y cada byte de continuación suma 1 antes de desplazar.
+fn offset_varint(data: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut byte = *data.get(*pos).ok_or("varint truncado")?;
+    *pos += 1;
+    let mut value = u64::from(byte & 0x7f);
+    while byte & 0x80 != 0 {
+        byte = *data.get(*pos).ok_or("varint truncado")?;
+        *pos += 1;
+        value = value
+            .checked_add(1)
+            .and_then(|v| shl_exact(v, 7))
+            .ok_or("varint fuera de rango")?
+            | u64::from(byte & 0x7f);
+    }
+    Ok(value)
+}
+
+// Varint de tamaño de los deltas: 7 bits por byte, el grupo menos significativo primero.
+fn size_varint(data: &[u8], pos: &mut usize) -> Result<usize, String> {
+    let mut value = 0u64;
+    let mut shift = 0u32;
+    loop {
+        let byte = *data.get(*pos).ok_or("delta truncado")?;
+        *pos += 1;
+        value |= shl_exact(u64::from(byte & 0x7f), shift).ok_or("varint fuera de rango")?;
+        if byte & 0x80 == 0 {
+            return usize::try_from(value).map_err(|_| "varint fuera de rango".to_string());
+        }
+        shift += 7;
+    }
+}
+
+fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>, String> {
+    let mut pos = 0;
+    let base_len = size_varint(delta, &mut pos)?;
+    let result_len = size_varint(delta, &mut pos)?;
+    if base_len != base.len() {
+        return Err("delta aplicado sobre una base de tamaño distinto".to_string());
+    }
+    let mut out = Vec::with_capacity(result_len.min(base.len() + delta.len()));
+    while pos < delta.len() {
+        let op = delta[pos];
+        pos += 1;
+        if op & 0x80 != 0 {
+            let mut copy_offset = 0usize;
+            let mut copy_len = 0usize;
+            for i in 0..4 {
+                if op & (1 << i) != 0 {
+                    copy_offset |= (*delta.get(pos).ok_or("delta truncado")? as usize) << (8 * i);
+                    pos += 1;
+                }
+            }
+            for i in 0..3 {
+                if op & (0x10 << i) != 0 {
+                    copy_len |= (*delta.get(pos).ok_or("delta truncado")? as usize) << (8 * i);
+                    pos += 1;
+                }
+            }
+            if copy_len == 0 {
+                copy_len = 0x10000;
+            }
+            let chunk = base
+                .get(copy_offset..copy_offset + copy_len)
+                .ok_or("delta copia fuera de la base")?;
+            out.extend_from_slice(chunk);
+        } else if op != 0 {
+            let chunk = delta.get(pos..pos + op as usize).ok_or("delta truncado")?;
+            out.extend_from_slice(chunk);
+            pos += op as usize;
+        } else {
+            return Err("delta con instrucción reservada".to_string());
+        }
+    }
+    if out.len() != result_len {
+        return Err("delta con tamaño de resultado inconsistente".to_string());
+    }
+    Ok(out)
+}
+
+fn find_git_dir(path: &Path) -> Option<PathBuf> {
+    let dot_git = path.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    // Worktrees y submódulos: ".git" es un archivo con "gitdir: <ruta>".
+    if let Ok(text) = fs::read_to_string(&dot_git) {
+        let target = PathBuf::from(text.trim().strip_prefix("gitdir: ")?);
+        return Some(if target.is_absolute() {
+            target
+        } else {
+            path.join(target)
+        });
+    }
+    // Repositorio bare o el gitdir de un worktree (sin objetos propios, con "commondir").
+    if (path.join("objects").is_dir() || path.join("commondir").is_file()) && path.join("HEAD").is_file() {
+        return Some(path.to_path_buf());
+    }
+    None
+}
+
+fn be32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+pub fn parse_hex(hex: &str) -> Option<Oid> {
+    if hex.len() != 40 {
+        return None;
+    }
+    let mut oid = [0u8; 20];
+    for (i, byte) in oid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(oid)
+}
+
+pub fn to_hex(oid: &Oid) -> String {
+    oid.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Directorio temporal propio de cada prueba; se borra al terminar.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let dir = env::temp_dir().join(format!("auditorlocal-git-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn write(path: &Path, data: &[u8]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    // Flujo zlib con un único bloque almacenado: basta para el lector, que no verifica el Adler-32.
+    fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        let len = data.len() as u16;
+        let mut out = vec![0x78, 0x01, 0x01];
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(data);
+        out.extend_from_slice(&[0; 4]);
+        out
+    }
+
+    fn write_loose(git_dir: &Path, oid: &Oid, kind: &str, body: &[u8]) {
+        let hex = to_hex(oid);
+        let mut raw = format!("{} {}\0", kind, body.len()).into_bytes();
+        raw.extend_from_slice(body);
+        write(
+            &git_dir.join("objects").join(&hex[..2]).join(&hex[2..]),
+            &zlib_stored(&raw),
+        );
+    }
+
+    // Blob, árbol con ese blob y commit que apunta al árbol; devuelve el oid del commit.
+    fn write_history(git_dir: &Path, seed: u8) -> Oid {
+        let (blob, tree, commit) = ([seed; 20], [seed + 1; 20], [seed + 2; 20]);
+        write_loose(git_dir, &blob, "blob", b"TOKEN=valor\n");
+        let mut entries = b"100644 config.env\0".to_vec();
+        entries.extend_from_slice(&blob);
+        write_loose(git_dir, &tree, "tree", &entries);
+        let body = format!(
+            "tree {}\nauthor Ana <ana@example.com> 1700000000 -0300\ncommitter Ana <ana@example.com> 1700000000 -0300\n\nmensaje\n",
+            to_hex(&tree)
+        );
+        write_loose(git_dir, &commit, "commit", body.as_bytes());
+        commit
+    }
+
+    fn index_file(version: u32, entries: &[(&str, u16, bool)]) -> Vec<u8> {
+        let mut out = b"DIRC".to_vec();
+        out.extend_from_slice(&version.to_be_bytes());
+        out.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        let mut previous = "";
+        for (i, &(name, stage, intent_to_add)) in entries.iter().enumerate() {
+            let start = out.len();
+            out.extend_from_slice(&[0; 24]);
+            out.extend_from_slice(&0o100644u32.to_be_bytes());
+            out.extend_from_slice(&[0; 12]);
+            out.extend_from_slice(&[i as u8 + 1; 20]);
+            let extended = if intent_to_add { 0x4000 } else { 0 };
+            out.extend_from_slice(&(stage << 12 | extended | name.len() as u16).to_be_bytes());
+            if intent_to_add {
+                out.extend_from_slice(&0x2000u16.to_be_bytes());
+            }
+            if version == 4 {
+                let common = previous.bytes().zip(name.bytes()).take_while(|(a, b)| a == b).count();
+                out.push((previous.len() - common) as u8);
+                out.extend_from_slice(&name.as_bytes()[common..]);
+                out.push(0);
+            } else {
+                out.extend_from_slice(name.as_bytes());
+                let padded = (out.len() - start + 8) / 8 * 8;
+                out.resize(start + padded, 0);
+            }
+            previous = name;
+        }
+        out
+    }
+
+    fn pack_header(kind: u8, size: usize) -> Vec<u8> {
+        let mut out = vec![(kind << 4) | (size & 0x0f) as u8];
+        let mut rest = size >> 4;
+        while rest > 0 {
+            *out.last_mut().unwrap() |= 0x80;
+            out.push((rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        out
+    }
+
+    #[test]
+    fn loose_objects_commits_and_trees() {
+        let tmp = TempDir::new("loose");
+        let git_dir = tmp.0.join(".git");
+        let commit = write_history(&git_dir, 0x10);
+        write(
+            &git_dir.join("refs/heads/main"),
+            format!("{}\n", to_hex(&commit)).as_bytes(),
+        );
+        write(&git_dir.join("HEAD"), b"ref: refs/heads/main\n");
+
+        let repo = Repository::open(&tmp.0).unwrap();
+        assert_eq!(repo.head(), Some(commit));
+        assert_eq!(repo.refs(), vec![("refs/heads/main".to_string(), commit)]);
+        let parsed = repo.commit(&commit).unwrap();
+        assert_eq!(parsed.author, "Ana <ana@example.com>");
+        assert_eq!((parsed.timestamp, parsed.tz_offset_minutes), (1_700_000_000, -180));
+        let tree = repo.tree(&parsed.tree).unwrap();
+        assert!(tree["config.env"].is_file());
+        assert_eq!(
+            repo.read_object(&tree["config.env"].oid).unwrap(),
+            (ObjectKind::Blob, b"TOKEN=valor\n".to_vec())
+        );
+        assert!(repo.commit(&parsed.tree).is_err());
+        assert!(repo.read_object(&[0xee; 20]).unwrap_err().contains("no encontrado"));
+    }
+
+    #[test]
+    fn packed_objects_and_deltas() {
+        let tmp = TempDir::new("pack");
+        let git_dir = tmp.0.join(".git");
+        write(&git_dir.join("HEAD"), b"ref: refs/heads/main\n");
+        let base = b"linea uno\nlinea dos\n";
+        // Copia los 10 primeros bytes de la base e inserta "linea tres\n".
+        let mut delta = vec![base.len() as u8, 21, 0x90, 10, 11];
+        delta.extend_from_slice(b"linea tres\n");
+
+        let mut pack = b"PACK".to_vec();
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&4u32.to_be_bytes());
+        let base_offset = pack.len();
+        pack.extend(pack_header(3, base.len()));
+        pack.extend(zlib_stored(base));
+        let ofs_offset = pack.len();
+        pack.extend(pack_header(6, delta.len()));
+        pack.push((ofs_offset - base_offset) as u8);
+        pack.extend(zlib_stored(&delta));
+        let ref_offset = pack.len();
+        pack.extend(pack_header(7, delta.len()));
+        pack.extend_from_slice(&[0xaa; 20]);
+        pack.extend(zlib_stored(&delta));
+        // OFS_DELTA cortado en medio de la distancia.
+        let truncated_offset = pack.len();
+        pack.extend(pack_header(6, delta.len()));
+        pack.push(0x81);
+        pack.extend_from_slice(&[0; 20]);
+
+        let objects = [
+            ([0xaa; 20], base_offset),
+            ([0xbb; 20], ofs_offset),
+            ([0xcc; 20], ref_offset),
+            ([0xdd; 20], truncated_offset),
+        ];
+        let mut idx = vec![0xff, b't', b'O', b'c', 0, 0, 0, 2];
+        for byte in 0..=255u8 {
+            let count = objects.iter().filter(|(oid, _)| oid[0] <= byte).count() as u32;
+            idx.extend_from_slice(&count.to_be_bytes());
+        }
+        for (oid, _) in &objects {
+            idx.extend_from_slice(oid);
+        }
+        idx.extend(vec![0; objects.len() * 4]);
+        for (_, offset) in &objects {
+            idx.extend_from_slice(&(*offset as u32).to_be_bytes());
+        }
+        write(&git_dir.join("objects/pack/pack-prueba.pack"), &pack);
+        write(&git_dir.join("objects/pack/pack-prueba.idx"), &idx);
+
+        let repo = Repository::open(&tmp.0).unwrap();
+        assert_eq!(
+            repo.read_object(&[0xaa; 20]).unwrap(),
+            (ObjectKind::Blob, base.to_vec())
+        );
+        for oid in [[0xbb; 20], [0xcc; 20]] {
+            assert_eq!(
+                repo.read_object(&oid).unwrap(),
+                (ObjectKind::Blob, b"linea uno\nlinea tres\n".to_vec())
+            );
+        }
+        assert!(apply_delta(b"corta", &delta).is_err());
+        assert!(repo.read_object(&[0xdd; 20]).is_err());
+    }
+
+    // Regresión: los varints de packs e índices se leían sin comprobar límites y el desplazamiento podía desbordar.
+    #[test]
+    fn malformed_varints_are_errors() {
+        let offset = |data: &[u8]| offset_varint(data, &mut 0);
+        assert_eq!(offset(&[0x05]), Ok(5));
+        assert_eq!(offset(&[0x80, 0x00]), Ok(128));
+        assert_eq!(offset(&[0x81, 0x7f]), Ok(((1 + 1) << 7) | 0x7f));
+        assert!(offset(&[]).is_err());
+        assert!(offset(&[0x81]).is_err());
+        assert!(offset(&[0xff; 12]).is_err());
+        let mut long = vec![0xff; 10];
+        long.push(0x7f);
+        assert!(offset(&long).unwrap_err().contains("fuera de rango"));
+
+        let size = |data: &[u8]| size_varint(data, &mut 0);
+        assert_eq!(size(&[0x80, 0x01]), Ok(128));
+        assert!(size(&[0x80]).is_err());
+        let mut long = vec![0x80; 10];
+        long.push(0x01);
+        assert!(size(&long).unwrap_err().contains("fuera de rango"));
+        // 9 grupos llenan 63 bits: un décimo con más de un bit ya no cabe en 64.
+        let mut edge = vec![0x80; 9];
+        edge.push(0x01);
+        assert_eq!(size_varint(&edge, &mut 0).map(|n| n as u64), Ok(1 << 63));
+        edge[9] = 0x02;
+        assert!(size(&edge).is_err());
+
+        assert!(apply_delta(b"", &[0x85]).is_err());
+        assert!(apply_delta(b"", &[0x00, 0xff, 0xff, 0xff, 0xff, 0x0f]).is_err());
+    }
+
+    #[test]
+    fn index_versions_two_to_four() {
+        let tmp = TempDir::new("index");
+        let git_dir = tmp.0.join(".git");
+        write_history(&git_dir, 0x10);
+        write(&git_dir.join("HEAD"), b"ref: refs/heads/main\n");
+        let repo = Repository::open(&tmp.0).unwrap();
+        assert!(repo.index().unwrap().is_empty());
+
+        let entries = [
+            ("src/config/a.env", 0, false),
+            ("src/config/b.env", 0, false),
+            ("src/conflicto.rs", 2, false),
+            ("src/main.rs", 0, false),
+        ];
+        for version in [2, 3, 4] {
+            write(&git_dir.join("index"), &index_file(version, &entries));
+            let index = repo.index().unwrap();
+            let names: Vec<&str> = index.keys().map(String::as_str).collect();
+            assert_eq!(
+                names,
+                ["src/config/a.env", "src/config/b.env", "src/main.rs"],
+                "versión {}",
+                version
+            );
+            assert_eq!(index["src/main.rs"].oid, [4; 20]);
+        }
+        // "git add -N" (solo desde la versión 3): la entrada existe pero no tiene contenido preparado.
+        write(
+            &git_dir.join("index"),
+            &index_file(3, &[("nuevo.env", 0, true), ("viejo.env", 0, false)]),
+        );
+        assert_eq!(repo.index().unwrap().keys().collect::<Vec<_>>(), ["viejo.env"]);
+        write(&git_dir.join("index"), &index_file(5, &entries));
+        assert!(repo.index().unwrap_err().contains("no soportada"));
+    }
+
+    #[test]
+    fn linked_worktree_uses_the_common_dir() {
+        let tmp = TempDir::new("worktree");
+        let git_dir = tmp.0.join("principal/.git");
+        let main = write_history(&git_dir, 0x10);
+        let feature = write_history(&git_dir, 0x20);
+        write(&git_dir.join("HEAD"), b"ref: refs/heads/main\n");
+        write(
+            &git_dir.join("refs/heads/main"),
+            format!("{}\n", to_hex(&main)).as_bytes(),
+        );
+        write(
+            &git_dir.join("packed-refs"),
+            format!(
+                "# pack-refs with: peeled fully-peeled sorted\n{} refs/heads/feature\n",
+                to_hex(&feature)
+            )
+            .as_bytes(),
+        );
+        let worktree_git_dir = git_dir.join("worktrees/rama");
+        write(&worktree_git_dir.join("HEAD"), b"ref: refs/heads/feature\n");
+        write(&worktree_git_dir.join("commondir"), b"../..\n");
+        write(
+            &worktree_git_dir.join("index"),
+            &index_file(2, &[("solo-en-rama.env", 0, false)]),
+        );
+        write(&tmp.0.join("rama/.git"), b"gitdir: ../principal/.git/worktrees/rama\n");
+
+        for path in [tmp.0.join("rama"), worktree_git_dir] {
+            let repo = Repository::open(&path).unwrap();
+            assert_eq!(repo.head(), Some(feature));
+            assert_eq!(repo.refs().len(), 2);
+            assert_eq!(repo.commit(&feature).unwrap().tree, [0x21; 20]);
+            assert_eq!(repo.index().unwrap().keys().collect::<Vec<_>>(), ["solo-en-rama.env"]);
+        }
+        let repo = Repository::open(&tmp.0.join("principal")).unwrap();
+        assert_eq!(repo.head(), Some(main));
+        assert!(repo.index().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+// Papiweb desarrollos informaticos
+// Reglas .gitignore: cada patrón glob se traduce al motor de patrones interno.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::pattern::Pattern;
+
+struct Rule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+}
+
+// Reglas de un único .gitignore, relativas al directorio que lo contiene.
+pub struct IgnoreFile {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl IgnoreFile {
+    pub fn load(dir: &Path) -> Option<IgnoreFile> {
+        let text = fs::read_to_string(dir.join(".gitignore")).ok()?;
+        let rules: Vec<Rule> = text.lines().filter_map(parse_rule).collect();
+        if rules.is_empty() {
+            return None;
+        }
+        Some(IgnoreFile {
+            base: dir.to_path_buf(),
+            rules,
+        })
+    }
+
+    // Some(true) ignorado, Some(false) re-incluido con '!', None sin opinión.
+    fn decide(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?;
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (!rule.dir_only || is_dir) && rule.pattern.is_match(&relative))
+            .map(|rule| !rule.negated)
+    }
+}
+
+// Pila de .gitignore activos durante el recorrido; el más profundo tiene prioridad.
+// Clonarla es barato: el recorrido en paralelo lleva una copia por directorio pendiente.
+#[derive(Default, Clone)]
+pub struct IgnoreStack {
+    files: Vec<(usize, Arc<IgnoreFile>)>,
+}
+
+impl IgnoreStack {
+    pub fn enter(&mut self, depth: usize, dir: &Path) {
+        self.files.retain(|(level, _)| *level < depth);
+        if let Some(file) = IgnoreFile::load(dir) {
+            self.files.push((depth, Arc::new(file)));
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.files
+            .iter()
+            .rev()
+            .find_map(|(_, file)| file.decide(path, is_dir))
+            .unwrap_or(false)
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    // Sin '/' intermedia el patrón aplica a cualquier profundidad.
+    let anchored = line.contains('/');
+    let glob = line.trim_start_matches('/');
+    let prefix = if anchored { "^" } else { "^(.*/)?" };
+    let pattern = Pattern::new(&format!("{}{}$", prefix, glob_to_regex(glob))).ok()?;
+    Some(Rule {
+        pattern,
+        negated,
+        dir_only,
+    })
+}
+
+pub fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match chars[i..].iter().position(|&c| c == ']') {
+                Some(close) => {
+                    let class: String = chars[i + 1..i + close].iter().collect();
+                    regex.push('[');
+                    regex.push_str(&class.replacen('!', "^", usize::from(class.starts_with('!'))));
+                    regex.push(']');
+                    i += close + 1;
+                    continue;
+                }
+                None => regex.push_str("\\["),
+            },
+            c if c.is_alphanumeric() || c == '/' || c == '_' => regex.push(c),
+            c => {
+                regex.push('\\');
+                regex.push(c);
+            }
+        }
+        i += 1;
+    }
+    regex
+}
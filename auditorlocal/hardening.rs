@@ -0,0 +1,40 @@
+// Papiweb desarrollos informaticos
+// Hallazgos de configuración del host (servicios expuestos, sshd_config...): no hay secreto que rotar sino un ajuste
+// que corregir. Usan el mismo registro Finding que los secretos para compartir baseline, --fail-on y JSON/SARIF.
+use crate::finding::{AuditPriority, BaselineStatus, Confidence, Finding, Location, Note, Secret, SecretKind};
+
+// Nota con la corrección concreta: el plan de remediación la usa como reemplazo.
+pub const FIX_NOTE: &str = "fix";
+
+// `evidence` es lo encontrado (la línea de configuración, la dirección): la baseline lo compara entre ejecuciones.
+pub fn finding(
+    rule_id: &str,
+    priority: AuditPriority,
+    location: Location,
+    evidence: &str,
+    text: String,
+    fix: String,
+) -> Finding {
+    Finding {
+        rule_id: rule_id.to_string(),
+        kind: SecretKind::Misconfiguration,
+        priority,
+        confidence: Confidence::High,
+        location,
+        secret: Secret::new(evidence),
+        status: BaselineStatus::New,
+        compromised: None,
+        notes: vec![
+            Note {
+                id: "detail",
+                priority,
+                text,
+            },
+            Note {
+                id: FIX_NOTE,
+                priority: AuditPriority::Low,
+                text: fix,
+            },
+        ],
+    }
+}
@@ -0,0 +1,198 @@
+// Papiweb desarrollos informaticos
+// Escaneo del historial git: secretos añadidos en cualquier commit de cualquier rama.
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::dates;
+use crate::filescan::{FileScanner, ScanStats};
+use crate::finding::{Finding, Location};
+use crate::git::{self, ObjectKind, Oid, Repository};
+
+struct Change {
+    path: String,
+    old: Option<Oid>,
+    new: Oid,
+}
+
+pub struct HistoryScanner<'a> {
+    repo: &'a Repository,
+    scanner: &'a FileScanner<'a>,
+    max_commits: Option<usize>,
+}
+
+impl<'a> HistoryScanner<'a> {
+    pub fn new(repo: &'a Repository, scanner: &'a FileScanner<'a>, max_commits: Option<usize>) -> Self {
+        Self {
+            repo,
+            scanner,
+            max_commits,
+        }
+    }
+
+    pub fn scan(&self, stats: &mut ScanStats) -> Result<Vec<Finding>, String> {
+        let mut findings = Vec::new();
+        for oid in self.commits()? {
+            let commit = self.repo.commit(&oid)?;
+            stats.commits_scanned += 1;
+            // Los merges no introducen contenido propio salvo al resolver conflictos; se omiten como "git log -p".
+            if commit.parents.len() > 1 {
+                continue;
+            }
+            let parent_tree = match commit.parents.first() {
+                Some(parent) => Some(self.repo.commit(parent)?.tree),
+                None => None,
+            };
+            let mut changes = Vec::new();
+            self.diff_trees(parent_tree.as_ref(), &commit.tree, "", &mut changes)?;
+
+            let hex = git::to_hex(&oid);
+            let date = dates::format_timestamp(commit.timestamp, commit.tz_offset_minutes);
+            for change in changes {
+                for finding in added_findings(
+                    self.repo,
+                    self.scanner,
+                    &change.path,
+                    change.old.as_ref(),
+                    &change.new,
+                    stats,
+                )? {
+                    let Location::File { path, line, column, .. } = finding.location else {
+                        continue;
+                    };
+                    findings.push(Finding {
+                        location: Location::Commit {
+                            commit: hex.clone(),
+                            author: commit.author.clone(),
+                            date: date.clone(),
+                            path,
+                            line,
+                            column,
+                            at_head: false,
+                        },
+                        ..finding
+                    });
+                }
+            }
+        }
+
+        let at_head = self.head_secrets()?;
+        for finding in &mut findings {
+            if let Location::Commit { at_head: present, .. } = &mut finding.location {
+                *present = at_head.contains(&(finding.rule_id.clone(), finding.secret.expose().to_string()));
+            }
+        }
+        Ok(findings)
+    }
+
+    // Commits alcanzables desde todas las ramas, tags y HEAD, cada uno una sola vez.
+    fn commits(&self) -> Result<Vec<Oid>, String> {
+        let mut tips: Vec<Oid> = self.repo.refs().into_iter().map(|(_, oid)| oid).collect();
+        tips.extend(self.repo.head());
+        let mut pending = VecDeque::new();
+        for tip in tips {
+            if let Ok((ObjectKind::Commit, oid)) = self.repo.peel(tip) {
+                pending.push_back(oid);
+            }
+        }
+        let mut seen = HashSet::new();
+        let mut order = Vec::new();
+        while let Some(oid) = pending.pop_front() {
+            if !seen.insert(oid) {
+                continue;
+            }
+            if self.max_commits.is_some_and(|max| order.len() >= max) {
+                break;
+            }
+            order.push(oid);
+            pending.extend(self.repo.commit(&oid)?.parents);
+        }
+        Ok(order)
+    }
+
+    fn diff_trees(&self, old: Option<&Oid>, new: &Oid, prefix: &str, out: &mut Vec<Change>) -> Result<(), String> {
+        if old == Some(new) {
+            return Ok(());
+        }
+        let old_entries = match old {
+            Some(oid) => self.repo.tree(oid)?,
+            None => Default::default(),
+        };
+        for (name, entry) in self.repo.tree(new)? {
+            let previous = old_entries.get(&name);
+            if previous.is_some_and(|p| p.oid == entry.oid) {
+                continue;
+            }
+            let path = format!("{}{}", prefix, name);
+            if entry.is_tree() {
+                let old_subtree = previous.filter(|p| p.is_tree()).map(|p| &p.oid);
+                self.diff_trees(old_subtree, &entry.oid, &format!("{}/", path), out)?;
+            } else if entry.is_file() {
+                out.push(Change {
+                    path,
+                    old: previous.filter(|p| p.is_file()).map(|p| p.oid),
+                    new: entry.oid,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Secretos presentes en el árbol de HEAD, para marcar los que siguen expuestos.
+    fn head_secrets(&self) -> Result<HashSet<(String, String)>, String> {
+        let mut secrets = HashSet::new();
+        let Some(head) = self.repo.head() else {
+            return Ok(secrets);
+        };
+        let tree = self.repo.commit(&head)?.tree;
+        let mut stats = ScanStats::default();
+        let mut pending = vec![(tree, PathBuf::new())];
+        while let Some((oid, prefix)) = pending.pop() {
+            for (name, entry) in self.repo.tree(&oid)? {
+                let path = prefix.join(&name);
+                if entry.is_tree() {
+                    pending.push((entry.oid, path));
+                } else if entry.is_file() {
+                    let (_, data) = self.repo.read_object(&entry.oid)?;
+                    for finding in self.scanner.scan_bytes(&path, &data, &mut stats) {
+                        secrets.insert((finding.rule_id, finding.secret.expose().to_string()));
+                    }
+                }
+            }
+        }
+        Ok(secrets)
+    }
+}
+
+// Hallazgos del blob nuevo limitados a las líneas que no existían en la versión anterior.
+pub fn added_findings(
+    repo: &Repository,
+    scanner: &FileScanner,
+    path: &str,
+    old: Option<&Oid>,
+    new: &Oid,
+    stats: &mut ScanStats,
+) -> Result<Vec<Finding>, String> {
+    let (_, new) = repo.read_object(new)?;
+    let old_lines: HashSet<String> = match old {
+        Some(oid) => {
+            let (_, old) = repo.read_object(oid)?;
+            String::from_utf8_lossy(&old).lines().map(str::to_string).collect()
+        }
+        None => HashSet::new(),
+    };
+    let text = String::from_utf8_lossy(&new);
+    let added: HashSet<usize> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !old_lines.contains(*line))
+        .map(|(index, _)| index + 1)
+        .collect();
+    if added.is_empty() {
+        return Ok(Vec::new());
+    }
+    let findings = scanner.scan_bytes(Path::new(path), &new, stats);
+    Ok(findings
+        .into_iter()
+        .filter(|f| matches!(f.location, Location::File { line, .. } if added.contains(&line)))
+        .collect())
+}
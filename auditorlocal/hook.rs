@@ -0,0 +1,62 @@
+// Papiweb desarrollos informaticos
+// Modo pre-commit: solo lo que el commit va a introducir, es decir las líneas nuevas del índice frente a HEAD.
+// Se lee el índice en disco, así que lo analizado es exactamente lo preparado y no el working tree.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::filescan::{FileScanner, ScanStats};
+use crate::finding::{Finding, Location};
+use crate::git::{Oid, Repository};
+use crate::history;
+use crate::permissions;
+
+// Repositorio que contiene la ruta: el hook puede ejecutarse desde cualquier subdirectorio.
+pub fn open_repository(path: &Path) -> Result<Repository, String> {
+    let path = path.canonicalize().map_err(|e| format!("{}: {}", path.display(), e))?;
+    path.ancestors()
+        .find_map(|dir| Repository::open(dir).ok())
+        .ok_or_else(|| format!("{}: no está dentro de un repositorio git", path.display()))
+}
+
+pub fn scan_staged(repo: &Repository, scanner: &FileScanner, stats: &mut ScanStats) -> Result<Vec<Finding>, String> {
+    let mut head_files = BTreeMap::new();
+    if let Some(head) = repo.head() {
+        let tree = repo.commit(&head)?.tree;
+        collect_files(repo, &tree, "", &mut head_files)?;
+    }
+    let mut findings = Vec::new();
+    for (path, entry) in repo.index()? {
+        let previous = head_files.get(&path);
+        if !entry.is_file() || previous == Some(&entry.oid) {
+            continue;
+        }
+        let mut added = history::added_findings(repo, scanner, &path, previous, &entry.oid, stats)?;
+        permissions::annotate_staged(&mut added, entry.mode);
+        for finding in added {
+            let Location::File { line, column, key, .. } = finding.location else {
+                continue;
+            };
+            // Rutas como las de "scan ." desde la raíz del repositorio: la misma baseline sirve para ambos.
+            let location = Location::File {
+                path: Path::new(".").join(&path),
+                line,
+                column,
+                key,
+            };
+            findings.push(Finding { location, ..finding });
+        }
+    }
+    Ok(findings)
+}
+
+fn collect_files(repo: &Repository, tree: &Oid, prefix: &str, out: &mut BTreeMap<String, Oid>) -> Result<(), String> {
+    for (name, entry) in repo.tree(tree)? {
+        let path = format!("{}{}", prefix, name);
+        if entry.is_tree() {
+            collect_files(repo, &entry.oid, &format!("{}/", path), out)?;
+        } else if entry.is_file() {
+            out.insert(path, entry.oid);
+        }
+    }
+    Ok(())
+}
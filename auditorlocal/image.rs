@@ -0,0 +1,453 @@
+// Papiweb desarrollos informaticos
+// Imágenes de contenedor guardadas con "docker save" (formato Docker u OCI, también .tar.gz): Env, Cmd,
+// Entrypoint e historial de la configuración, y todas las capas. Un archivo borrado u ocultado en una capa
+// posterior sigue dentro del tar de su capa: cualquiera con la imagen puede extraerlo.
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use crate::cmdline;
+use crate::detectors::Detector;
+use crate::filescan::{FileScanner, ScanStats};
+use crate::finding::{Finding, Location};
+use crate::inflate;
+use crate::json::{self, Value};
+use crate::tar::{self, Entry, EntryKind};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const WHITEOUT_PREFIX: &str = ".wh.";
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+// Árboles del gestor de paquetes y de dependencias: miles de archivos sin secretos propios.
+const PACKAGE_PREFIXES: &[&str] = &[
+    "usr/share/",
+    "usr/lib/",
+    "usr/lib64/",
+    "usr/include/",
+    "lib/",
+    "lib64/",
+    "var/lib/dpkg/",
+    "var/lib/apt/",
+    "var/lib/rpm/",
+    "var/cache/",
+];
+const PACKAGE_DIRS: &[&str] = &["node_modules", "site-packages", "dist-packages", "__pycache__"];
+
+#[derive(Debug, Default)]
+pub struct ImageStats {
+    pub images_scanned: u64,
+    pub layers_scanned: u64,
+    pub layers_unsupported: u64,
+}
+
+struct Manifest {
+    name: String,
+    config: String,
+    layers: Vec<String>,
+}
+
+// Qué deja cada capa en el sistema de archivos final: archivos propios, borrados y directorios opacos.
+#[derive(Default)]
+struct LayerListing {
+    files: HashSet<String>,
+    whiteouts: Vec<String>,
+    opaque_dirs: Vec<String>,
+}
+
+impl LayerListing {
+    fn hides(&self, path: &str) -> bool {
+        let under = |dir: &String| {
+            path.strip_prefix(dir.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+        };
+        self.files.contains(path)
+            || self.whiteouts.iter().any(|w| w == path || under(w))
+            || self.opaque_dirs.iter().any(under)
+    }
+}
+
+pub fn scan_image(
+    path: &Path,
+    detector: &Detector,
+    scanner: &FileScanner,
+    stats: &mut ImageStats,
+    scan_stats: &mut ScanStats,
+) -> Result<Vec<Finding>, String> {
+    let mut file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut magic = [0u8; 2];
+    let compressed = file.read_exact(&mut magic).is_ok() && magic == GZIP_MAGIC;
+    file.rewind().map_err(|e| e.to_string())?;
+    let mut image = ImageArchive {
+        detector,
+        scanner,
+        stats,
+        scan_stats,
+    };
+    if compressed {
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(|e| e.to_string())?;
+        image.scan(&mut Cursor::new(inflate::gzip_decompress(&data)?))
+    } else {
+        image.scan(&mut file)
+    }
+}
+
+struct ImageArchive<'a> {
+    detector: &'a Detector,
+    scanner: &'a FileScanner<'a>,
+    stats: &'a mut ImageStats,
+    scan_stats: &'a mut ScanStats,
+}
+
+impl ImageArchive<'_> {
+    fn scan<R: Read + Seek>(&mut self, reader: &mut R) -> Result<Vec<Finding>, String> {
+        let entries = tar::index(reader)?;
+        let mut findings = Vec::new();
+        for manifest in manifests(reader, &entries)? {
+            self.stats.images_scanned += 1;
+            let config = read_json(reader, &entries, &manifest.config)?;
+            findings.extend(self.config_findings(&manifest.name, &config));
+            let diff_ids: Vec<&str> = config
+                .get("rootfs")
+                .and_then(|r| r.get("diff_ids"))
+                .and_then(Value::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(Value::as_str)
+                .collect();
+
+            let mut listings: Vec<LayerListing> = Vec::new();
+            let mut layer_findings: Vec<(usize, Finding)> = Vec::new();
+            for (index, layer) in manifest.layers.iter().enumerate() {
+                let digest = diff_ids
+                    .get(index)
+                    .map_or_else(|| layer_digest(layer), |d| d.to_string());
+                let Some(data) = self.layer_data(reader, &entries, layer)? else {
+                    listings.push(LayerListing::default());
+                    continue;
+                };
+                let (listing, found) = self.scan_layer(&manifest.name, &digest, &data)?;
+                listings.push(listing);
+                layer_findings.extend(found.into_iter().map(|f| (index, f)));
+            }
+            for (index, mut finding) in layer_findings {
+                if let Location::Image { path, in_final, .. } = &mut finding.location {
+                    *in_final = !listings[index + 1..].iter().any(|later| later.hides(path));
+                }
+                findings.push(finding);
+            }
+        }
+        Ok(findings)
+    }
+
+    // Contenido descomprimido de la capa, o None si usa una compresión no soportada (zstd).
+    fn layer_data<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        entries: &[Entry],
+        layer: &str,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let data = member(entries, layer)?.read(reader)?;
+        if data.starts_with(&ZSTD_MAGIC) {
+            self.stats.layers_unsupported += 1;
+            return Ok(None);
+        }
+        if data.starts_with(&GZIP_MAGIC) {
+            return inflate::gzip_decompress(&data)
+                .map(Some)
+                .map_err(|e| format!("capa {}: {}", layer, e));
+        }
+        Ok(Some(data))
+    }
+
+    fn scan_layer(&mut self, image: &str, digest: &str, data: &[u8]) -> Result<(LayerListing, Vec<Finding>), String> {
+        let entries = tar::index(&mut Cursor::new(data)).map_err(|e| format!("capa {}: {}", digest, e))?;
+        self.stats.layers_scanned += 1;
+        let mut listing = LayerListing::default();
+        let mut findings = Vec::new();
+        for entry in &entries {
+            let (parent, name) = entry.path.rsplit_once('/').unwrap_or(("", &entry.path));
+            if name == OPAQUE_WHITEOUT {
+                listing.opaque_dirs.push(parent.to_string());
+                continue;
+            }
+            if let Some(deleted) = name.strip_prefix(WHITEOUT_PREFIX) {
+                listing.whiteouts.push(join(parent, deleted));
+                continue;
+            }
+            if entry.kind != EntryKind::File {
+                continue;
+            }
+            listing.files.insert(entry.path.clone());
+            if is_package_path(&entry.path) {
+                self.scan_stats.skipped_ignored += 1;
+                continue;
+            }
+            let found = self
+                .scanner
+                .scan_bytes(Path::new(&entry.path), entry.slice(data), self.scan_stats);
+            for finding in found {
+                let Location::File { line, column, key, .. } = finding.location else {
+                    continue;
+                };
+                findings.push(Finding {
+                    location: Location::Image {
+                        image: image.to_string(),
+                        layer: Some(digest.to_string()),
+                        path: entry.path.clone(),
+                        line,
+                        column,
+                        key,
+                        in_final: true,
+                    },
+                    ..finding
+                });
+            }
+        }
+        Ok((listing, findings))
+    }
+
+    // Env, Cmd/Entrypoint e historial (ENV, ARG de build y RUN quedan registrados en texto plano).
+    fn config_findings(&self, image: &str, config: &Value) -> Vec<Finding> {
+        let location = |path: &str, line: usize, column: usize, key: String| Location::Image {
+            image: image.to_string(),
+            layer: None,
+            path: path.to_string(),
+            line,
+            column,
+            key: Some(key),
+            in_final: true,
+        };
+        let mut findings: Vec<Finding> = Vec::new();
+        let settings = config.get("config").or_else(|| config.get("Config"));
+        let strings = |key: &str| -> Vec<&str> {
+            settings
+                .and_then(|s| s.get(key))
+                .and_then(Value::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(Value::as_str)
+                .collect()
+        };
+
+        for (index, variable) in strings("Env").into_iter().enumerate() {
+            let Some((name, value)) = variable.split_once('=') else {
+                continue;
+            };
+            for detection in self.detector.scan_pair(name, value) {
+                let column = name.chars().count() + value[..detection.start].chars().count() + 2;
+                findings
+                    .push(detection.into_finding(value, location("Config.Env", index + 1, column, name.to_string())));
+            }
+        }
+        for key in ["Entrypoint", "Cmd"] {
+            let command = strings(key).join(" ");
+            for hit in cmdline::scan_command(self.detector, &command) {
+                let column = command[..hit.detection.start].chars().count() + 1;
+                let path = format!("Config.{}", key);
+                findings.push(
+                    hit.detection
+                        .into_finding(&command, location(&path, 1, column, hit.key)),
+                );
+            }
+        }
+
+        let history = config.get("history").and_then(Value::as_array).unwrap_or_default();
+        for (index, step) in history.iter().enumerate() {
+            let Some(created_by) = step.get("created_by").and_then(Value::as_str) else {
+                continue;
+            };
+            let command = history_command(created_by);
+            for hit in cmdline::scan_command(self.detector, &command) {
+                let column = command[..hit.detection.start].chars().count() + 1;
+                let finding = hit
+                    .detection
+                    .into_finding(&command, location("history", index + 1, column, hit.key));
+                // Un ENV de la última etapa ya se informa desde Config.Env.
+                let repeated = findings
+                    .iter()
+                    .any(|f| f.rule_id == finding.rule_id && f.secret == finding.secret);
+                if !repeated {
+                    findings.push(finding);
+                }
+            }
+        }
+        findings
+    }
+}
+
+// Imágenes del tar: manifest.json de "docker save" o, en un layout OCI puro, index.json.
+fn manifests<R: Read + Seek>(reader: &mut R, entries: &[Entry]) -> Result<Vec<Manifest>, String> {
+    if member(entries, "manifest.json").is_ok() {
+        let manifest = read_json(reader, entries, "manifest.json")?;
+        let images = manifest.as_array().ok_or("manifest.json: se esperaba una lista")?;
+        return images
+            .iter()
+            .map(|image| {
+                let config = image
+                    .get("Config")
+                    .and_then(Value::as_str)
+                    .ok_or("manifest.json: falta Config")?;
+                let tag = image
+                    .get("RepoTags")
+                    .and_then(Value::as_array)
+                    .and_then(|tags| tags.first())
+                    .and_then(Value::as_str);
+                Ok(Manifest {
+                    name: tag.map_or_else(|| layer_digest(config), str::to_string),
+                    config: config.to_string(),
+                    layers: string_array(image.get("Layers")),
+                })
+            })
+            .collect();
+    }
+    if member(entries, "index.json").is_err() {
+        return Err("no parece una imagen de docker save: falta manifest.json e index.json".to_string());
+    }
+    let mut found = Vec::new();
+    let index = read_json(reader, entries, "index.json")?;
+    let mut pending: Vec<(Value, Option<String>)> = vec![(index, None)];
+    while let Some((document, name)) = pending.pop() {
+        // Índice (posiblemente multiplataforma): se siguen sus descriptores.
+        if let Some(descriptors) = document.get("manifests").and_then(Value::as_array) {
+            for descriptor in descriptors.iter().rev() {
+                let digest = descriptor
+                    .get("digest")
+                    .and_then(Value::as_str)
+                    .ok_or("index.json: falta digest")?;
+                let reference = descriptor
+                    .get("annotations")
+                    .and_then(|a| a.get("org.opencontainers.image.ref.name"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .or(name.clone());
+                pending.push((read_json(reader, entries, &blob_path(digest))?, reference));
+            }
+            continue;
+        }
+        let config = document
+            .get("config")
+            .and_then(|c| c.get("digest"))
+            .and_then(Value::as_str)
+            .ok_or("manifiesto OCI sin config")?;
+        let layers = document.get("layers").and_then(Value::as_array).unwrap_or_default();
+        found.push(Manifest {
+            name: name.unwrap_or_else(|| config.to_string()),
+            config: blob_path(config),
+            layers: layers
+                .iter()
+                .filter_map(|l| l.get("digest").and_then(Value::as_str))
+                .map(blob_path)
+                .collect(),
+        });
+    }
+    Ok(found)
+}
+
+fn member<'a>(entries: &'a [Entry], path: &str) -> Result<&'a Entry, String> {
+    let path = path.trim_start_matches("./");
+    entries
+        .iter()
+        .find(|e| e.path == path && e.kind == EntryKind::File)
+        .ok_or(format!("falta {} en el tar de la imagen", path))
+}
+
+fn read_json<R: Read + Seek>(reader: &mut R, entries: &[Entry], path: &str) -> Result<Value, String> {
+    let data = member(entries, path)?.read(reader)?;
+    json::parse(&String::from_utf8_lossy(&data)).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn string_array(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+// "sha256:abc..." -> "blobs/sha256/abc...".
+fn blob_path(digest: &str) -> String {
+    format!("blobs/{}", digest.replacen(':', "/", 1))
+}
+
+// Digest a partir de la ruta del blob cuando la configuración no trae rootfs.diff_ids.
+fn layer_digest(path: &str) -> String {
+    match path.strip_prefix("blobs/").and_then(|rest| rest.split_once('/')) {
+        Some((algorithm, hex)) => format!("{}:{}", algorithm, hex),
+        None => path
+            .trim_end_matches("/layer.tar")
+            .trim_end_matches(".json")
+            .to_string(),
+    }
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+fn is_package_path(path: &str) -> bool {
+    PACKAGE_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+        || path.split('/').any(|component| PACKAGE_DIRS.contains(&component))
+}
+
+// created_by del historial -> comando analizable: sin "RUN", "/bin/sh -c", "#(nop)", "ENV"/"ARG" ni el
+// prefijo "|N" de los argumentos de build ("|1 TOKEN=x /bin/sh -c cmd" -> "TOKEN=x ; cmd").
+fn history_command(created_by: &str) -> String {
+    let mut text = created_by.trim().trim_end_matches("# buildkit").trim_end();
+    loop {
+        let trimmed = text.trim_start();
+        let stripped = [
+            "RUN ",
+            "/bin/sh -c ",
+            "/bin/bash -c ",
+            "#(nop)",
+            "ENV ",
+            "ARG ",
+            "LABEL ",
+        ]
+        .iter()
+        .find_map(|prefix| trimmed.strip_prefix(prefix))
+        .or_else(|| {
+            let (first, rest) = trimmed.split_once(' ')?;
+            let build_args = first.len() > 1 && first[1..].bytes().all(|b| b.is_ascii_digit());
+            (first.starts_with('|') && build_args).then_some(rest)
+        });
+        match stripped {
+            Some(rest) => text = rest,
+            None => break,
+        }
+    }
+    text.trim_start().replace(" /bin/sh -c ", " ; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_layers_hide_files_whiteouts_and_opaque_dirs() {
+        let listing = LayerListing {
+            files: HashSet::from(["app/.env".to_string()]),
+            whiteouts: vec!["etc/secreto.env".to_string(), "root/.aws".to_string()],
+            opaque_dirs: vec!["var/cache".to_string()],
+        };
+        for hidden in [
+            "app/.env",
+            "etc/secreto.env",
+            "root/.aws/credentials",
+            "var/cache/token",
+        ] {
+            assert!(listing.hides(hidden), "{}", hidden);
+        }
+        for visible in ["app/.env.example", "etc/secreto.env.bak", "root/.aws-cli", "var/cache"] {
+            assert!(!listing.hides(visible), "{}", visible);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+// Papiweb desarrollos informaticos
+// Descompresor DEFLATE (RFC 1951) y envoltorios zlib (RFC 1950) y gzip (RFC 1952), sin dependencias externas.
+// Implementación canónica de Huffman al estilo de "puff" de zlib: simple y suficiente para objetos git.
+
+const MAX_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, need: u32) -> Result<u32, String> {
+        while self.count < need {
+            let byte = *self.data.get(self.pos).ok_or("deflate: datos truncados")?;
+            self.pos += 1;
+            self.buffer |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u32 << need) - 1);
+        self.buffer >>= need;
+        self.count -= need;
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+struct Huffman {
+    count: [u16; MAX_BITS + 1],
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut count = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            count[len as usize] += 1;
+        }
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + count[len];
+        }
+        let mut symbol = vec![0u16; lengths.len()];
+        for (sym, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbol[offsets[len as usize] as usize] = sym as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Huffman { count, symbol }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.count[len] as i32;
+            if code - count < first {
+                return Ok(self.symbol[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err("deflate: código Huffman inválido".to_string())
+    }
+}
+
+// Descomprime un flujo zlib (cabecera de 2 bytes + DEFLATE + Adler-32).
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 2 || data[0] & 0x0f != 8 || (u16::from(data[0]) << 8 | u16::from(data[1])) % 31 != 0 {
+        return Err("zlib: cabecera inválida".to_string());
+    }
+    inflate(&data[2..])
+}
+
+// Descomprime un miembro gzip (RFC 1952): cabecera con campos opcionales + DEFLATE + CRC-32 e ISIZE.
+pub fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+    if data.len() < 18 || data[..3] != [0x1f, 0x8b, 8] {
+        return Err("gzip: cabecera inválida".to_string());
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let extra = data.get(pos..pos + 2).ok_or("gzip: cabecera truncada")?;
+        pos += 2 + usize::from(u16::from_le_bytes([extra[0], extra[1]]));
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data.get(pos..).and_then(|rest| rest.iter().position(|&b| b == 0));
+            pos += end.ok_or("gzip: cabecera truncada")? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    let out = inflate(data.get(pos..).ok_or("gzip: cabecera truncada")?)?;
+    // ISIZE: tamaño descomprimido módulo 2^32, suficiente para detectar flujos truncados.
+    let trailer = &data[data.len() - 4..];
+    if u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != out.len() as u32 {
+        return Err("gzip: tamaño descomprimido no coincide (¿archivo truncado?)".to_string());
+    }
+    Ok(out)
+}
+
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader {
+        data,
+        pos: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Vec::with_capacity(data.len() * 3);
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored(&mut reader, &mut out)?,
+            1 => {
+                let (lit, dist) = fixed_tables();
+                codes(&mut reader, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(&mut reader)?;
+                codes(&mut reader, &mut out, &lit, &dist)?;
+            }
+            _ => return Err("deflate: tipo de bloque inválido".to_string()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn stored(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), String> {
+    reader.align();
+    let header = reader
+        .data
+        .get(reader.pos..reader.pos + 4)
+        .ok_or("deflate: bloque almacenado truncado")?;
+    let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+    let nlen = u16::from_le_bytes([header[2], header[3]]) as usize;
+    if len != !nlen & 0xffff {
+        return Err("deflate: longitud de bloque almacenado inválida".to_string());
+    }
+    reader.pos += 4;
+    let block = reader
+        .data
+        .get(reader.pos..reader.pos + len)
+        .ok_or("deflate: bloque almacenado truncado")?;
+    out.extend_from_slice(block);
+    reader.pos += len;
+    Ok(())
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    for (sym, len) in lengths.iter_mut().enumerate() {
+        *len = match sym {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    (Huffman::new(&lengths), Huffman::new(&[5u8; 30]))
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let nlen = reader.bits(5)? as usize + 257;
+    let ndist = reader.bits(5)? as usize + 1;
+    let ncode = reader.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return Err("deflate: demasiados códigos".to_string());
+    }
+    let mut code_lengths = [0u8; 19];
+    for &index in CODE_LENGTH_ORDER.iter().take(ncode) {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_table = Huffman::new(&code_lengths);
+
+    let mut lengths = vec![0u8; nlen + ndist];
+    let mut index = 0;
+    while index < nlen + ndist {
+        let symbol = code_table.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => {
+                lengths[index] = symbol as u8;
+                index += 1;
+                continue;
+            }
+            16 => {
+                let prev = *lengths[..index]
+                    .last()
+                    .ok_or("deflate: repetición sin longitud previa")?;
+                (prev, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if index + repeat > nlen + ndist {
+            return Err("deflate: demasiadas longitudes".to_string());
+        }
+        lengths[index..index + repeat].fill(value);
+        index += repeat;
+    }
+    Ok((Huffman::new(&lengths[..nlen]), Huffman::new(&lengths[nlen..])))
+}
+
+fn codes(reader: &mut BitReader, out: &mut Vec<u8>, lit: &Huffman, dist: &Huffman) -> Result<(), String> {
+    loop {
+        let symbol = lit.decode(reader)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let symbol = symbol - 257;
+                if symbol >= LENGTH_BASE.len() {
+                    return Err("deflate: símbolo de longitud inválido".to_string());
+                }
+                let len = LENGTH_BASE[symbol] as usize + reader.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+                let dsym = dist.decode(reader)? as usize;
+                if dsym >= DIST_BASE.len() {
+                    return Err("deflate: símbolo de distancia inválido".to_string());
+                }
+                let distance = DIST_BASE[dsym] as usize + reader.bits(DIST_EXTRA[dsym] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("deflate: distancia fuera de rango".to_string());
+                }
+                let start = out.len() - distance;
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn stored_and_fixed_blocks() {
+        // Bloque almacenado no final seguido de uno fijo con una referencia hacia atrás.
+        let mut data = vec![0x00, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c'];
+        data.extend(hex("cbc8cf4954c8402100"));
+        assert_eq!(inflate(&data).unwrap(), b"abchola hola hola hola");
+        assert_eq!(inflate(&hex("cbc8cf4954c8402100")).unwrap(), b"hola hola hola hola");
+    }
+
+    #[test]
+    fn dynamic_block_in_zlib_stream() {
+        let compressed = hex(concat!(
+            "78dabdd33b0a83401800e1de537884ff5d047218235b08a2a0b93f81b0cdc2d4b6d37cd5ecdbd196595ef3ddd6ab7dcff7f2",
+            "5965daff5587aabdda50ad571faaf71a438d5e13b5424d5053d40c35472d504bd40a35414d5133d41cb5402d512bd4043545",
+            "cd5073d402b544ad5013d4143543cd510bd412b542eda9037e445a239b"
+        ));
+        let expected: String = (0..40)
+            .map(|i| format!("linea {}: secreto=abc{}\n", i % 7, i % 5))
+            .collect();
+        assert_eq!(zlib_decompress(&compressed).unwrap(), expected.as_bytes());
+    }
+
+    #[test]
+    fn malformed_streams_are_rejected() {
+        assert!(zlib_decompress(&[0x78, 0x00, 0x01]).is_err());
+        assert!(inflate(&[0x01, 0x03, 0x00, 0x00, 0x00]).is_err());
+        assert!(inflate(&[0x07]).is_err());
+        assert!(inflate(&[0x01, 0x05, 0x00, 0xfa, 0xff, b'a']).is_err());
+    }
+
+    #[test]
+    fn gzip_checks_the_uncompressed_size() {
+        let mut member = vec![0x1f, 0x8b, 8, 0x08, 0, 0, 0, 0, 0, 0xff];
+        member.extend_from_slice(b"nombre.txt\0");
+        member.extend_from_slice(&[0x01, 0x04, 0x00, 0xfb, 0xff]);
+        member.extend_from_slice(b"hola");
+        member.extend_from_slice(&[0, 0, 0, 0, 4, 0, 0, 0]);
+        assert_eq!(gzip_decompress(&member).unwrap(), b"hola");
+        let len = member.len();
+        member[len - 4] = 5;
+        assert!(gzip_decompress(&member).is_err());
+    }
+}
@@ -0,0 +1,457 @@
+// Papiweb desarrollos informaticos
+// JSON mínimo (lectura y escritura) para baselines y reportes, sin serde.
+use std::fmt::Write;
+
+// Anidamiento máximo de objetos y listas: el análisis es recursivo y un archivo con miles de '[' desbordaría la pila.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    // Se conserva el orden de inserción para que los archivos generados sean estables.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn to_pretty(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Value::Null => out.push_str("null"),
+            Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+                let _ = write!(out, "{}", *n as i64);
+            }
+            Value::Number(n) => {
+                let _ = write!(out, "{}", n);
+            }
+            Value::String(s) => write_string(out, s),
+            Value::Array(items) if items.is_empty() => out.push_str("[]"),
+            Value::Array(items) => {
+                out.push_str("[\n");
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(&"  ".repeat(indent + 1));
+                    item.write(out, indent + 1);
+                    out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(indent));
+                out.push(']');
+            }
+            Value::Object(fields) if fields.is_empty() => out.push_str("{}"),
+            Value::Object(fields) => {
+                out.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    out.push_str(&"  ".repeat(indent + 1));
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(indent));
+                out.push('}');
+            }
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser::new(text, false);
+    parser.document()
+}
+
+// Cadena de un documento con su ruta de claves ("api.token"; los elementos de una lista no suman segmento, igual que
+// en YAML) y el desplazamiento en bytes de su contenido dentro del texto.
+pub struct StringLeaf {
+    pub path: String,
+    pub value: String,
+    pub offset: usize,
+}
+
+// Cadenas del documento en orden de aparición, para los archivos .json analizados.
+pub fn string_leaves(text: &str) -> Result<Vec<StringLeaf>, String> {
+    let mut parser = Parser::new(text, true);
+    parser.document()?;
+    Ok(parser.leaves.unwrap_or_default())
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+    // Solo con `string_leaves`: claves del objeto actual y cadenas encontradas.
+    path: Vec<String>,
+    leaves: Option<Vec<StringLeaf>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str, leaves: bool) -> Self {
+        Parser {
+            input: text.as_bytes(),
+            pos: 0,
+            depth: 0,
+            path: Vec::new(),
+            leaves: leaves.then(Vec::new),
+        }
+    }
+
+    fn document(&mut self) -> Result<Value, String> {
+        let value = self.value()?;
+        self.skip_ws();
+        if self.pos != self.input.len() {
+            return Err(self.error("contenido extra tras el valor JSON"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.input[..self.pos.min(self.input.len())]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+            + 1;
+        format!("JSON inválido (línea {}): {}", line, message)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_ws();
+        if self.input.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("se esperaba '{}'", byte as char)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_ws();
+        match self.input.get(self.pos) {
+            Some(b'{' | b'[') => self.nested(),
+            Some(b'"') => {
+                let offset = self.pos + 1;
+                let value = self.string()?;
+                if let Some(leaves) = &mut self.leaves {
+                    leaves.push(StringLeaf {
+                        path: self.path.join("."),
+                        value: value.clone(),
+                        offset,
+                    });
+                }
+                Ok(Value::String(value))
+            }
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error("valor inesperado")),
+        }
+    }
+
+    fn nested(&mut self) -> Result<Value, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("anidamiento demasiado profundo"));
+        }
+        self.depth += 1;
+        let value = if self.input[self.pos] == b'{' {
+            self.object()
+        } else {
+            self.array()
+        };
+        self.depth -= 1;
+        value
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        if self.input[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("literal inválido"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'))
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Value::Number)
+            .ok_or_else(|| self.error("número inválido"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = *self
+                .input
+                .get(self.pos)
+                .ok_or_else(|| self.error("cadena sin cerrar"))?;
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = *self
+                        .input
+                        .get(self.pos)
+                        .ok_or_else(|| self.error("escape incompleto"))?;
+                    self.pos += 1;
+                    let c = match escape {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // Par sustituto UTF-16 para caracteres fuera del plano básico.
+                            if (0xd800..0xdc00).contains(&code) && self.input[self.pos..].starts_with(b"\\u") {
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+                            char::from_u32(code).unwrap_or('\u{fffd}')
+                        }
+                        other => other as char,
+                    };
+                    let mut buf = [0u8; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                other => bytes.push(other),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("cadena con UTF-8 inválido"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("escape \\u incompleto"))?;
+        self.pos += 4;
+        // from_str_radix aceptaría un signo: "\u+12a".
+        std::str::from_utf8(digits)
+            .ok()
+            .filter(|s| s.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|s| u32::from_str_radix(s, 16).ok())
+            .ok_or_else(|| self.error("escape \\u inválido"))
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.input.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_ws();
+            match self.input.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("se esperaba ',' o ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_ws();
+        if self.input.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_ws();
+            let key = self.string()?;
+            self.expect(b':')?;
+            if self.leaves.is_some() {
+                self.path.push(key.clone());
+            }
+            let value = self.value()?;
+            if self.leaves.is_some() {
+                self.path.pop();
+            }
+            fields.push((key, value));
+            self.skip_ws();
+            match self.input.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err(self.error("se esperaba ',' o '}'")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_values_and_escapes() {
+        let value = parse(r#" {"a": [1, -2.5e3, true, null], "b": "x\"y\\z\n\u00e9\ud83d\ude00", "c": {}} "#).unwrap();
+        assert_eq!(
+            value.get("a"),
+            Some(&Value::Array(vec![
+                Value::Number(1.0),
+                Value::Number(-2500.0),
+                Value::Bool(true),
+                Value::Null
+            ]))
+        );
+        assert_eq!(value.get("b").and_then(Value::as_str), Some("x\"y\\z\né😀"));
+        assert_eq!(value.get("c"), Some(&Value::Object(Vec::new())));
+        // Sustituto suelto: se reemplaza en lugar de fallar.
+        assert_eq!(parse(r#""\udc00""#).unwrap(), Value::from("\u{fffd}"));
+    }
+
+    #[test]
+    fn errors_report_the_line() {
+        let cases = [
+            (
+                "{\n  \"a\": 1,\n  \"b\" 2\n}",
+                "JSON inválido (línea 3): se esperaba ':'",
+            ),
+            ("[1, 2", "JSON inválido (línea 1): se esperaba ',' o ']'"),
+            ("\"abc", "JSON inválido (línea 1): cadena sin cerrar"),
+            ("{} {}", "JSON inválido (línea 1): contenido extra tras el valor JSON"),
+            ("[tru]", "JSON inválido (línea 1): literal inválido"),
+            ("[1.2.3]", "JSON inválido (línea 1): número inválido"),
+            ("\"\\u12\"", "JSON inválido (línea 1): escape \\u incompleto"),
+            ("\"\\u+12a\"", "JSON inválido (línea 1): escape \\u inválido"),
+            ("", "JSON inválido (línea 1): valor inesperado"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse(text).unwrap_err(), expected, "{:?}", text);
+        }
+    }
+
+    // Regresión: miles de '[' desbordaban la pila al analizar un .json del árbol.
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert_eq!(
+            parse(&deep).unwrap_err(),
+            "JSON inválido (línea 1): anidamiento demasiado profundo"
+        );
+        let limit = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&limit).is_ok());
+    }
+
+    #[test]
+    fn pretty_output_round_trips() {
+        let value = Value::Object(vec![
+            ("id".to_string(), Value::Number(42.0)),
+            ("ratio".to_string(), Value::Number(0.5)),
+            ("nombre".to_string(), Value::from("línea\n\"citada\"\u{1}")),
+            ("vacío".to_string(), Value::Array(Vec::new())),
+            ("lista".to_string(), Value::Array(vec![Value::Null, Value::Bool(false)])),
+        ]);
+        let text = value.to_pretty();
+        assert!(text.starts_with("{\n  \"id\": 42,\n  \"ratio\": 0.5,\n"), "{}", text);
+        assert!(text.contains(r#""línea\n\"citada\"\u0001""#), "{}", text);
+        assert_eq!(parse(&text).unwrap(), value);
+    }
+
+    #[test]
+    fn string_leaves_have_paths_and_offsets() {
+        let text = r#"{"api": {"token": "t1", "hosts": ["h1", {"user": "u"}]}, "n": 1}"#;
+        let leaves = string_leaves(text).unwrap();
+        let found: Vec<(&str, &str)> = leaves.iter().map(|l| (l.path.as_str(), l.value.as_str())).collect();
+        assert_eq!(
+            found,
+            [("api.token", "t1"), ("api.hosts", "h1"), ("api.hosts.user", "u")]
+        );
+        for leaf in &leaves {
+            assert!(text[leaf.offset..].starts_with(&leaf.value));
+        }
+    }
+}
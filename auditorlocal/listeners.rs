@@ -0,0 +1,680 @@
+// Papiweb desarrollos informaticos
+// Inventario de sockets en escucha desde /proc/net/{tcp,tcp6,udp,udp6} con el proceso dueño (vía /proc/<pid>/fd):
+// qué expone el host sin lanzar nmap. Marca servicios pensados para uso local atados a 0.0.0.0 o ::.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+
+use crate::finding::{AuditPriority, Finding, Location};
+use crate::hardening;
+use crate::procenv;
+use crate::system;
+
+const DEBUG_RULE: &str = "debug-port-exposed";
+const EXPOSED_RULE: &str = "local-service-exposed";
+const INTERFACE_RULE: &str = "local-service-on-interface";
+
+const TABLES: &[(&str, Proto)] = &[
+    ("/proc/net/tcp", Proto::Tcp),
+    ("/proc/net/tcp6", Proto::Tcp),
+    ("/proc/net/udp", Proto::Udp),
+    ("/proc/net/udp6", Proto::Udp),
+];
+const TCP_LISTEN: &str = "0A";
+// UDP sin conectar: estado TCP_CLOSE con dirección remota vacía.
+const UDP_UNCONNECTED: &str = "07";
+// Procesos que reenvían puertos publicados de contenedores: el servicio real está detrás.
+const PORT_PROXIES: &[&str] = &["docker-proxy", "rootlessport", "slirp4netns", "pasta"];
+
+struct LocalService {
+    name: &'static str,
+    ports: &'static [u16],
+    processes: &'static [&'static str],
+    // Sin autenticación en la configuración por defecto: cualquiera que llegue al puerto entra.
+    open_by_default: bool,
+    fix: &'static str,
+}
+
+const LOCAL_SERVICES: &[LocalService] = &[
+    LocalService {
+        name: "Redis",
+        ports: &[6379],
+        processes: &["redis-server", "valkey-server"],
+        open_by_default: true,
+        fix: "bind 127.0.0.1 -::1 y protected-mode yes en redis.conf; si debe ser remoto, requirepass/ACL y firewall",
+    },
+    LocalService {
+        name: "Memcached",
+        ports: &[11211],
+        processes: &["memcached"],
+        open_by_default: true,
+        fix: "-l 127.0.0.1 en /etc/memcached.conf y -U 0 (el UDP abierto sirve para ataques de amplificación)",
+    },
+    LocalService {
+        name: "API de Docker sin TLS",
+        ports: &[2375],
+        processes: &[],
+        open_by_default: true,
+        fix: "quitar -H tcp://0.0.0.0:2375 de dockerd: usar /var/run/docker.sock o TLS mutuo en 2376 (quien accede es root en el host)",
+    },
+    LocalService {
+        name: "API de Docker",
+        ports: &[2376],
+        processes: &["dockerd"],
+        open_by_default: false,
+        fix: "limitar el acceso a 2376 por firewall y verificar --tlsverify con certificados de cliente",
+    },
+    LocalService {
+        name: "MongoDB",
+        ports: &[27017, 27018, 27019],
+        processes: &["mongod", "mongos"],
+        open_by_default: true,
+        fix: "net.bindIp: 127.0.0.1 y security.authorization: enabled en mongod.conf",
+    },
+    LocalService {
+        name: "Elasticsearch/OpenSearch",
+        ports: &[9200, 9300],
+        processes: &[],
+        open_by_default: true,
+        fix: "network.host: 127.0.0.1 y la seguridad del clúster activada (xpack.security.enabled / plugin de seguridad)",
+    },
+    LocalService {
+        name: "etcd",
+        ports: &[2379, 2380],
+        processes: &["etcd"],
+        open_by_default: true,
+        fix: "--listen-client-urls https://127.0.0.1:2379 con --client-cert-auth (guarda los secretos de Kubernetes)",
+    },
+    LocalService {
+        name: "kubelet de solo lectura",
+        ports: &[10255],
+        processes: &[],
+        open_by_default: true,
+        fix: "readOnlyPort: 0 en la configuración del kubelet",
+    },
+    LocalService {
+        name: "ZooKeeper",
+        ports: &[2181],
+        processes: &[],
+        open_by_default: true,
+        fix: "clientPortAddress=127.0.0.1 o SASL y firewall hacia los brokers",
+    },
+    LocalService {
+        name: "Consul",
+        ports: &[8500],
+        processes: &["consul"],
+        open_by_default: true,
+        fix: "client_addr = \"127.0.0.1\" y ACL con default_policy = \"deny\"",
+    },
+    LocalService {
+        name: "PHP-FPM (FastCGI)",
+        ports: &[9000],
+        processes: &["php-fpm"],
+        open_by_default: true,
+        fix: "listen = 127.0.0.1:9000 o un socket unix en el pool: FastCGI abierto permite ejecutar PHP arbitrario",
+    },
+    LocalService {
+        name: "Java RMI/JMX",
+        ports: &[1099],
+        processes: &[],
+        open_by_default: true,
+        fix: "-Dcom.sun.management.jmxremote.host=127.0.0.1 o autenticación y SSL de JMX",
+    },
+    LocalService {
+        name: "X11",
+        ports: &[6000, 6001, 6002],
+        processes: &["Xorg", "Xvfb", "Xwayland"],
+        open_by_default: true,
+        fix: "arrancar el servidor X con -nolisten tcp y usar ssh -X para acceso remoto",
+    },
+    LocalService {
+        name: "rpcbind",
+        ports: &[111],
+        processes: &["rpcbind"],
+        open_by_default: true,
+        fix: "desactivarlo si no hay NFSv3 (systemctl disable --now rpcbind.socket) o limitarlo por firewall",
+    },
+    LocalService {
+        name: "MySQL/MariaDB",
+        ports: &[3306, 33060],
+        processes: &["mysqld", "mariadbd"],
+        open_by_default: false,
+        fix: "bind-address = 127.0.0.1 en my.cnf, o firewall hacia los hosts de la aplicación",
+    },
+    LocalService {
+        name: "PostgreSQL",
+        ports: &[5432],
+        processes: &["postgres", "postmaster"],
+        open_by_default: false,
+        fix: "listen_addresses = 'localhost' en postgresql.conf y reglas hostssl acotadas en pg_hba.conf",
+    },
+    LocalService {
+        name: "RabbitMQ/Erlang",
+        ports: &[5672, 15672, 25672, 4369],
+        processes: &["epmd"],
+        open_by_default: false,
+        fix: "listeners.tcp.default = 127.0.0.1:5672 y ERL_EPMD_ADDRESS=127.0.0.1 (la distribución Erlang solo la protege la cookie)",
+    },
+    LocalService {
+        name: "InfluxDB",
+        ports: &[8086],
+        processes: &["influxd"],
+        open_by_default: false,
+        fix: "http-bind-address = \"127.0.0.1:8086\" o autenticación activada y firewall",
+    },
+    LocalService {
+        name: "CouchDB",
+        ports: &[5984],
+        processes: &[],
+        open_by_default: false,
+        fix: "bind_address = 127.0.0.1 en la sección [chttpd] de local.ini",
+    },
+    LocalService {
+        name: "VNC",
+        ports: &[5900, 5901, 5902],
+        processes: &["Xvnc", "x11vnc", "Xtightvnc"],
+        open_by_default: false,
+        fix: "escuchar solo en localhost (-localhost) y acceder por túnel SSH",
+    },
+];
+
+// Puertos de depuración por defecto; los indicados en la línea de comandos se suman a estos.
+const DEBUG_PORTS: &[(u16, &str)] = &[
+    (9229, "inspector de Node.js"),
+    (5005, "JDWP de Java"),
+    (5678, "debugpy"),
+    (9222, "DevTools de Chrome"),
+];
+// Opciones de arranque que abren un depurador: "--inspect=0.0.0.0:9230", "-agentlib:jdwp=...,address=*:8000".
+const DEBUG_FLAGS: &[&str] = &["--inspect", "jdwp", "debugpy", "--remote-debugging-port"];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Proto {
+    Tcp,
+    Udp,
+}
+
+impl Proto {
+    fn as_str(self) -> &'static str {
+        match self {
+            Proto::Tcp => "tcp",
+            Proto::Udp => "udp",
+        }
+    }
+}
+
+pub struct Owner {
+    pub pid: u32,
+    pub exe: String,
+    pub user: String,
+    pub unit: Option<String>,
+    // Procesos que comparten el socket (workers heredados del proceso principal).
+    pub processes: usize,
+    cmdline: String,
+}
+
+impl Owner {
+    fn name(&self) -> &str {
+        self.exe
+            .trim_matches(['[', ']'])
+            .rsplit('/')
+            .next()
+            .unwrap_or(&self.exe)
+    }
+}
+
+pub struct Socket {
+    pub proto: Proto,
+    pub addr: SocketAddr,
+    pub uid: u32,
+    inode: u64,
+    pub owner: Option<Owner>,
+}
+
+impl Socket {
+    fn owner_text(&self, users: &HashMap<u32, String>) -> String {
+        match &self.owner {
+            Some(owner) => {
+                let mut text = format!("{}, pid {}, usuario {}", owner.exe, owner.pid, owner.user);
+                if let Some(unit) = &owner.unit {
+                    text.push_str(&format!(", unidad {}", unit));
+                }
+                if owner.processes > 1 {
+                    text.push_str(&format!(", {} procesos", owner.processes));
+                }
+                text
+            }
+            None => format!(
+                "proceso no visible, usuario {}",
+                users.get(&self.uid).cloned().unwrap_or_else(|| self.uid.to_string())
+            ),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum Bind {
+    Loopback,
+    All,
+    Interface,
+}
+
+fn bind(addr: IpAddr) -> Bind {
+    // ::ffff:127.0.0.1 es loopback aunque llegue por la tabla IPv6.
+    let addr = match addr {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(addr, IpAddr::V4),
+        v4 => v4,
+    };
+    if addr.is_loopback() {
+        Bind::Loopback
+    } else if addr.is_unspecified() {
+        Bind::All
+    } else {
+        Bind::Interface
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ListenerStats {
+    pub tcp: u64,
+    pub udp: u64,
+    pub without_owner: u64,
+    pub processes_denied: u64,
+}
+
+pub fn rule_ids() -> Vec<String> {
+    [DEBUG_RULE, EXPOSED_RULE, INTERFACE_RULE].map(str::to_string).to_vec()
+}
+
+pub fn scan_listeners(stats: &mut ListenerStats) -> Result<(Vec<Socket>, Vec<Finding>), String> {
+    let mut sockets = Vec::new();
+    for &(table, proto) in TABLES {
+        let text = match fs::read_to_string(table) {
+            Ok(text) => text,
+            // Sin IPv6 en el kernel no hay tcp6/udp6; la tabla IPv4 sí es obligatoria.
+            Err(_) if table.ends_with('6') => continue,
+            Err(err) => return Err(format!("{}: {}", table, err)),
+        };
+        sockets.extend(parse_table(&text, proto));
+    }
+    sockets.sort_by_key(|s| (s.proto, s.addr.port(), s.addr.is_ipv6(), s.addr.ip()));
+
+    let users = system::user_names();
+    let inodes: HashSet<u64> = sockets.iter().map(|s| s.inode).collect();
+    let holders = socket_holders(&inodes, stats);
+    let boot_uptime = procenv::boot_uptime();
+    for socket in &mut sockets {
+        match socket.proto {
+            Proto::Tcp => stats.tcp += 1,
+            Proto::Udp => stats.udp += 1,
+        }
+        let Some(pids) = holders.get(&socket.inode) else {
+            stats.without_owner += 1;
+            continue;
+        };
+        let pid = pids[0];
+        let dir = Path::new("/proc").join(pid.to_string());
+        let info = procenv::process_info(&dir, &users, boot_uptime);
+        let cmdline = fs::read(dir.join("cmdline"))
+            .map(|raw| String::from_utf8_lossy(&raw).replace('\0', " ").trim().to_string())
+            .unwrap_or_default();
+        socket.owner = Some(Owner {
+            pid,
+            exe: info.exe,
+            user: info.user,
+            unit: info.unit,
+            processes: pids.len(),
+            cmdline,
+        });
+    }
+    let findings = exposures(&sockets, &users);
+    Ok((sockets, findings))
+}
+
+// Tabla completa de /proc/net: cabecera y una línea por socket; se quedan los que están en escucha.
+fn parse_table(text: &str, proto: Proto) -> Vec<Socket> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| parse_line(line, proto))
+        .collect()
+}
+
+// "   1: 00000000:1F90 00000000:0000 0A ... uid timeout inode"
+fn parse_line(line: &str, proto: Proto) -> Option<Socket> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let (local, remote, state) = (fields.get(1)?, fields.get(2)?, *fields.get(3)?);
+    let listening = match proto {
+        Proto::Tcp => state == TCP_LISTEN,
+        Proto::Udp => state == UDP_UNCONNECTED && parse_addr(remote)?.ip().is_unspecified(),
+    };
+    if !listening {
+        return None;
+    }
+    Some(Socket {
+        proto,
+        addr: parse_addr(local)?,
+        uid: fields.get(7)?.parse().ok()?,
+        inode: fields.get(9)?.parse().ok()?,
+        owner: None,
+    })
+}
+
+// El kernel imprime la dirección como palabras de 32 bits en orden del host y el puerto ya en orden normal.
+fn parse_addr(text: &str) -> Option<SocketAddr> {
+    let (ip, port) = text.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    // Palabras completas de 8 dígitos: una más corta daría una dirección distinta sin error.
+    if ip.len() % 8 != 0 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(16);
+    for word in ip.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(word).ok()?, 16).ok()?;
+        bytes.extend(word.to_ne_bytes());
+    }
+    let ip = match bytes.len() {
+        4 => IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])),
+        16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+// Procesos que tienen abierto cada socket (enlaces "socket:[INODO]" en /proc/<pid>/fd), ordenados por pid.
+fn socket_holders(inodes: &HashSet<u64>, stats: &mut ListenerStats) -> HashMap<u64, Vec<u32>> {
+    let mut holders: HashMap<u64, Vec<u32>> = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return holders;
+    };
+    let mut pids: Vec<u32> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| e.file_name().to_str()?.parse().ok())
+        .collect();
+    pids.sort_unstable();
+    for pid in pids {
+        let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            stats.processes_denied += 1;
+            continue;
+        };
+        for fd in fds.filter_map(Result::ok) {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u64>().ok());
+            if let Some(inode) = inode.filter(|i| inodes.contains(i)) {
+                let pids = holders.entry(inode).or_default();
+                if pids.last() != Some(&pid) {
+                    pids.push(pid);
+                }
+            }
+        }
+    }
+    holders
+}
+
+fn service_for(socket: &Socket) -> Option<&'static LocalService> {
+    let owner = socket.owner.as_ref().map(Owner::name);
+    let by_process = |name: &str| {
+        LOCAL_SERVICES
+            .iter()
+            .find(|s| s.processes.iter().any(|p| name.starts_with(p)))
+    };
+    if let Some(service) = owner.and_then(by_process) {
+        return Some(service);
+    }
+    // Por puerto solo si el dueño no contradice el servicio: otro programa puede usar el 9000 o el 8086.
+    LOCAL_SERVICES.iter().find(|s| {
+        s.ports.contains(&socket.addr.port())
+            && match owner {
+                None => true,
+                Some(name) => s.processes.is_empty() || PORT_PROXIES.contains(&name),
+            }
+    })
+}
+
+// Puertos de depuración abiertos por la línea de comandos del proceso ("--inspect=0.0.0.0:9230").
+fn debug_ports(cmdline: &str) -> Vec<u16> {
+    cmdline
+        .split_whitespace()
+        .filter(|arg| DEBUG_FLAGS.iter().any(|flag| arg.contains(flag)))
+        .filter_map(|arg| {
+            let digits: String = arg
+                .chars()
+                .rev()
+                .take_while(char::is_ascii_digit)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+struct Exposure {
+    rule_id: &'static str,
+    priority: AuditPriority,
+    text: String,
+    fix: String,
+}
+
+fn exposure(socket: &Socket) -> Option<Exposure> {
+    let bind = bind(socket.addr.ip());
+    if bind == Bind::Loopback {
+        return None;
+    }
+    let everywhere = bind == Bind::All;
+    let port = socket.addr.port();
+    let from_cmdline = socket
+        .owner
+        .as_ref()
+        .is_some_and(|o| debug_ports(&o.cmdline).contains(&port));
+    let debugger = DEBUG_PORTS
+        .iter()
+        .find(|(p, _)| *p == port)
+        .map(|(_, name)| *name)
+        .or(from_cmdline.then_some("abierto por la línea de comandos"));
+    let place = if everywhere {
+        "en todas las interfaces".to_string()
+    } else {
+        format!("en la interfaz {}", socket.addr.ip())
+    };
+    if let Some(debugger) = debugger {
+        return Some(Exposure {
+            rule_id: DEBUG_RULE,
+            priority: if everywhere {
+                AuditPriority::Critical
+            } else {
+                AuditPriority::High
+            },
+            text: format!(
+                "puerto de depuración ({}) {}: quien se conecte ejecuta código en el proceso",
+                debugger, place
+            ),
+            fix: "atarlo a 127.0.0.1 (--inspect=127.0.0.1:9229, address=127.0.0.1:5005) o quitarlo en producción; para depurar en remoto, túnel SSH".to_string(),
+        });
+    }
+    let service = service_for(socket)?;
+    let priority = match (everywhere, service.open_by_default) {
+        (true, true) => AuditPriority::Critical,
+        (true, false) | (false, true) => AuditPriority::High,
+        (false, false) => AuditPriority::Medium,
+    };
+    Some(Exposure {
+        rule_id: if everywhere { EXPOSED_RULE } else { INTERFACE_RULE },
+        priority,
+        text: format!(
+            "{} escucha {}{}",
+            service.name,
+            place,
+            if service.open_by_default {
+                " y no pide autenticación con la configuración por defecto"
+            } else {
+                ""
+            }
+        ),
+        fix: service.fix.to_string(),
+    })
+}
+
+// Regla, protocolo, puerto y dueño del socket.
+type ExposureKey = (&'static str, Proto, u16, String);
+
+fn exposures(sockets: &[Socket], users: &HashMap<u32, String>) -> Vec<Finding> {
+    // Un servicio en 0.0.0.0 y en :: (o con varios workers) es un solo hallazgo con varias direcciones.
+    let mut grouped: BTreeMap<ExposureKey, (Vec<String>, Exposure, String)> = BTreeMap::new();
+    for socket in sockets {
+        let Some(exposure) = exposure(socket) else {
+            continue;
+        };
+        let owner = socket.owner_text(users);
+        let service = match &socket.owner {
+            Some(owner) => owner.unit.clone().unwrap_or_else(|| owner.exe.clone()),
+            None => format!("uid {}", socket.uid),
+        };
+        let entry = grouped
+            .entry((exposure.rule_id, socket.proto, socket.addr.port(), owner))
+            .or_insert_with(|| (Vec::new(), exposure, service));
+        // SO_REUSEPORT: varios sockets en la misma dirección.
+        let addr = socket.addr.to_string();
+        if !entry.0.contains(&addr) {
+            entry.0.push(addr);
+        }
+    }
+    grouped
+        .into_iter()
+        .map(|((_, proto, _, owner), (addrs, exposure, service))| {
+            let address = format!("{} {}", proto.as_str(), addrs.join(", "));
+            hardening::finding(
+                exposure.rule_id,
+                exposure.priority,
+                Location::Socket {
+                    address: address.clone(),
+                    owner,
+                    service,
+                },
+                &address,
+                exposure.text,
+                exposure.fix,
+            )
+        })
+        .collect()
+}
+
+pub fn print_inventory(sockets: &[Socket]) {
+    if sockets.is_empty() {
+        return;
+    }
+    let users = system::user_names();
+    println!("📡 SOCKETS EN ESCUCHA:");
+    for socket in sockets {
+        let marker = match bind(socket.addr.ip()) {
+            Bind::Loopback => "🏠",
+            Bind::All => "🌍",
+            Bind::Interface => "🔌",
+        };
+        println!(
+            "   {} {} {:<28} {}",
+            marker,
+            socket.proto.as_str(),
+            socket.addr,
+            socket.owner_text(&users)
+        );
+    }
+    println!("   (🏠 solo local, 🌍 todas las interfaces, 🔌 una interfaz concreta)\n");
+}
+
+// Las tablas de ejemplo son las de un kernel little-endian (x86, arm64): el kernel imprime en el orden del host.
+#[cfg(all(test, target_endian = "little"))]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000   112        0 21001 1 0000000000000000 100 0 0 10 0
+   1: 00000000:18EB 00000000:0000 0A 00000000:00000000 00:00000000 00000000   999        0 21002 1 0000000000000000 100 0 0 10 0
+   2: 0500000A:238D 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 21003 1 0000000000000000 100 0 0 10 0
+   3: 0500000A:0016 6400000A:C350 01 00000000:00000000 02:0009A2C1 00000000     0        0 21004 2 0000000000000000 20 4 29 10 -1
+   4: basura
+";
+    const TCP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:18EB 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000   999        0 22001 1 0000000000000000 100 0 0 10 0
+   1: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 22002 1 0000000000000000 100 0 0 10 0
+   2: 0000000000000000FFFF00000100007F:1F91 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 22003 1 0000000000000000 100 0 0 10 0
+   3: 0000000000000000FFFF000000000000:2BCB 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 22004 1 0000000000000000 100 0 0 10 0
+   4: 0000000000000000FFFF00000500000A:1F92 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 22005 1 0000000000000000 100 0 0 10 0
+";
+    const UDP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  10: 00000000:2BCB 00000000:0000 07 00000000:00000000 00:00000000 00000000   113        0 23001 2 0000000000000000 0
+  11: 0500000A:0035 0100000A:0035 07 00000000:00000000 00:00000000 00000000     0        0 23002 2 0000000000000000 0
+";
+
+    fn addresses(sockets: &[Socket]) -> Vec<String> {
+        sockets.iter().map(|s| s.addr.to_string()).collect()
+    }
+
+    #[test]
+    fn ipv4_table_keeps_only_listening_sockets() {
+        let sockets = parse_table(TCP, Proto::Tcp);
+        assert_eq!(addresses(&sockets), ["127.0.0.1:3306", "0.0.0.0:6379", "10.0.0.5:9101"]);
+        assert_eq!((sockets[1].uid, sockets[1].inode), (999, 21002));
+        let binds: Vec<_> = sockets.iter().map(|s| bind(s.addr.ip())).collect();
+        assert!(binds == [Bind::Loopback, Bind::All, Bind::Interface]);
+
+        // UDP: solo los sockets sin conectar (remoto vacío).
+        let udp = parse_table(UDP, Proto::Udp);
+        assert_eq!(addresses(&udp), ["0.0.0.0:11211"]);
+    }
+
+    #[test]
+    fn ipv6_wildcard_and_mapped_addresses() {
+        let sockets = parse_table(TCP6, Proto::Tcp);
+        assert_eq!(
+            addresses(&sockets),
+            [
+                "[::]:6379",
+                "[::1]:8080",
+                "[::ffff:127.0.0.1]:8081",
+                "[::ffff:0.0.0.0]:11211",
+                "[::ffff:10.0.0.5]:8082"
+            ]
+        );
+        let binds: Vec<_> = sockets.iter().map(|s| bind(s.addr.ip())).collect();
+        assert!(binds == [Bind::All, Bind::Loopback, Bind::Loopback, Bind::All, Bind::Interface]);
+    }
+
+    #[test]
+    fn malformed_addresses_are_skipped() {
+        assert!(parse_addr("0100007F").is_none());
+        assert!(parse_addr("0100007F:XYZ").is_none());
+        assert!(parse_addr("0100007:0016").is_none());
+        assert!(parse_addr("0100007G:0016").is_none());
+        assert!(parse_addr("000000000000000000000000010000:0016").is_none());
+        assert!(parse_line("   0: 0100007F:0CEA 00000000:0000 0A", Proto::Tcp).is_none());
+    }
+
+    #[test]
+    fn wildcard_v4_and_v6_are_one_finding() {
+        let mut sockets = parse_table(TCP, Proto::Tcp);
+        sockets.extend(parse_table(TCP6, Proto::Tcp));
+        let findings = exposures(&sockets, &HashMap::from([(999, "redis".to_string())]));
+        // Redis en 0.0.0.0 y ::, Memcached en ::ffff:0.0.0.0; lo atado a loopback no cuenta.
+        assert_eq!(findings.len(), 2);
+        let redis = &findings[0];
+        assert_eq!(
+            (redis.rule_id.as_str(), redis.priority),
+            (EXPOSED_RULE, AuditPriority::Critical)
+        );
+        match &redis.location {
+            Location::Socket { address, owner, .. } => {
+                assert_eq!(address, "tcp 0.0.0.0:6379, [::]:6379");
+                assert_eq!(owner, "proceso no visible, usuario redis");
+            }
+            other => panic!("ubicación inesperada: {}", other),
+        }
+    }
+}
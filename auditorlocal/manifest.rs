@@ -0,0 +1,563 @@
+// Papiweb desarrollos informaticos
+// Manifiestos de Kubernetes (Pods y cargas con plantilla de pod, ConfigMaps, Secrets) y archivos docker-compose:
+// secretos en claro en env:/environment:, command/args, ConfigMaps y datos base64 de los Secrets, con la
+// alternativa concreta para cada caso (secretKeyRef, secrets: de compose, gestores de secretos externos).
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::base64;
+use crate::cmdline;
+use crate::detectors::Detector;
+use crate::filescan::FileScanner;
+use crate::finding::{AuditPriority, Finding, Location, Note};
+use crate::formats::Format;
+use crate::gitignore::IgnoreStack;
+use crate::json::Value;
+use crate::packfile::{self, YamlDocument};
+
+#[derive(Debug, Default)]
+pub struct ManifestStats {
+    pub files_scanned: u64,
+    pub resources_scanned: u64,
+    // "ruta: error" de los YAML que no se pudieron interpretar (plantillas Helm, anclas...).
+    pub invalid: Vec<String>,
+}
+
+// Dónde estaba el secreto: decide la recomendación concreta.
+#[derive(Clone, Copy)]
+enum Origin {
+    ContainerEnv,
+    ContainerArgs,
+    ConfigMap,
+    Secret,
+    ComposeEnv,
+    ComposeBuildArg,
+    ComposeCommand,
+}
+
+impl Origin {
+    fn note(self) -> Note {
+        let (id, text) = match self {
+            Origin::ContainerEnv => (
+                "k8s-env-plaintext",
+                "valor en claro en env: usar valueFrom.secretKeyRef hacia un Secret sincronizado desde Vault o un gestor externo (External Secrets Operator)",
+            ),
+            Origin::ContainerArgs => (
+                "k8s-args-plaintext",
+                "secreto en command/args, visible en la API y en ps: montar un Secret como archivo o pasarlo con secretKeyRef",
+            ),
+            Origin::ConfigMap => (
+                "k8s-configmap-secret",
+                "los ConfigMaps no son confidenciales: mover la clave a un Secret y consumirla con secretKeyRef o envFrom.secretRef",
+            ),
+            Origin::Secret => (
+                "k8s-secret-in-manifest",
+                "base64 no es cifrado: no versionar el Secret; usar SealedSecrets, SOPS o External Secrets Operator",
+            ),
+            Origin::ComposeEnv => (
+                "compose-env-plaintext",
+                "valor en claro en environment: reemplazarlo por ${NOMBRE} (--patch genera el cambio) interpolado desde un .env 0600 fuera del repositorio o desde el entorno de despliegue",
+            ),
+            Origin::ComposeBuildArg => (
+                "compose-build-arg",
+                "los build args quedan en el historial de la imagen: usar secretos de build (RUN --mount=type=secret)",
+            ),
+            Origin::ComposeCommand => (
+                "compose-command-plaintext",
+                "secreto en command/entrypoint: leerlo de secrets: de compose o de una variable cargada desde un env_file",
+            ),
+        };
+        Note {
+            id,
+            priority: AuditPriority::Low,
+            text: text.to_string(),
+        }
+    }
+}
+
+// Las notas de los manifiestos ya son la alternativa concreta: el plan de remediación las usa como reemplazo.
+pub fn is_advice(note: &Note) -> bool {
+    note.id.starts_with("k8s-") || note.id.starts_with("compose-")
+}
+
+pub fn scan_manifests(
+    root: &Path,
+    detector: &Detector,
+    scanner: &FileScanner,
+    stats: &mut ManifestStats,
+) -> Result<Vec<Finding>, String> {
+    let mut findings = Vec::new();
+    for path in manifest_files(root)? {
+        let documents = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| packfile::parse_yaml_documents(&text).map(|documents| (text, documents)));
+        let (text, documents) = match documents {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                stats.invalid.push(format!("{}: {}", path.display(), err));
+                continue;
+            }
+        };
+        stats.files_scanned += 1;
+        let lines: Vec<&str> = text.lines().collect();
+        for document in &documents {
+            let mut manifest = Manifest {
+                detector,
+                scanner,
+                path: &path,
+                lines: &lines,
+                document,
+                findings: &mut findings,
+            };
+            stats.resources_scanned += manifest.scan();
+        }
+    }
+    Ok(findings)
+}
+
+// Archivos .yaml/.yml bajo la ruta respetando .gitignore; una ruta a archivo se analiza tal cual.
+fn manifest_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let metadata = fs::metadata(root).map_err(|e| format!("{}: {}", root.display(), e))?;
+    if metadata.is_file() {
+        return Ok(vec![root.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    let mut ignores = IgnoreStack::default();
+    let mut pending: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        ignores.enter(depth, &dir);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_symlink() || entry.file_name() == ".git" || ignores.is_ignored(&path, file_type.is_dir()) {
+                continue;
+            }
+            if file_type.is_dir() {
+                pending.push((path, depth + 1));
+            } else if Format::detect(&path) == Format::Yaml {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+struct Manifest<'a> {
+    detector: &'a Detector,
+    scanner: &'a FileScanner<'a>,
+    path: &'a Path,
+    lines: &'a [&'a str],
+    document: &'a YamlDocument,
+    findings: &'a mut Vec<Finding>,
+}
+
+impl Manifest<'_> {
+    // Recursos del documento: un objeto con kind, cada elemento de un "kind: List" o cada servicio de compose.
+    fn scan(&mut self) -> u64 {
+        let root = &self.document.value;
+        match root.get("kind").and_then(Value::as_str) {
+            Some("List") => {
+                let items = root.get("items").and_then(Value::as_array).unwrap_or_default();
+                for (index, item) in items.iter().enumerate() {
+                    self.resource(item, &format!("items[{}]", index));
+                }
+                items.len() as u64
+            }
+            Some(_) => {
+                self.resource(root, "");
+                1
+            }
+            None => match root.get("services") {
+                Some(Value::Object(services)) => {
+                    for (name, service) in services {
+                        self.compose_service(name, service);
+                    }
+                    services.len() as u64
+                }
+                _ => 0,
+            },
+        }
+    }
+
+    fn resource(&mut self, resource: &Value, base: &str) {
+        let kind = resource.get("kind").and_then(Value::as_str).unwrap_or_default();
+        let name = resource
+            .get("metadata")
+            .and_then(|m| m.get("name"))
+            .and_then(Value::as_str)
+            .unwrap_or("?");
+        let label = format!("{}/{}", kind, name);
+        match kind {
+            "ConfigMap" => self.data(resource, base, &label, "data", Origin::ConfigMap),
+            "Secret" => {
+                self.data(resource, base, &label, "data", Origin::Secret);
+                self.data(resource, base, &label, "stringData", Origin::Secret);
+            }
+            _ => {
+                let Some(spec_path) = pod_spec_path(kind) else {
+                    return;
+                };
+                if let Some(spec) = lookup(resource, spec_path) {
+                    self.pod_spec(spec, &join(base, spec_path), &label);
+                }
+            }
+        }
+    }
+
+    fn pod_spec(&mut self, spec: &Value, path: &str, label: &str) {
+        for group in ["initContainers", "containers", "ephemeralContainers"] {
+            let containers = spec.get(group).and_then(Value::as_array).unwrap_or_default();
+            for (index, container) in containers.iter().enumerate() {
+                let path = format!("{}.{}[{}]", path, group, index);
+                let label = format!(
+                    "{} contenedor {}",
+                    label,
+                    container.get("name").and_then(Value::as_str).unwrap_or("?")
+                );
+                let env = container.get("env").and_then(Value::as_array).unwrap_or_default();
+                for (i, variable) in env.iter().enumerate() {
+                    // valueFrom (secretKeyRef, configMapKeyRef...) no lleva el valor en el manifiesto.
+                    let name = variable.get("name").and_then(Value::as_str);
+                    let value = variable.get("value").and_then(scalar_text);
+                    if let (Some(name), Some(value)) = (name, value) {
+                        let key = format!("{} env {}", label, name);
+                        self.pair(
+                            &format!("{}.env[{}].value", path, i),
+                            key,
+                            name,
+                            &value,
+                            Origin::ContainerEnv,
+                        );
+                    }
+                }
+                let mut words = words_at(container.get("command"), &format!("{}.command", path));
+                words.extend(words_at(container.get("args"), &format!("{}.args", path)));
+                self.command(&words, &format!("{} args", label), Origin::ContainerArgs);
+            }
+        }
+    }
+
+    // data/stringData de ConfigMaps y Secrets; en un Secret, data va en base64.
+    fn data(&mut self, resource: &Value, base: &str, label: &str, field: &str, origin: Origin) {
+        let Some(Value::Object(entries)) = resource.get(field) else {
+            return;
+        };
+        let encoded = matches!(origin, Origin::Secret) && field == "data";
+        for (key, value) in entries {
+            let Some(text) = scalar_text(value) else {
+                continue;
+            };
+            let text = if encoded {
+                match base64::decode(&text).map(String::from_utf8) {
+                    Some(Ok(decoded)) => decoded,
+                    _ => continue,
+                }
+            } else {
+                text
+            };
+            let path = join(base, &format!("{}.{}", field, key));
+            let label = format!("{} {}.{}", label, field, key);
+            if text.trim_end().contains('\n') {
+                self.embedded_file(&path, &label, key, &text, encoded, origin);
+            } else {
+                self.pair(&path, label, key, text.trim_end(), origin);
+            }
+        }
+    }
+
+    // Archivo completo dentro de una clave (application.properties, .dockerconfigjson...): se analiza con el
+    // formato que indica su nombre; si no estaba en base64, las líneas se reubican dentro del manifiesto.
+    fn embedded_file(&mut self, path: &str, label: &str, name: &str, text: &str, encoded: bool, origin: Origin) {
+        let key_line = self.document.line(path);
+        let file = Path::new(name);
+        for finding in self.scanner.scan_text(file, Format::detect(file), text) {
+            let Location::File { line, key, .. } = finding.location else {
+                continue;
+            };
+            let line = if encoded { key_line } else { key_line + line };
+            let key = key.map_or_else(|| label.to_string(), |key| format!("{} {}", label, key));
+            let location = self.location_at(line, finding.secret.expose(), key);
+            self.push(Finding { location, ..finding }, origin);
+        }
+    }
+
+    // compose: environment, build.args y entrypoint/command de un servicio.
+    fn compose_service(&mut self, name: &str, service: &Value) {
+        let path = format!("services.{}", name);
+        let label = format!("servicio {}", name);
+        self.compose_vars(
+            service.get("environment"),
+            &format!("{}.environment", path),
+            &format!("{} environment", label),
+            Origin::ComposeEnv,
+        );
+        self.compose_vars(
+            service.get("build").and_then(|b| b.get("args")),
+            &format!("{}.build.args", path),
+            &format!("{} build.args", label),
+            Origin::ComposeBuildArg,
+        );
+        let mut words = words_at(service.get("entrypoint"), &format!("{}.entrypoint", path));
+        words.extend(words_at(service.get("command"), &format!("{}.command", path)));
+        self.command(&words, &format!("{} command", label), Origin::ComposeCommand);
+    }
+
+    // Lista "CLAVE=valor" o mapa CLAVE: valor.
+    fn compose_vars(&mut self, vars: Option<&Value>, path: &str, label: &str, origin: Origin) {
+        match vars {
+            Some(Value::Array(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    if let Some((name, value)) = item.as_str().and_then(|item| item.split_once('=')) {
+                        let key = format!("{} {}", label, name);
+                        self.pair(&format!("{}[{}]", path, index), key, name, value, origin);
+                    }
+                }
+            }
+            Some(Value::Object(fields)) => {
+                for (name, value) in fields {
+                    if let Some(value) = scalar_text(value) {
+                        let key = format!("{} {}", label, name);
+                        self.pair(&format!("{}.{}", path, name), key, name, &value, origin);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn pair(&mut self, path: &str, label: String, name: &str, value: &str, origin: Origin) {
+        for detection in self.detector.scan_pair(name, value) {
+            let location = self.location(path, &value[detection.start..detection.end], label.clone());
+            self.push(detection.into_finding(value, location), origin);
+        }
+    }
+
+    // Las palabras sueltas del comando se unen en una línea y cada script ("sh -c '...'", command: como
+    // cadena) se analiza aparte; la ubicación es la del elemento de la lista que contiene el secreto.
+    fn command(&mut self, words: &[(String, String)], label: &str, origin: Origin) {
+        let (scripts, argv): (Vec<_>, Vec<_>) = words.iter().partition(|(_, word)| word.contains(char::is_whitespace));
+        let mut commands: Vec<(String, Vec<&(String, String)>)> =
+            scripts.iter().map(|&word| (word.1.clone(), vec![word])).collect();
+        commands.push((
+            argv.iter().map(|(_, word)| word.as_str()).collect::<Vec<_>>().join(" "),
+            argv,
+        ));
+        for (command, parts) in commands {
+            for hit in cmdline::scan_command(self.detector, &command) {
+                let secret = &command[hit.detection.start..hit.detection.end];
+                let Some((path, _)) = parts.iter().find(|(_, word)| word.contains(secret)).or(parts.first()) else {
+                    continue;
+                };
+                let location = self.location(path, secret, format!("{} ({})", label, hit.key));
+                self.push(hit.detection.into_finding(&command, location), origin);
+            }
+        }
+    }
+
+    fn location(&self, path: &str, secret: &str, key: String) -> Location {
+        self.location_at(self.document.line(path), secret, key)
+    }
+
+    // Busca el secreto en la línea indicada y en su continuación (bloques | y >, escalares multilínea).
+    fn location_at(&self, line: usize, secret: &str, key: String) -> Location {
+        let indent = |text: &str| text.len() - text.trim_start().len();
+        let base = self.lines.get(line - 1).map_or(0, |text| indent(text));
+        let found = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(line - 1)
+            .take_while(|&(index, text)| index == line - 1 || text.trim().is_empty() || indent(text) > base)
+            .find_map(|(index, text)| text.find(secret).map(|i| (index + 1, text[..i].chars().count() + 1)));
+        let (line, column) = found.unwrap_or((line, 1));
+        Location::File {
+            path: self.path.to_path_buf(),
+            line,
+            column,
+            key: Some(key),
+        }
+    }
+
+    fn push(&mut self, mut finding: Finding, origin: Origin) {
+        finding.notes.push(origin.note());
+        self.findings.push(finding);
+    }
+}
+
+// Ruta de la especificación del pod según el tipo de recurso.
+fn pod_spec_path(kind: &str) -> Option<&'static str> {
+    match kind {
+        "Pod" => Some("spec"),
+        "PodTemplate" => Some("template.spec"),
+        "Deployment" | "StatefulSet" | "DaemonSet" | "ReplicaSet" | "ReplicationController" | "Job" => {
+            Some("spec.template.spec")
+        }
+        "CronJob" => Some("spec.jobTemplate.spec.template.spec"),
+        _ => None,
+    }
+}
+
+fn lookup<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
+
+fn join(base: &str, path: &str) -> String {
+    if base.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}.{}", base, path)
+    }
+}
+
+// Valores escalares como texto (un PIN numérico también puede ser un secreto).
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+// command/args como cadena o lista: cada palabra con su ruta en el documento.
+fn words_at(value: Option<&Value>, path: &str) -> Vec<(String, String)> {
+    match value {
+        Some(Value::String(text)) => vec![(path.to_string(), text.clone())],
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| Some((format!("{}[{}]", path, index), scalar_text(item)?)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names::NameMatcher;
+    use crate::rules::RuleSet;
+    use std::{env, process};
+
+    // Clave, línea, columna y nota de origen de cada hallazgo del manifiesto.
+    fn scan(name: &str, text: &str) -> Vec<(String, usize, usize, &'static str)> {
+        let rules = RuleSet::builtin();
+        let detector = Detector::new(&rules, NameMatcher::new(&rules.keywords, &rules.benign));
+        let scanner = FileScanner::new(&detector, 1024 * 1024);
+        let path = env::temp_dir().join(format!("auditorlocal-manifest-{}-{}", process::id(), name));
+        fs::write(&path, text).unwrap();
+        let mut stats = ManifestStats::default();
+        let findings = scan_manifests(&path, &detector, &scanner, &mut stats);
+        fs::remove_file(&path).unwrap();
+        assert!(stats.invalid.is_empty(), "{:?}", stats.invalid);
+        findings
+            .unwrap()
+            .into_iter()
+            .map(|finding| match finding.location {
+                Location::File { line, column, key, .. } => {
+                    (key.unwrap(), line, column, finding.notes.last().unwrap().id)
+                }
+                other => panic!("ubicación inesperada: {}", other),
+            })
+            .collect()
+    }
+
+    fn row(key: &str, line: usize, column: usize, note: &'static str) -> (String, usize, usize, &'static str) {
+        (key.to_string(), line, column, note)
+    }
+
+    #[test]
+    fn kubernetes_secret_is_decoded() {
+        let token = format!("ghp_{}", "a1B2c3D4e5".repeat(4));
+        let text = format!(
+            "apiVersion: v1
+kind: Secret
+metadata:
+  name: db
+data:
+  DB_PASSWORD: aHVudGVyMnNlY3JldA==
+  app.properties: ZGIucGFzc3dvcmQ9U3VwM3JTM2NyZXRWYWx1ZQp1c2VyPWFwcAo=
+  roto: '%%%'
+stringData:
+  API_TOKEN: {}
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+spec:
+  template:
+    spec:
+      containers:
+        - name: app
+          env:
+            - name: DB_PASSWORD
+              value: hunter2secret
+            - name: DESDE_SECRET
+              valueFrom:
+                secretKeyRef:
+                  name: db
+                  key: DB_PASSWORD
+",
+            token
+        );
+        assert_eq!(
+            scan("k8s.yaml", &text),
+            [
+                // Decodificado, el valor no está en el texto: se marca el inicio de la línea.
+                row("Secret/db data.DB_PASSWORD", 6, 1, "k8s-secret-in-manifest"),
+                row(
+                    "Secret/db data.app.properties db.password",
+                    7,
+                    1,
+                    "k8s-secret-in-manifest"
+                ),
+                row("Secret/db stringData.API_TOKEN", 10, 14, "k8s-secret-in-manifest"),
+                row(
+                    "Deployment/web contenedor app env DB_PASSWORD",
+                    23,
+                    22,
+                    "k8s-env-plaintext"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn compose_environment_as_map_and_list() {
+        let text = "services:
+  api:
+    image: api
+    environment:
+      DB_PASSWORD: hunter2secret
+      LOG_LEVEL: debug
+  worker:
+    environment:
+      - DB_PASSWORD=hunter2secret
+      - SOLO_NOMBRE
+      - LOG_LEVEL=debug
+    build:
+      args:
+        NPM_TOKEN: hunter2secret
+";
+        assert_eq!(
+            scan("compose.yaml", text),
+            [
+                row("servicio api environment DB_PASSWORD", 5, 20, "compose-env-plaintext"),
+                row(
+                    "servicio worker environment DB_PASSWORD",
+                    9,
+                    21,
+                    "compose-env-plaintext"
+                ),
+                row("servicio worker build.args NPM_TOKEN", 14, 20, "compose-build-arg"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+// Papiweb desarrollos informaticos
+// Coincidencia de palabras clave por tokens del nombre (evita KEYBOARD_LAYOUT, AUTHOR_NAME...).
+use std::collections::HashSet;
+
+pub struct NameMatcher {
+    keywords: Vec<String>,
+    benign: HashSet<String>,
+}
+
+impl NameMatcher {
+    // Palabras clave y nombres benignos del conjunto de reglas efectivo (ver rules.rs).
+    pub fn new(keywords: &[String], benign: &[String]) -> Self {
+        Self {
+            keywords: keywords.iter().map(|kw| kw.to_uppercase()).collect(),
+            benign: benign.iter().map(|name| name.to_uppercase()).collect(),
+        }
+    }
+
+    pub fn with_benign(mut self, name: &str) -> Self {
+        self.benign.insert(name.to_uppercase());
+        self
+    }
+
+    pub fn is_sensitive(&self, name: &str) -> bool {
+        if self.benign.contains(&name.to_uppercase()) {
+            return false;
+        }
+        tokenize(name).iter().any(|token| self.keywords.contains(token))
+    }
+}
+
+// Divide un nombre en tokens en mayúsculas: separadores '_', '-', '.', camelCase y dígitos.
+// "dbPassword" -> [DB, PASSWORD], "OAUTH2_TOKEN" -> [OAUTH, 2, TOKEN], "APIKey" -> [API, KEY].
+pub fn tokenize(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if let Some(prev) = current.chars().last() {
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            let boundary = (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_uppercase() && c.is_uppercase() && next_is_lower)
+                || (prev.is_ascii_digit() != c.is_ascii_digit());
+            if boundary {
+                tokens.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens.iter().map(|t| t.to_uppercase()).collect()
+}
@@ -0,0 +1,892 @@
+// Papiweb desarrollos informaticos
+// Lectura de packs de reglas y manifiestos: subconjuntos de TOML y YAML convertidos a json::Value.
+// TOML: tablas [t], arrays de tablas [[t]], claves simples, con comillas o con puntos, cadenas "..." y '...' (también
+// multilínea """...""" y '''...'''), números, booleanos, arrays (también multilínea) y tablas en línea { k = v }.
+// YAML: mapas y listas por indentación, escalares planos o entre comillas, colecciones en línea [a, b] y
+// {k: v}, bloques | y >, y varios documentos separados por "---". Sin anclas ni etiquetas.
+use crate::json::Value;
+
+pub fn parse_toml(text: &str) -> Result<Value, String> {
+    let mut cursor = Cursor { text, pos: 0 };
+    let mut root = Value::Object(Vec::new());
+    let mut current: Vec<String> = Vec::new();
+    loop {
+        cursor.skip_blank();
+        let Some(c) = cursor.peek() else {
+            break;
+        };
+        if c == '[' {
+            cursor.pos += 1;
+            let array = cursor.eat('[');
+            cursor.skip_spaces();
+            let path = cursor.key_path()?;
+            if !(cursor.eat(']') && (!array || cursor.eat(']'))) {
+                return Err(cursor.error("se esperaba ']' al cerrar la tabla"));
+            }
+            cursor.end_of_line()?;
+            if array {
+                let (last, parent) = path.split_last().expect("ruta no vacía");
+                let fields = table_mut(&mut root, parent).map_err(|e| cursor.error(&e))?;
+                match field_mut(fields, last) {
+                    Some(Value::Array(items)) => items.push(Value::Object(Vec::new())),
+                    Some(_) => {
+                        return Err(cursor.error(&format!("'{}' ya está definido y no es un array de tablas", last)))
+                    }
+                    None => fields.push((last.clone(), Value::Array(vec![Value::Object(Vec::new())]))),
+                }
+            } else {
+                table_mut(&mut root, &path).map_err(|e| cursor.error(&e))?;
+            }
+            current = path;
+        } else {
+            let line = cursor.line();
+            let path = cursor.key_path()?;
+            cursor.skip_spaces();
+            if !cursor.eat('=') {
+                return Err(cursor.error("se esperaba '=' tras la clave"));
+            }
+            cursor.skip_spaces();
+            let value = cursor.toml_value()?;
+            cursor.end_of_line()?;
+            let (last, parent) = path.split_last().expect("ruta no vacía");
+            let full: Vec<String> = current.iter().chain(parent).cloned().collect();
+            let fields = table_mut(&mut root, &full).map_err(|e| format!("línea {}: {}", line, e))?;
+            if field_mut(fields, last).is_some() {
+                return Err(format!("línea {}: clave '{}' duplicada", line, last));
+            }
+            fields.push((last.clone(), value));
+        }
+    }
+    Ok(root)
+}
+
+fn field_mut<'a>(fields: &'a mut [(String, Value)], key: &str) -> Option<&'a mut Value> {
+    fields.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+// Tabla en la ruta dada, creándola si hace falta; en un array de tablas se usa el último elemento.
+fn table_mut<'a>(root: &'a mut Value, path: &[String]) -> Result<&'a mut Vec<(String, Value)>, String> {
+    let mut node = root;
+    for key in path {
+        let Value::Object(fields) = node else {
+            unreachable!("los nodos intermedios siempre son tablas");
+        };
+        let index = match fields.iter().position(|(k, _)| k == key) {
+            Some(index) => index,
+            None => {
+                fields.push((key.clone(), Value::Object(Vec::new())));
+                fields.len() - 1
+            }
+        };
+        let not_a_table = || format!("'{}' ya está definido y no es una tabla", key);
+        node = match &mut fields[index].1 {
+            Value::Array(items) => match items.last_mut() {
+                Some(last @ Value::Object(_)) => last,
+                _ => return Err(not_a_table()),
+            },
+            value => {
+                if !matches!(value, Value::Object(_)) {
+                    return Err(not_a_table());
+                }
+                value
+            }
+        };
+    }
+    match node {
+        Value::Object(fields) => Ok(fields),
+        _ => unreachable!("el nodo final siempre es una tabla"),
+    }
+}
+
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn line(&self) -> usize {
+        self.text[..self.pos].matches('\n').count() + 1
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("línea {}: {}", self.line(), message)
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            self.pos = self.text[self.pos..]
+                .find('\n')
+                .map_or(self.text.len(), |i| self.pos + i);
+        }
+    }
+
+    // Espacios, saltos de línea y comentarios (entre sentencias y dentro de arrays).
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            if !(self.eat('\n') || self.eat('\r')) {
+                break;
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\n' | '\r') => Ok(()),
+            Some(c) => Err(self.error(&format!("carácter inesperado '{}' al final de la línea", c))),
+        }
+    }
+
+    fn key_path(&mut self) -> Result<Vec<String>, String> {
+        let mut path = vec![self.key()?];
+        loop {
+            self.skip_spaces();
+            if !self.eat('.') {
+                return Ok(path);
+            }
+            self.skip_spaces();
+            path.push(self.key()?);
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(self.error("se esperaba una clave"));
+                }
+                Ok(self.text[start..self.pos].to_string())
+            }
+        }
+    }
+
+    fn toml_value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_blank();
+                    if self.eat(']') {
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.toml_value()?);
+                    self.skip_blank();
+                    if !self.eat(',') && self.peek() != Some(']') {
+                        return Err(self.error("se esperaba ',' o ']' en el array"));
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut fields: Vec<(String, Value)> = Vec::new();
+                loop {
+                    self.skip_spaces();
+                    if self.eat('}') {
+                        return Ok(Value::Object(fields));
+                    }
+                    let key = self.key()?;
+                    self.skip_spaces();
+                    if !self.eat('=') {
+                        return Err(self.error("se esperaba '=' en la tabla en línea"));
+                    }
+                    self.skip_spaces();
+                    fields.push((key, self.toml_value()?));
+                    self.skip_spaces();
+                    if !self.eat(',') && self.peek() != Some('}') {
+                        return Err(self.error("se esperaba ',' o '}' en la tabla en línea"));
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | '_'))
+                {
+                    self.pos += 1;
+                }
+                let word = &self.text[start..self.pos];
+                match word {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => word
+                        .replace('_', "")
+                        .parse()
+                        .map(Value::Number)
+                        .map_err(|_| self.error(&format!("valor inválido '{}'", word))),
+                }
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        if self.text[self.pos..].starts_with("\"\"\"") {
+            return self.multiline_string("\"\"\"");
+        }
+        self.pos += 1;
+        let rest = &self.text[self.pos..];
+        let end = closing_quote(rest).ok_or_else(|| self.error("cadena sin cerrar"))?;
+        let value = unescape(&rest[..end]).map_err(|e| self.error(&e))?;
+        self.pos += end + 1;
+        Ok(value)
+    }
+
+    // """...""" con escapes y '\' al final de línea para unirla con la siguiente; '''...''' tal cual. El salto
+    // de línea justo tras la apertura no forma parte del valor.
+    fn multiline_string(&mut self, delimiter: &str) -> Result<String, String> {
+        let start = self.pos;
+        self.pos += delimiter.len();
+        let rest = &self.text[self.pos..];
+        let basic = delimiter.starts_with('"');
+        let mut escaped = false;
+        let mut end = None;
+        for (i, c) in rest.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if basic && c == '\\' {
+                escaped = true;
+            } else if rest[i..].starts_with(delimiter) {
+                end = Some(i);
+                break;
+            }
+        }
+        let Some(end) = end else {
+            self.pos = start;
+            return Err(self.error("cadena multilínea sin cerrar"));
+        };
+        let raw = &rest[..end];
+        let raw = raw
+            .strip_prefix("\r\n")
+            .or_else(|| raw.strip_prefix('\n'))
+            .unwrap_or(raw);
+        let value = if basic {
+            unescape(&join_continued_lines(raw)).map_err(|e| {
+                self.pos = start;
+                self.error(&e)
+            })?
+        } else {
+            raw.to_string()
+        };
+        self.pos += end + delimiter.len();
+        Ok(value)
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        if self.text[self.pos..].starts_with("'''") {
+            return self.multiline_string("'''");
+        }
+        self.pos += 1;
+        let rest = &self.text[self.pos..];
+        let end = rest.find(['\'', '\n']).filter(|&i| rest[i..].starts_with('\''));
+        let end = end.ok_or_else(|| self.error("cadena sin cerrar"))?;
+        self.pos += end + 1;
+        Ok(rest[..end].to_string())
+    }
+}
+
+// Posición de la comilla doble que cierra la cadena (sin la de apertura), respetando escapes.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '\n' => return None,
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+// '\' al final de una línea de """...""": se quitan el salto y los espacios iniciales de las líneas siguientes.
+fn join_continued_lines(raw: &str) -> String {
+    let mut out = String::new();
+    let mut rest = raw;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let blank = after.len() - after.trim_start_matches([' ', '\t']).len();
+        if after[blank..].starts_with(['\n', '\r']) {
+            rest = after.trim_start();
+        } else {
+            // Escape normal: se copia con el carácter siguiente para que "\\" no cuente como continuación.
+            let next = after.chars().next().map_or(0, char::len_utf8);
+            out.push_str(&rest[i..i + 1 + next]);
+            rest = &after[next..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Escapes de las cadenas entre comillas dobles (comunes a TOML y YAML).
+fn unescape(text: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let escape = chars.next().ok_or("escape incompleto")?;
+        match escape {
+            '\\' => out.push('\\'),
+            '"' => out.push('"'),
+            '/' => out.push('/'),
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            'r' => out.push('\r'),
+            'b' => out.push('\u{8}'),
+            'f' => out.push('\u{c}'),
+            'u' | 'U' => {
+                let digits: String = chars.by_ref().take(if escape == 'u' { 4 } else { 8 }).collect();
+                let code = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+                out.push(code.ok_or(format!("escape unicode inválido \\{}{}", escape, digits))?);
+            }
+            other => return Err(format!("escape desconocido \\{} (usar '...' o \\\\)", other)),
+        }
+    }
+    Ok(out)
+}
+
+struct YamlLine {
+    number: usize,
+    indent: usize,
+    text: String,
+    // Contenido del bloque | o > que abre esta línea.
+    block: Option<String>,
+}
+
+// Documento YAML con la línea de cada clave y elemento, por ruta ("spec.containers[0].env[1].value").
+pub struct YamlDocument {
+    pub value: Value,
+    pub start_line: usize,
+    lines: Vec<(String, usize)>,
+}
+
+impl YamlDocument {
+    pub fn line(&self, path: &str) -> usize {
+        self.lines
+            .iter()
+            .find(|(p, _)| p == path)
+            .map_or(self.start_line, |(_, line)| *line)
+    }
+}
+
+pub fn parse_yaml(text: &str) -> Result<Value, String> {
+    let mut documents = parse_yaml_documents(text)?;
+    match documents.len() {
+        0 => Ok(Value::Object(Vec::new())),
+        1 => Ok(documents.remove(0).value),
+        _ => Err(format!(
+            "línea {}: se esperaba un solo documento YAML",
+            documents[1].start_line
+        )),
+    }
+}
+
+// Documentos separados por "---"; los vacíos se omiten.
+pub fn parse_yaml_documents(text: &str) -> Result<Vec<YamlDocument>, String> {
+    let raw: Vec<&str> = text.lines().collect();
+    let mut documents = Vec::new();
+    let mut lines = Vec::new();
+    let mut index = 0;
+    while index < raw.len() {
+        let number = index + 1;
+        let line = raw[index];
+        index += 1;
+        let marker = line.trim_end();
+        if marker == "---" || marker == "..." || marker.starts_with("--- ") {
+            documents.extend(yaml_document(std::mem::take(&mut lines))?);
+            continue;
+        }
+        let content = strip_yaml_comment(line).trim_end();
+        let trimmed = content.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = content.len() - trimmed.len();
+        if content[..indent].contains('\t') {
+            return Err(format!("línea {}: tabulación en la indentación", number));
+        }
+        let block = block_header(trimmed).map(|(folded, strip)| read_block(&raw, &mut index, indent, folded, strip));
+        lines.push(YamlLine {
+            number,
+            indent,
+            text: trimmed.to_string(),
+            block,
+        });
+    }
+    documents.extend(yaml_document(lines)?);
+    Ok(documents)
+}
+
+fn yaml_document(lines: Vec<YamlLine>) -> Result<Option<YamlDocument>, String> {
+    let Some(first) = lines.first() else {
+        return Ok(None);
+    };
+    let (start_line, indent) = (first.number, first.indent);
+    let mut parser = YamlParser {
+        lines,
+        pos: 0,
+        located: Vec::new(),
+    };
+    let value = parser.block(indent, "")?;
+    match parser.lines.get(parser.pos) {
+        Some(line) => Err(format!("línea {}: indentación inesperada", line.number)),
+        None => Ok(Some(YamlDocument {
+            value,
+            start_line,
+            lines: parser.located,
+        })),
+    }
+}
+
+// Quita el comentario final: '#' al inicio o tras un espacio, fuera de comillas.
+fn strip_yaml_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if previous == ' ' || i == 0 || matches!(previous, '[' | ',' | ':' | '-') => {
+                quote = Some(c)
+            }
+            (None, '#') if previous == ' ' || previous == '\t' || i == 0 => return &line[..i],
+            _ => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+// Indicador de bloque al final de la línea ("clave: |", "- >-"): (plegado, sin salto final).
+fn block_header(text: &str) -> Option<(bool, bool)> {
+    let body = text.strip_prefix("- ").map_or(text, str::trim_start);
+    let value = split_yaml_key(body).map_or(body, |(_, value)| value);
+    let mut chars = value.chars();
+    let folded = match chars.next()? {
+        '|' => false,
+        '>' => true,
+        _ => return None,
+    };
+    let modifiers = chars.as_str();
+    if !modifiers.chars().all(|c| c == '-' || c == '+' || c.is_ascii_digit()) {
+        return None;
+    }
+    Some((folded, modifiers.contains('-')))
+}
+
+// Líneas más indentadas que la cabecera del bloque, sin la indentación común.
+fn read_block(raw: &[&str], index: &mut usize, parent_indent: usize, folded: bool, strip: bool) -> String {
+    let mut content: Vec<&str> = Vec::new();
+    let mut block_indent = None;
+    while let Some(line) = raw.get(*index) {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if !trimmed.is_empty() && indent <= parent_indent {
+            break;
+        }
+        *index += 1;
+        if trimmed.is_empty() {
+            content.push("");
+            continue;
+        }
+        let block_indent = *block_indent.get_or_insert(indent);
+        content.push(
+            line.get(block_indent..)
+                .filter(|_| indent >= block_indent)
+                .unwrap_or(trimmed),
+        );
+    }
+    while content.last() == Some(&"") {
+        content.pop();
+    }
+    let mut text = if folded {
+        content.iter().fold(String::new(), |mut out, line| {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push(' ');
+                }
+                out.push_str(line);
+            }
+            out
+        })
+    } else {
+        content.join("\n")
+    };
+    if !strip {
+        text.push('\n');
+    }
+    text
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+struct YamlParser {
+    lines: Vec<YamlLine>,
+    pos: usize,
+    located: Vec<(String, usize)>,
+}
+
+impl YamlParser {
+    fn block(&mut self, indent: usize, path: &str) -> Result<Value, String> {
+        if is_sequence_item(&self.lines[self.pos].text) {
+            self.sequence(indent, path)
+        } else {
+            self.mapping(indent, path)
+        }
+    }
+
+    // Bloque hijo (más indentado) de la línea anterior, o nulo si no lo hay.
+    fn child(&mut self, parent_indent: usize, path: &str) -> Result<Value, String> {
+        match self.lines.get(self.pos) {
+            Some(line) if line.indent > parent_indent => {
+                let indent = line.indent;
+                self.block(indent, path)
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+
+    fn sequence(&mut self, indent: usize, path: &str) -> Result<Value, String> {
+        let mut items = Vec::new();
+        while self.pos < self.lines.len()
+            && self.lines[self.pos].indent == indent
+            && is_sequence_item(&self.lines[self.pos].text)
+        {
+            let item_path = format!("{}[{}]", path, items.len());
+            let line = &mut self.lines[self.pos];
+            self.located.push((item_path.clone(), line.number));
+            let rest = line.text[1..].trim_start().to_string();
+            let offset = line.text.len() - rest.len();
+            if rest.is_empty() {
+                self.pos += 1;
+                items.push(self.child(indent, &item_path)?);
+            } else if split_yaml_key(&rest).is_some() {
+                // "- clave: valor": el elemento es un mapa cuyas claves siguientes se alinean con "clave".
+                line.indent = indent + offset;
+                line.text = rest;
+                items.push(self.mapping(indent + offset, &item_path)?);
+            } else {
+                items.push(self.inline_value(&rest, indent)?);
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize, path: &str) -> Result<Value, String> {
+        let mut fields: Vec<(String, Value)> = Vec::new();
+        while self.pos < self.lines.len()
+            && self.lines[self.pos].indent == indent
+            && !is_sequence_item(&self.lines[self.pos].text)
+        {
+            let number = self.lines[self.pos].number;
+            let (key, rest) = split_yaml_key(&self.lines[self.pos].text)
+                .ok_or_else(|| format!("línea {}: se esperaba 'clave: valor'", number))?;
+            let key = match key.chars().next() {
+                Some('"' | '\'') => match yaml_scalar(key, number)? {
+                    Value::String(key) => key,
+                    _ => unreachable!("una cadena entre comillas siempre es texto"),
+                },
+                _ => key.to_string(),
+            };
+            if fields.iter().any(|(k, _)| *k == key) {
+                return Err(format!("línea {}: clave '{}' duplicada", number, key));
+            }
+            let rest = rest.to_string();
+            let key_path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            self.located.push((key_path.clone(), number));
+            let value = if rest.is_empty() {
+                self.pos += 1;
+                // Una lista puede ir al mismo nivel que su clave ("rules:\n- id: x").
+                match self.lines.get(self.pos) {
+                    Some(next) if next.indent == indent && is_sequence_item(&next.text) => {
+                        self.sequence(indent, &key_path)?
+                    }
+                    _ => self.child(indent, &key_path)?,
+                }
+            } else {
+                self.inline_value(&rest, indent)?
+            };
+            fields.push((key, value));
+        }
+        Ok(Value::Object(fields))
+    }
+
+    // Valor en la misma línea que la clave o el guion: escalar o bloque | y >.
+    fn inline_value(&mut self, text: &str, indent: usize) -> Result<Value, String> {
+        let line = &mut self.lines[self.pos];
+        let number = line.number;
+        let value = match line.block.take() {
+            Some(block) => Value::String(block),
+            None => yaml_scalar(text, number)?,
+        };
+        self.pos += 1;
+        no_deeper_block(&self.lines, self.pos, indent, number)?;
+        Ok(value)
+    }
+}
+
+fn no_deeper_block(lines: &[YamlLine], pos: usize, indent: usize, number: usize) -> Result<(), String> {
+    match lines.get(pos) {
+        Some(next) if next.indent > indent => Err(format!(
+            "línea {}: indentación inesperada tras el valor de la línea {}",
+            next.number, number
+        )),
+        _ => Ok(()),
+    }
+}
+
+// "clave: valor" o "clave:" -> (clave, valor); el ':' debe ir seguido de espacio o fin de línea.
+fn split_yaml_key(text: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (None, '[' | '{') if i == 0 => return None,
+            (None, ':') if text[i + 1..].is_empty() || text[i + 1..].starts_with(' ') => {
+                return Some((text[..i].trim_end(), text[i + 1..].trim()));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn yaml_scalar(text: &str, number: usize) -> Result<Value, String> {
+    let error = |message: &str| format!("línea {}: {}", number, message);
+    match text.chars().next() {
+        Some('"') => {
+            let end = closing_quote(&text[1..]).ok_or_else(|| error("cadena sin cerrar"))?;
+            if !text[end + 2..].trim().is_empty() {
+                return Err(error("texto inesperado tras la cadena"));
+            }
+            unescape(&text[1..end + 1]).map(Value::String).map_err(|e| error(&e))
+        }
+        Some('\'') => {
+            let mut value = String::new();
+            let mut chars = text[1..].char_indices().peekable();
+            while let Some((i, c)) = chars.next() {
+                if c != '\'' {
+                    value.push(c);
+                } else if chars.next_if(|&(_, c)| c == '\'').is_some() {
+                    value.push('\'');
+                } else if text[i + 2..].trim().is_empty() {
+                    return Ok(Value::String(value));
+                } else {
+                    return Err(error("texto inesperado tras la cadena"));
+                }
+            }
+            Err(error("cadena sin cerrar"))
+        }
+        Some('[') => {
+            let inner = text
+                .strip_suffix(']')
+                .ok_or_else(|| error("lista en línea sin cerrar"))?[1..]
+                .trim();
+            if inner.is_empty() {
+                return Ok(Value::Array(Vec::new()));
+            }
+            split_flow(inner)
+                .into_iter()
+                .map(|item| yaml_scalar(item.trim(), number))
+                .collect::<Result<_, _>>()
+                .map(Value::Array)
+        }
+        Some('{') => {
+            let inner = text
+                .strip_suffix('}')
+                .ok_or_else(|| error("mapa en línea sin cerrar"))?[1..]
+                .trim();
+            if inner.is_empty() {
+                return Ok(Value::Object(Vec::new()));
+            }
+            split_flow(inner)
+                .into_iter()
+                .map(|item| {
+                    let (key, value) = split_yaml_key(item.trim())
+                        .ok_or_else(|| error("se esperaba 'clave: valor' en el mapa en línea"))?;
+                    Ok((key.trim_matches(['"', '\'']).to_string(), yaml_scalar(value, number)?))
+                })
+                .collect::<Result<_, String>>()
+                .map(Value::Object)
+        }
+        Some('&' | '*' | '!') => Err(error("anclas, alias y etiquetas no están soportados")),
+        _ => Ok(match text {
+            "true" | "True" | "TRUE" => Value::Bool(true),
+            "false" | "False" | "FALSE" => Value::Bool(false),
+            "null" | "Null" | "NULL" | "~" => Value::Null,
+            _ => match text.parse::<f64>() {
+                Ok(n) if text.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.') => {
+                    Value::Number(n)
+                }
+                _ => Value::String(text.to_string()),
+            },
+        }),
+    }
+}
+
+// Elementos de una lista o mapa en línea separados por comas fuera de comillas y de colecciones anidadas.
+fn split_flow(inner: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                items.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&inner[start..]);
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules;
+    use std::{env, fs, process};
+
+    fn toml_str(text: &str, key: &str) -> String {
+        let value = parse_toml(text).unwrap();
+        value.get(key).and_then(Value::as_str).unwrap().to_string()
+    }
+
+    #[test]
+    fn toml_quoting_and_escapes() {
+        assert_eq!(toml_str(r#"a = "x\"y\\z\tñ\u00e9""#, "a"), "x\"y\\z\tñé");
+        assert_eq!(toml_str(r"a = 'C:\ruta\(sk|rk)_\d+'", "a"), r"C:\ruta\(sk|rk)_\d+");
+        assert_eq!(
+            toml_str("\"clave con espacios\" = 'v' # comentario", "clave con espacios"),
+            "v"
+        );
+        let nested = parse_toml("[reglas]\napi.token = \"t\"").unwrap();
+        let api = nested.get("reglas").and_then(|r| r.get("api")).unwrap();
+        assert_eq!(api.get("token").and_then(Value::as_str), Some("t"));
+        // Un patrón con \d entre comillas dobles es un error habitual: se indica cómo escribirlo.
+        let error = parse_toml("a = 1\nb = \"\\d+\"").unwrap_err();
+        assert!(error.starts_with("línea 2: escape desconocido \\d"), "{}", error);
+    }
+
+    #[test]
+    fn toml_multiline_strings() {
+        let text = "a = \"\"\"\nuno \\\n    dos\\n\"\"\"\nb = '''\n(sk|rk)_\\d+\n\"fin\"'''\n";
+        let value = parse_toml(text).unwrap();
+        assert_eq!(value.get("a").and_then(Value::as_str), Some("uno dos\n"));
+        assert_eq!(value.get("b").and_then(Value::as_str), Some("(sk|rk)_\\d+\n\"fin\""));
+        assert_eq!(toml_str("a = \"\"\"x \\\\\ny\"\"\"", "a"), "x \\\ny");
+        assert_eq!(toml_str("a = \"\"\"\r\nlínea\r\n\"\"\"", "a"), "línea\r\n");
+        assert_eq!(
+            parse_toml("a = 1\nb = \"\"\"\nsin cerrar\n").unwrap_err(),
+            "línea 2: cadena multilínea sin cerrar"
+        );
+    }
+
+    #[test]
+    fn yaml_scalars_and_blocks() {
+        let text = "a: \"x\\ty\"\nb: 'it''s'\nc: |\n  uno\n  dos\nd: >-\n  uno\n  dos\ne: [1, 'dos']\n";
+        let value = parse_yaml(text).unwrap();
+        assert_eq!(value.get("a").and_then(Value::as_str), Some("x\ty"));
+        assert_eq!(value.get("b").and_then(Value::as_str), Some("it's"));
+        assert_eq!(value.get("c").and_then(Value::as_str), Some("uno\ndos\n"));
+        assert_eq!(value.get("d").and_then(Value::as_str), Some("uno dos"));
+        assert_eq!(value.get("e").and_then(Value::as_array).map(|a| a.len()), Some(2));
+    }
+
+    #[test]
+    fn invalid_packs_report_the_line() {
+        let cases_toml = [
+            ("a = 1\n\na = 2\n", "línea 3: clave 'a' duplicada"),
+            ("[reglas\nid = 1\n", "línea 1: se esperaba ']' al cerrar la tabla"),
+            ("a = 1\nb\n", "línea 2: se esperaba '=' tras la clave"),
+            ("a = 1\nb = \"sin cerrar\nc = 2\n", "línea 2: cadena sin cerrar"),
+            ("a = [1,\n 2\n 3]\n", "línea 3: se esperaba ',' o ']' en el array"),
+        ];
+        for (text, expected) in cases_toml {
+            assert_eq!(parse_toml(text).unwrap_err(), expected, "{:?}", text);
+        }
+        let cases_yaml = [
+            ("a: 1\na: 2\n", "línea 2: clave 'a' duplicada"),
+            ("a:\n\tb: 1\n", "línea 2: tabulación en la indentación"),
+            ("a: 'x\n", "línea 1: cadena sin cerrar"),
+            ("a: *ancla\n", "línea 1: anclas, alias y etiquetas no están soportados"),
+        ];
+        for (text, expected) in cases_yaml {
+            assert_eq!(parse_yaml(text).unwrap_err(), expected, "{:?}", text);
+        }
+    }
+
+    // Un pack de usuario con repeticiones anidadas no debe desbordar la pila ni colgar el análisis.
+    #[test]
+    fn user_pack_with_nested_repeats() {
+        let dir = env::temp_dir().join(format!("auditorlocal-packfile-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("anidado.toml");
+        let pack = "name = 'anidado'\n\n[[rules]]\nid = 'rep-ab'\nseverity = 'alta'\ndescription = 'repetición'\n\
+                    pattern = '(ab)+x'\n\n[[rules]]\nid = 'rep-aa'\nseverity = 'media'\ndescription = 'alternativa'\n\
+                    pattern = '''(a|a)+b'''\n";
+        fs::write(&path, pack).unwrap();
+        let loaded = rules::load_pack(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.rules.len(), 2);
+
+        let long = "ab".repeat(50_000);
+        let repeated = loaded.rules[0].pattern.as_ref().unwrap();
+        assert!(!repeated.is_match(&long));
+        assert_eq!(repeated.find_all("ababx").len(), 1);
+        let alternative = loaded.rules[1].pattern.as_ref().unwrap();
+        assert!(!alternative.is_match(&"a".repeat(5_000)));
+        assert!(alternative.is_match("aaab"));
+    }
+}
@@ -0,0 +1,793 @@
+// Papiweb desarrollos informaticos
+// Motor de patrones mínimo (subconjunto de regex) para no depender de crates externos.
+// Soporta: literales, '.', clases [a-z] y [^...], \d \w \s \b, grupos (...) y (?:...),
+// alternativas '|', anclas '^' '$', cuantificadores * + ? {n} {n,} {n,m} y el prefijo (?i).
+// Antes de recorrer el texto posición por posición se busca un literal que toda coincidencia contiene
+// ("_live_" en "(sk|rk)_live_..."): sin él no puede haber coincidencia y se descarta el texto entero.
+
+// Los patrones de packs de usuario pueden anidar repeticiones ("(ab)+", "(a+)+b"): la vuelta atrás se limita en
+// profundidad de recursión (cada elemento recorrido de un grupo repetido es un nivel) y en pasos por byte del texto.
+// Al agotarse, esa rama no coincide y la búsqueda sigue: peor caso lineal en el texto, sin desbordar la pila.
+use std::cell::Cell;
+
+const MAX_DEPTH: u32 = 1_000;
+const STEPS_PER_BYTE: u64 = 256;
+const MIN_STEPS: u64 = 100_000;
+
+// Bytes de una clase, un bit por byte: [a-z0-9] marca de 'a' a 'z' y de '0' a '9'. Con (?i) ya incluye las dos
+// formas de cada letra, así que comprobar un byte es leer un bit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    fn range(lo: u8, hi: u8) -> Self {
+        let mut set = Self::default();
+        (lo..=hi).for_each(|c| set.insert(c));
+        set
+    }
+
+    fn insert(&mut self, c: u8) {
+        self.0[usize::from(c >> 6)] |= 1 << (c & 63);
+    }
+
+    fn contains(&self, c: u8) -> bool {
+        self.0[usize::from(c >> 6)] & (1 << (c & 63)) != 0
+    }
+
+    fn union(self, other: Self) -> Self {
+        ByteSet([0, 1, 2, 3].map(|i| self.0[i] | other.0[i]))
+    }
+
+    fn complement(self) -> Self {
+        ByteSet(self.0.map(|word| !word))
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.union(*other) == *other
+    }
+
+    fn fold_case(self) -> Self {
+        let mut folded = self;
+        for c in (0..=255u8).filter(|&c| self.contains(c)) {
+            folded.insert(c.to_ascii_lowercase());
+            folded.insert(c.to_ascii_uppercase());
+        }
+        folded
+    }
+}
+
+// Texto de una búsqueda con lo que le queda de presupuesto.
+struct Input<'a> {
+    bytes: &'a [u8],
+    depth: Cell<u32>,
+    steps: Cell<u64>,
+}
+
+impl<'a> Input<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Input {
+            bytes,
+            depth: Cell::new(0),
+            steps: Cell::new(MIN_STEPS.max(bytes.len() as u64 * STEPS_PER_BYTE)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Byte(u8),
+    Any,
+    Class(ByteSet, bool),
+    Start,
+    End,
+    WordBoundary,
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat(Box<Node>, u32, Option<u32>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    root: Node,
+    ignore_case: bool,
+    // Toda coincidencia contiene al menos uno de estos literales; vacío si el patrón no garantiza ninguno.
+    required: Vec<Vec<u8>>,
+    // Primeros bytes de esos literales: el texto se recorre una sola vez y solo se compara donde empieza alguno.
+    required_heads: ByteSet,
+    // Patrones que empiezan con una racha de clase ("[A-Za-z_][A-Za-z0-9_]*...", "[a-z0-9]{20,}"): clase del
+    // primer carácter y de la racha.
+    leading_run: Option<(ByteSet, ByteSet)>,
+    // Bytes con los que puede empezar una coincidencia; None si el patrón acepta el texto vacío.
+    first: Option<ByteSet>,
+}
+
+impl Pattern {
+    pub fn new(source: &str) -> Result<Self, String> {
+        let (ignore_case, body) = match source.strip_prefix("(?i)") {
+            Some(rest) => (true, rest),
+            None => (false, source),
+        };
+        let mut parser = Parser {
+            input: body.as_bytes(),
+            pos: 0,
+        };
+        let mut root = parser.parse_alt()?;
+        if parser.pos != parser.input.len() {
+            return Err(format!("patrón '{}': ')' sin abrir en posición {}", source, parser.pos));
+        }
+        if ignore_case {
+            fold_classes(&mut root);
+        }
+        let (first, nullable) = first_bytes(&root, ignore_case);
+        let mut required = required_literals(&root).unwrap_or_default();
+        if ignore_case {
+            required.iter_mut().for_each(|literal| literal.make_ascii_lowercase());
+        }
+        let required_heads = required
+            .iter()
+            .filter_map(|literal| literal.first())
+            .fold(ByteSet::default(), |heads, &c| heads.union(ByteSet::range(c, c)));
+        Ok(Self {
+            leading_run: leading_run(&root),
+            first: (!nullable).then_some(first),
+            root,
+            ignore_case,
+            required,
+            required_heads: if ignore_case {
+                required_heads.fold_case()
+            } else {
+                required_heads
+            },
+        })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.may_match(text.as_bytes()) && self.find_at(&Input::new(text.as_bytes()), 0).is_some()
+    }
+
+    // Todas las coincidencias sin solapamiento, como rangos de bytes.
+    pub fn find_all(&self, text: &str) -> Vec<(usize, usize)> {
+        let input = Input::new(text.as_bytes());
+        let mut matches = Vec::new();
+        if !self.may_match(input.bytes) {
+            return matches;
+        }
+        let mut from = 0;
+        while from <= input.bytes.len() {
+            match self.find_at(&input, from) {
+                Some((start, end)) => {
+                    matches.push((start, end));
+                    from = if end > start { end } else { end + 1 };
+                }
+                None => break,
+            }
+        }
+        matches
+    }
+
+    // Las coincidencias empiezan y terminan en límites de carácter: quien las usa corta el &str con ellas.
+    fn find_at(&self, text: &Input, from: usize) -> Option<(usize, usize)> {
+        let input = text.bytes;
+        let mut start = from;
+        while start <= input.len() {
+            // Hasta el próximo byte que puede abrir una coincidencia no hace falta entrar al motor: en una línea
+            // típica casi todas las posiciones se descartan así.
+            if let Some(first) = &self.first {
+                match input[start..].iter().position(|&c| first.contains(c)) {
+                    Some(skip) => start += skip,
+                    None => return None,
+                }
+            }
+            if !is_char_boundary(input, start) {
+                start += 1;
+                continue;
+            }
+            let mut found = None;
+            if self.match_node(&self.root, text, start, &mut |end| {
+                if !is_char_boundary(input, end) {
+                    return false;
+                }
+                found = Some(end);
+                true
+            }) {
+                return found.map(|end| (start, end));
+            }
+            // Si falló empezando en la racha, empezar más adentro solo ofrece un subconjunto de los mismos finales
+            // para el resto del patrón: se salta la racha entera en lugar de reintentar en cada byte.
+            if let Some((first, run)) = &self.leading_run {
+                if input.get(start).is_some_and(|&c| first.contains(c)) {
+                    start += 1;
+                    while input.get(start).is_some_and(|&c| run.contains(c)) {
+                        start += 1;
+                    }
+                    continue;
+                }
+            }
+            start += 1;
+        }
+        None
+    }
+
+    fn may_match(&self, input: &[u8]) -> bool {
+        self.required.is_empty()
+            || input
+                .iter()
+                .enumerate()
+                .filter(|&(_, &c)| self.required_heads.contains(c))
+                .any(|(at, _)| {
+                    self.required.iter().any(|literal| {
+                        input[at..].get(..literal.len()).is_some_and(|window| {
+                            if self.ignore_case {
+                                window.eq_ignore_ascii_case(literal)
+                            } else {
+                                window == literal.as_slice()
+                            }
+                        })
+                    })
+                })
+    }
+
+    fn byte_eq(&self, a: u8, b: u8) -> bool {
+        if self.ignore_case {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    }
+
+    // Bytes que consume un nodo simple en `pos`, o None si no coincide. '.' y las clases negadas consumen el carácter
+    // UTF-8 entero: "corp_.{3}" cuenta caracteres, no bytes.
+    fn step(&self, node: &Node, input: &[u8], pos: usize) -> Option<usize> {
+        let c = *input.get(pos)?;
+        let (matched, whole_char) = match node {
+            Node::Byte(b) => (self.byte_eq(*b, c), false),
+            Node::Any => (c != b'\n', true),
+            Node::Class(set, negated) => (set.contains(c) != *negated, *negated),
+            _ => return None,
+        };
+        let width = if whole_char { utf8_width(c) } else { 1 };
+        (matched && pos + width <= input.len()).then_some(width)
+    }
+
+    // Cada nodo que coincide llama a su continuación sin volver: la profundidad de la pila crece con lo recorrido.
+    fn match_node(&self, node: &Node, text: &Input, pos: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+        let (steps, depth) = (text.steps.get(), text.depth.get());
+        if steps == 0 || depth >= MAX_DEPTH {
+            return false;
+        }
+        text.steps.set(steps - 1);
+        text.depth.set(depth + 1);
+        let matched = self.match_node_inner(node, text, pos, k);
+        text.depth.set(depth);
+        matched
+    }
+
+    fn match_node_inner(&self, node: &Node, text: &Input, pos: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+        let input = text.bytes;
+        match node {
+            Node::Byte(_) | Node::Any | Node::Class(..) => self.step(node, input, pos).is_some_and(|w| k(pos + w)),
+            Node::Start => pos == 0 && k(pos),
+            Node::End => pos == input.len() && k(pos),
+            Node::WordBoundary => {
+                let before = pos > 0 && is_word(input[pos - 1]);
+                let after = pos < input.len() && is_word(input[pos]);
+                before != after && k(pos)
+            }
+            Node::Concat(items) => self.match_seq(items, text, pos, k),
+            Node::Alt(alternatives) => alternatives.iter().any(|alt| self.match_node(alt, text, pos, k)),
+            Node::Repeat(inner, min, max) => {
+                let limit = max.map_or(usize::MAX, |m| m as usize);
+                if matches!(**inner, Node::Byte(_) | Node::Class(_, false)) {
+                    // Un byte o una clase sin negar avanzan siempre de a un byte: basta contar las repeticiones.
+                    let mut count = 0;
+                    while count < limit && self.step(inner, input, pos + count).is_some() {
+                        count += 1;
+                    }
+                    (*min as usize..=count).rev().any(|n| k(pos + n))
+                } else if matches!(**inner, Node::Any | Node::Class(..)) {
+                    // Repetición de un nodo simple: greedy iterativo, sin recursión profunda. `ends` guarda dónde
+                    // termina cada repetición porque un carácter puede ocupar varios bytes.
+                    let mut ends = vec![pos];
+                    while ends.len() - 1 < limit {
+                        let at = ends[ends.len() - 1];
+                        match self.step(inner, input, at) {
+                            Some(width) => ends.push(at + width),
+                            None => break,
+                        }
+                    }
+                    ends.iter().skip(*min as usize).rev().any(|&end| k(end))
+                } else {
+                    self.match_repeat(inner, *min, *max, text, pos, 0, k)
+                }
+            }
+        }
+    }
+
+    fn match_seq(&self, items: &[Node], text: &Input, pos: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+        match items.split_first() {
+            None => k(pos),
+            Some((first, rest)) => self.match_node(first, text, pos, &mut |next| self.match_seq(rest, text, next, k)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn match_repeat(
+        &self,
+        inner: &Node,
+        min: u32,
+        max: Option<u32>,
+        text: &Input,
+        pos: usize,
+        count: u32,
+        k: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        if max.is_none_or(|m| count < m)
+            && self.match_node(inner, text, pos, &mut |next| {
+                next != pos && self.match_repeat(inner, min, max, text, next, count + 1, k)
+            })
+        {
+            return true;
+        }
+        count >= min && k(pos)
+    }
+}
+
+// Literales de los que toda coincidencia de `node` contiene al menos uno, o None si no hay garantía.
+// En una secuencia se elige el candidato cuyo literal más corto es más largo: es el que más texto descarta.
+fn required_literals(node: &Node) -> Option<Vec<Vec<u8>>> {
+    match node {
+        Node::Byte(b) => Some(vec![vec![*b]]),
+        Node::Repeat(inner, min, _) if *min > 0 => required_literals(inner),
+        Node::Alt(alternatives) => {
+            let mut literals = Vec::new();
+            for alternative in alternatives {
+                literals.extend(required_literals(alternative)?);
+            }
+            Some(literals)
+        }
+        Node::Concat(items) => {
+            let shortest = |literals: &Vec<Vec<u8>>| literals.iter().map(Vec::len).min().unwrap_or(0);
+            let mut best: Option<Vec<Vec<u8>>> = None;
+            let mut consider = |candidate: Option<Vec<Vec<u8>>>| {
+                if let Some(candidate) = candidate.filter(|c| !c.is_empty()) {
+                    if best.as_ref().is_none_or(|b| shortest(&candidate) > shortest(b)) {
+                        best = Some(candidate);
+                    }
+                }
+            };
+            // Bytes literales consecutivos; las anclas no consumen texto y no cortan la secuencia.
+            let mut run = Vec::new();
+            for item in items {
+                match item {
+                    Node::Start | Node::End | Node::WordBoundary => {}
+                    Node::Byte(b) => run.push(*b),
+                    Node::Concat(bytes) if bytes.iter().all(|n| matches!(n, Node::Byte(_))) => {
+                        run.extend(bytes.iter().filter_map(|n| match n {
+                            Node::Byte(b) => Some(*b),
+                            _ => None,
+                        }))
+                    }
+                    _ => {
+                        if !run.is_empty() {
+                            consider(Some(vec![std::mem::take(&mut run)]));
+                        }
+                        consider(required_literals(item));
+                    }
+                }
+            }
+            if !run.is_empty() {
+                consider(Some(vec![run]));
+            }
+            best
+        }
+        _ => None,
+    }
+}
+
+// Primer carácter y racha de "c1 c2*" (con c1 incluida en c2) o de "c{n,}", ambas clases sin negar.
+fn leading_run(root: &Node) -> Option<(ByteSet, ByteSet)> {
+    let Node::Concat(items) = root else {
+        return None;
+    };
+    let class = |node: &Node| match node {
+        Node::Class(set, false) => Some(*set),
+        _ => None,
+    };
+    match items.as_slice() {
+        [Node::Repeat(inner, _, None), ..] => class(inner).map(|run| (run, run)),
+        [first, Node::Repeat(inner, 0, None), ..] => {
+            let (first, run) = (class(first)?, class(inner)?);
+            first.is_subset(&run).then_some((first, run))
+        }
+        _ => None,
+    }
+}
+
+// Bytes con los que puede empezar una coincidencia de `node`, y si `node` acepta el texto vacío (entonces lo que
+// sigue también puede aportar el primer byte). Las anclas no consumen texto.
+fn first_bytes(node: &Node, ignore_case: bool) -> (ByteSet, bool) {
+    match node {
+        Node::Byte(b) if ignore_case => (ByteSet::range(*b, *b).fold_case(), false),
+        Node::Byte(b) => (ByteSet::range(*b, *b), false),
+        Node::Any => (ByteSet::range(b'\n', b'\n').complement(), false),
+        Node::Class(set, false) => (*set, false),
+        Node::Class(set, true) => (set.complement(), false),
+        Node::Start | Node::End | Node::WordBoundary => (ByteSet::default(), true),
+        Node::Concat(items) => {
+            let mut first = ByteSet::default();
+            for item in items {
+                let (item_first, nullable) = first_bytes(item, ignore_case);
+                first = first.union(item_first);
+                if !nullable {
+                    return (first, false);
+                }
+            }
+            (first, true)
+        }
+        Node::Alt(alternatives) => alternatives
+            .iter()
+            .fold((ByteSet::default(), false), |(first, nullable), alt| {
+                let (alt_first, alt_nullable) = first_bytes(alt, ignore_case);
+                (first.union(alt_first), nullable || alt_nullable)
+            }),
+        Node::Repeat(inner, min, _) => {
+            let (first, nullable) = first_bytes(inner, ignore_case);
+            (first, nullable || *min == 0)
+        }
+    }
+}
+
+fn fold_classes(node: &mut Node) {
+    match node {
+        Node::Class(set, _) => *set = set.fold_case(),
+        Node::Concat(items) | Node::Alt(items) => items.iter_mut().for_each(fold_classes),
+        Node::Repeat(inner, ..) => fold_classes(inner),
+        _ => {}
+    }
+}
+
+fn is_char_boundary(input: &[u8], pos: usize) -> bool {
+    // Los bytes de continuación UTF-8 son 0b10xxxxxx.
+    input.get(pos).is_none_or(|&c| c & 0xc0 != 0x80)
+}
+
+fn utf8_width(c: u8) -> usize {
+    match c {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    }
+}
+
+fn is_word(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<u8, String> {
+        let c = self
+            .peek()
+            .ok_or_else(|| "patrón terminado de forma inesperada".to_string())?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn parse_alt(&mut self) -> Result<Node, String> {
+        let mut alternatives = vec![self.parse_concat()?];
+        while self.peek() == Some(b'|') {
+            self.pos += 1;
+            alternatives.push(self.parse_concat()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Node::Alt(alternatives)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, String> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == b'|' || c == b')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            items.push(self.parse_quantifier(atom)?);
+        }
+        Ok(Node::Concat(items))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some(b'*') => (0, None),
+            Some(b'+') => (1, None),
+            Some(b'?') => (0, Some(1)),
+            Some(b'{') => {
+                let close = self.input[self.pos..]
+                    .iter()
+                    .position(|&c| c == b'}')
+                    .ok_or_else(|| "cuantificador '{' sin cerrar".to_string())?;
+                let body = std::str::from_utf8(&self.input[self.pos + 1..self.pos + close])
+                    .map_err(|_| "cuantificador inválido".to_string())?;
+                let parse = |s: &str| {
+                    s.trim()
+                        .parse::<u32>()
+                        .map_err(|_| format!("cuantificador inválido '{{{}}}'", body))
+                };
+                let bounds = match body.split_once(',') {
+                    None => {
+                        let n = parse(body)?;
+                        (n, Some(n))
+                    }
+                    Some((lo, "")) => (parse(lo)?, None),
+                    Some((lo, hi)) => (parse(lo)?, Some(parse(hi)?)),
+                };
+                self.pos += close;
+                bounds
+            }
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, String> {
+        match self.next()? {
+            b'(' => {
+                if self.input[self.pos..].starts_with(b"?:") {
+                    self.pos += 2;
+                }
+                let inner = self.parse_alt()?;
+                if self.next()? != b')' {
+                    return Err("grupo sin cerrar".to_string());
+                }
+                Ok(inner)
+            }
+            b'[' => self.parse_class(),
+            b'.' => Ok(Node::Any),
+            b'^' => Ok(Node::Start),
+            b'$' => Ok(Node::End),
+            b'\\' => {
+                let c = self.next()?;
+                Ok(match c {
+                    b'b' => Node::WordBoundary,
+                    b'd' | b'D' | b'w' | b'W' | b's' | b'S' => {
+                        Node::Class(shorthand(c.to_ascii_lowercase()), c.is_ascii_uppercase())
+                    }
+                    _ => Node::Byte(escaped(c)),
+                })
+            }
+            b'*' | b'+' | b'?' => Err("cuantificador sin expresión previa".to_string()),
+            // Un carácter no ASCII es un solo átomo: "ñ+" repite el carácter, no su último byte.
+            c if c >= 0x80 => {
+                let end = (self.pos - 1 + utf8_width(c)).min(self.input.len());
+                let bytes = self.input[self.pos - 1..end].iter().map(|&b| Node::Byte(b)).collect();
+                self.pos = end;
+                Ok(Node::Concat(bytes))
+            }
+            c => Ok(Node::Byte(c)),
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Node, String> {
+        let negated = self.peek() == Some(b'^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut set = ByteSet::default();
+        let mut first = true;
+        loop {
+            let c = self.next().map_err(|_| "clase '[' sin cerrar".to_string())?;
+            if c == b']' && !first {
+                break;
+            }
+            first = false;
+            let lo = if c == b'\\' {
+                let e = self.next()?;
+                if matches!(e, b'd' | b'w' | b's') {
+                    set = set.union(shorthand(e));
+                    continue;
+                }
+                escaped(e)
+            } else {
+                c
+            };
+            if self.peek() == Some(b'-') && self.input.get(self.pos + 1).is_some_and(|&n| n != b']') {
+                self.pos += 1;
+                let mut hi = self.next()?;
+                if hi == b'\\' {
+                    hi = escaped(self.next()?);
+                }
+                if hi < lo {
+                    return Err(format!("rango inválido en clase: {}-{}", lo as char, hi as char));
+                }
+                set = set.union(ByteSet::range(lo, hi));
+            } else {
+                set.insert(lo);
+            }
+        }
+        Ok(Node::Class(set, negated))
+    }
+}
+
+fn shorthand(c: u8) -> ByteSet {
+    let ranges: &[(u8, u8)] = match c {
+        b'd' => &[(b'0', b'9')],
+        b'w' => &[(b'a', b'z'), (b'A', b'Z'), (b'0', b'9'), (b'_', b'_')],
+        _ => &[
+            (b' ', b' '),
+            (b'\t', b'\t'),
+            (b'\n', b'\n'),
+            (b'\r', b'\r'),
+            (0x0b, 0x0c),
+        ],
+    };
+    ranges
+        .iter()
+        .fold(ByteSet::default(), |set, &(lo, hi)| set.union(ByteSet::range(lo, hi)))
+}
+
+fn escaped(c: u8) -> u8 {
+    match c {
+        b'n' => b'\n',
+        b't' => b'\t',
+        b'r' => b'\r',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pattern;
+
+    fn find(pattern: &str, text: &str) -> Option<String> {
+        let pattern = Pattern::new(pattern).expect("patrón válido");
+        pattern
+            .find_all(text)
+            .first()
+            .map(|&(start, end)| text[start..end].to_string())
+    }
+
+    #[test]
+    fn anchors_and_word_boundaries() {
+        assert_eq!(find("^abc$", "abc").as_deref(), Some("abc"));
+        assert_eq!(find("^abc$", "xabc"), None);
+        assert_eq!(find("^abc", "abcd").as_deref(), Some("abc"));
+        assert_eq!(find(r"\bkey\b", "a key here").as_deref(), Some("key"));
+        assert_eq!(find(r"\bkey\b", "monkeys"), None);
+    }
+
+    #[test]
+    fn classes_and_shorthands() {
+        assert_eq!(find("[a-c]+", "xxabcabd").as_deref(), Some("abcab"));
+        assert_eq!(find("[^0-9]+", "12ab34").as_deref(), Some("ab"));
+        assert_eq!(find(r"\d{3}-\w+", "id 123-ab_9 fin").as_deref(), Some("123-ab_9"));
+        assert_eq!(find(r"[\s=]+", "a = b").as_deref(), Some(" = "));
+        assert_eq!(find("[a-]+", "--a-").as_deref(), Some("--a-"));
+        assert_eq!(find("(?i)[a-f]+", "XYZ ABCdef").as_deref(), Some("ABCdef"));
+        assert!(Pattern::new("[z-a]").is_err());
+        assert!(Pattern::new("[abc").is_err());
+    }
+
+    #[test]
+    fn repetition_and_alternatives() {
+        assert_eq!(find("ab{2,3}", "abbbb").as_deref(), Some("abbb"));
+        assert_eq!(find("ab{2,}c", "abbbbc").as_deref(), Some("abbbbc"));
+        assert_eq!(find("ab{2}c", "abbbc"), None);
+        assert_eq!(find("a.*b", "a12b34b").as_deref(), Some("a12b34b"));
+        assert_eq!(find("(?:ab)+c", "abababc").as_deref(), Some("abababc"));
+        assert_eq!(find("cat|dog", "hotdog").as_deref(), Some("dog"));
+        assert_eq!(find("x?y", "y").as_deref(), Some("y"));
+        assert!(Pattern::new("*a").is_err());
+        assert!(Pattern::new("(ab").is_err());
+        assert!(Pattern::new("ab)").is_err());
+    }
+
+    #[test]
+    fn find_all_does_not_overlap() {
+        let pattern = Pattern::new("aa").unwrap();
+        assert_eq!(pattern.find_all("aaaaa"), vec![(0, 2), (2, 4)]);
+        let empty = Pattern::new("x*").unwrap();
+        assert_eq!(empty.find_all("ab").len(), 3);
+    }
+
+    #[test]
+    fn required_literals_prefilter() {
+        let literals = |source: &str| Pattern::new(source).unwrap().required;
+        assert_eq!(literals(r"\b(sk|rk)_live_[0-9A-Za-z]{16,}"), [b"_live_".to_vec()]);
+        assert_eq!(
+            literals(r"\b(AKIA|ASIA)[0-9A-Z]{16}\b"),
+            [b"AKIA".to_vec(), b"ASIA".to_vec()]
+        );
+        assert_eq!(literals("(?i)Bearer +[a-z]+"), [b"bearer".to_vec()]);
+        assert_eq!(literals("x(ab)+y"), [b"ab".to_vec()]);
+        assert!(literals("[a-z]{20,}").is_empty());
+        assert!(literals("(ab)?c*").is_empty());
+        assert!(literals("ab|[0-9]").is_empty());
+
+        assert_eq!(find("(?i)bearer [a-z]+", "BEARER abc").as_deref(), Some("BEARER abc"));
+        assert_eq!(find("(sk|rk)_live_[0-9]+", "rk_live_42").as_deref(), Some("rk_live_42"));
+        assert_eq!(find("(sk|rk)_live_[0-9]+", "sk_test_42 rk_live"), None);
+        assert!(Pattern::new("ñ+").unwrap().is_match("xñy"));
+    }
+
+    #[test]
+    fn leading_runs_are_skipped_only_after_a_failed_start() {
+        assert!(Pattern::new("[a-z_][a-z0-9_]*=x").unwrap().leading_run.is_some());
+        assert!(Pattern::new("[a-z0-9][a-z]*=x").unwrap().leading_run.is_none());
+        assert_eq!(find("[a-z_][a-z0-9_]*=x", "abc=y ab_1=x").as_deref(), Some("ab_1=x"));
+        assert_eq!(find("[a-z][a-z0-9]*:", "9ab:").as_deref(), Some("ab:"));
+        assert_eq!(find("[a-z]{3,}!", "ab abcd! ").as_deref(), Some("abcd!"));
+        assert_eq!(find("(?i)[a-z]{2,}1", "ABc AB1").as_deref(), Some("AB1"));
+        assert_eq!(Pattern::new("[0-9]*").unwrap().find_all("a12b").len(), 4);
+    }
+
+    #[test]
+    fn first_bytes_skip_positions_that_cannot_start() {
+        let first = |source: &str| Pattern::new(source).unwrap().first;
+        let akia = first(r"\b(AKIA|ASIA)[0-9A-Z]{16}\b").unwrap();
+        assert!(akia.contains(b'A') && !akia.contains(b'K'));
+        assert!(first("(?i)key").unwrap().contains(b'K'));
+        assert!(first("x?y").unwrap().contains(b'x') && first("x?y").unwrap().contains(b'y'));
+        assert!(first("x*").is_none());
+        assert!(first("^$").is_none());
+
+        assert_eq!(find("(?i)KEY=[a-z]+", "mi key=Valor").as_deref(), Some("key=Valor"));
+        assert_eq!(find("[^a-z]b", "aab ñb").as_deref(), Some("ñb"));
+        assert_eq!(
+            find(r"\bAKIA[0-9]{4}", "xAKIA1234 AKIA5678").as_deref(),
+            Some("AKIA5678")
+        );
+        assert_eq!(Pattern::new("b+").unwrap().find_all("abba b"), vec![(1, 3), (5, 6)]);
+    }
+
+    // Regresión: una coincidencia que terminaba dentro de "ñ" hacía entrar en pánico al cortar el &str.
+    #[test]
+    fn utf8_matches_end_on_char_boundaries() {
+        assert_eq!(find("corp_.{3}", "X=corp_abñcdef").as_deref(), Some("corp_abñ"));
+        assert_eq!(find("[^a-z]+", "abñé€z").as_deref(), Some("ñé€"));
+        assert_eq!(find("a.b", "a€b").as_deref(), Some("a€b"));
+        assert_eq!(find("ñ+", "xññy").as_deref(), Some("ññ"));
+        for text in ["ñ", "añ", "€€", "😀x", "x😀"] {
+            for pattern in [".", ".{2}", "[^x]", "[\u{80}-\u{ff}]", "x?", "[a-z]*"] {
+                let compiled = Pattern::new(pattern).unwrap();
+                for (start, end) in compiled.find_all(text) {
+                    assert!(
+                        text.get(start..end).is_some(),
+                        "{} en {:?}: {}..{}",
+                        pattern,
+                        text,
+                        start,
+                        end
+                    );
+                }
+            }
+        }
+    }
+
+    // Regresión: "(ab)+" sobre una racha larga desbordaba la pila y "(a|a)+b" tardaba un tiempo exponencial.
+    #[test]
+    fn nested_repeats_are_bounded() {
+        let text = "ab".repeat(100_000);
+        let matches = Pattern::new("(ab)+").unwrap().find_all(&text);
+        assert!(!matches.is_empty());
+        assert!(matches.iter().all(|&(start, end)| start < end && end <= text.len()));
+        assert!(!Pattern::new("(ab)+x").unwrap().is_match(&text));
+
+        let start = std::time::Instant::now();
+        assert!(!Pattern::new("(a|a)+b").unwrap().is_match(&"a".repeat(40)));
+        assert!(!Pattern::new("(a+)+b").unwrap().is_match(&"a".repeat(40)));
+        assert!(start.elapsed().as_secs() < 5);
+        assert_eq!(find("(a|a)+b", "aaab").as_deref(), Some("aaab"));
+    }
+}
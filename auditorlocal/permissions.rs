@@ -0,0 +1,398 @@
+// Papiweb desarrollos informaticos
+// Permisos de los archivos con hallazgos: dueño, grupo, modo, ACL POSIX y directorios padre modificables.
+// El mismo secreto pesa distinto en un 0600 del usuario del servicio que en un archivo legible por todos.
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+use std::os::raw::{c_char, c_void};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::finding::{AuditPriority, Finding, Location, Note, SecretKind};
+use crate::system;
+
+// Atributo extendido con la ACL de acceso (formato binario de Linux: versión 2 y entradas de 8 bytes).
+const ACL_XATTR: &[u8] = b"system.posix_acl_access\0";
+const ACL_VERSION: u32 = 2;
+const ACL_USER: u16 = 0x02;
+const ACL_GROUP: u16 = 0x08;
+const ACL_MASK: u16 = 0x10;
+const KEY_NAMES: &[&str] = &[
+    "id_rsa",
+    "id_dsa",
+    "id_ecdsa",
+    "id_ed25519",
+    "id_ecdsa_sk",
+    "id_ed25519_sk",
+];
+const KEY_EXTENSIONS: &[&str] = &["key", "pem", "p12", "pfx", "jks", "keystore"];
+
+extern "C" {
+    fn getxattr(path: *const c_char, name: *const c_char, value: *mut c_void, size: usize) -> isize;
+}
+
+struct AclEntry {
+    group: bool,
+    id: u32,
+    perm: u16,
+}
+
+struct FileAccess {
+    uid: u32,
+    gid: u32,
+    mode: u32,
+    acl: Vec<AclEntry>,
+    // Primer directorio padre en el que otro usuario puede renombrar o reemplazar el archivo.
+    writable_parent: Option<(PathBuf, String)>,
+}
+
+impl FileAccess {
+    fn read(path: &Path) -> Option<Self> {
+        let path = path.canonicalize().ok()?;
+        let metadata = fs::metadata(&path).ok()?;
+        let writable_parent = path.ancestors().skip(1).find_map(|dir| {
+            let parent = fs::metadata(dir).ok()?;
+            other_writers(&parent, metadata.uid()).map(|reason| (dir.to_path_buf(), reason))
+        });
+        Some(Self {
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            mode: metadata.mode() & 0o7777,
+            acl: acl_entries(&path),
+            writable_parent,
+        })
+    }
+}
+
+// Quién más puede modificar el directorio: escritura de grupo/otros sin sticky bit o un dueño ajeno.
+fn other_writers(dir: &fs::Metadata, file_uid: u32) -> Option<String> {
+    let mode = dir.mode() & 0o7777;
+    let sticky = mode & 0o1000 != 0;
+    if mode & 0o002 != 0 && !sticky {
+        Some(format!("permisos {:04o}: modificable por cualquier usuario", mode))
+    } else if mode & 0o020 != 0 && !sticky && dir.gid() != 0 {
+        Some(format!(
+            "permisos {:04o}: modificable por el grupo gid {}",
+            mode,
+            dir.gid()
+        ))
+    } else if dir.uid() != 0 && dir.uid() != file_uid {
+        Some(format!("pertenece a otro usuario (uid {})", dir.uid()))
+    } else {
+        None
+    }
+}
+
+fn acl_entries(path: &Path) -> Vec<AclEntry> {
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return Vec::new();
+    };
+    let mut buffer = [0u8; 4096];
+    // getxattr(2) de la libc que ya enlaza std: rutas terminadas en NUL y búfer con su tamaño real.
+    let len = unsafe {
+        getxattr(
+            c_path.as_ptr(),
+            ACL_XATTR.as_ptr().cast(),
+            buffer.as_mut_ptr().cast(),
+            buffer.len(),
+        )
+    };
+    // Sin ACL extendida (ENODATA), sistema de archivos sin soporte o error: solo cuentan los bits del modo.
+    if len < 4 {
+        return Vec::new();
+    }
+    let data = &buffer[..len as usize];
+    if u32::from_le_bytes([data[0], data[1], data[2], data[3]]) != ACL_VERSION {
+        return Vec::new();
+    }
+    let entries: Vec<(u16, u16, u32)> = data[4..]
+        .chunks_exact(8)
+        .map(|e| {
+            (
+                u16::from_le_bytes([e[0], e[1]]),
+                u16::from_le_bytes([e[2], e[3]]),
+                u32::from_le_bytes([e[4], e[5], e[6], e[7]]),
+            )
+        })
+        .collect();
+    // La máscara limita lo que conceden las entradas con nombre.
+    let mask = entries.iter().find(|e| e.0 == ACL_MASK).map_or(0o7, |e| e.1);
+    entries
+        .into_iter()
+        .filter(|e| e.0 == ACL_USER || e.0 == ACL_GROUP)
+        .map(|(tag, perm, id)| AclEntry {
+            group: tag == ACL_GROUP,
+            id,
+            perm: perm & mask,
+        })
+        .collect()
+}
+
+// Nombres de usuarios y grupos: se leen una vez por análisis y sirven para todos los lotes de hallazgos.
+pub struct Owners {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl Owners {
+    pub fn load() -> Self {
+        Self {
+            users: system::user_names(),
+            groups: system::group_names(),
+        }
+    }
+}
+
+fn is_key_file(finding: &Finding, path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    finding.kind == SecretKind::PrivateKey
+        || KEY_NAMES.contains(&name.as_str())
+        || KEY_EXTENSIONS.contains(&ext.as_str())
+}
+
+// Solo las claves privadas y los almacenes de credenciales suben de severidad por sus permisos; en el resto los
+// permisos quedan como nota.
+fn is_sensitive(finding: &Finding, path: &Path) -> bool {
+    finding.kind == SecretKind::CredentialStore || is_key_file(finding, path)
+}
+
+fn raise(finding: &mut Finding, notes: Vec<Note>, sensitive: bool) {
+    if sensitive {
+        if let Some(worst) = notes.iter().map(|n| n.priority).max() {
+            finding.priority = finding.priority.max(worst);
+        }
+    }
+    finding.notes.extend(notes);
+}
+
+pub fn annotate(findings: &mut [Finding], owners: &Owners) {
+    let Owners { users, groups } = owners;
+    let name = |names: &HashMap<u32, String>, id: u32| names.get(&id).cloned().unwrap_or_else(|| id.to_string());
+    let mut cache: HashMap<PathBuf, Option<FileAccess>> = HashMap::new();
+    for finding in findings.iter_mut() {
+        let Location::File { path, .. } = &finding.location else {
+            continue;
+        };
+        // Los archivos de configuración son legibles por diseño: sus chequeos revisan los permisos que importan.
+        if finding.kind == SecretKind::Misconfiguration {
+            continue;
+        }
+        let Some(access) = cache.entry(path.clone()).or_insert_with(|| FileAccess::read(path)) else {
+            continue;
+        };
+        let key_file = is_key_file(finding, path);
+        let sensitive = is_sensitive(finding, path);
+        let who = |entry: &AclEntry| {
+            if entry.group {
+                format!("group:{}", name(groups, entry.id))
+            } else {
+                format!("user:{}", name(users, entry.id))
+            }
+        };
+        let acl: Vec<String> = access
+            .acl
+            .iter()
+            .map(|e| format!("{}:{}", who(e), rwx(e.perm)))
+            .collect();
+        let mut notes = vec![Note {
+            id: "file-access",
+            priority: AuditPriority::Low,
+            text: format!(
+                "archivo {}:{} {:04o}{}",
+                name(users, access.uid),
+                name(groups, access.gid),
+                access.mode,
+                if acl.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ACL {}", acl.join(","))
+                }
+            ),
+        }];
+        // Los almacenes de credenciales ya evalúan sus bits de modo: no se repiten.
+        if finding.kind != SecretKind::CredentialStore {
+            notes.extend(mode_notes(access.mode, key_file, sensitive));
+        }
+        let readers: Vec<String> = access.acl.iter().filter(|e| e.perm & 0o4 != 0).map(who).collect();
+        if !readers.is_empty() {
+            notes.push(Note {
+                id: "file-acl-read",
+                priority: if key_file {
+                    AuditPriority::High
+                } else {
+                    AuditPriority::Medium
+                },
+                text: format!(
+                    "la ACL concede lectura a {}: quitarla con setfacl -b",
+                    readers.join(", ")
+                ),
+            });
+        }
+        if let Some((dir, reason)) = &access.writable_parent {
+            notes.push(Note {
+                id: "parent-writable",
+                priority: AuditPriority::High,
+                text: format!(
+                    "directorio padre {} ({}): otro usuario puede reemplazar o renombrar el archivo",
+                    dir.display(),
+                    reason
+                ),
+            });
+        }
+        raise(finding, notes, sensitive);
+    }
+}
+
+// Modo del blob en el índice para el hook: git solo guarda 644 o 755, sin dueño ni ACL, y es el que tendrá cada
+// checkout del commit.
+pub fn annotate_staged(findings: &mut [Finding], mode: u32) {
+    let mode = mode & 0o777;
+    for finding in findings.iter_mut() {
+        let Location::File { path, .. } = &finding.location else {
+            continue;
+        };
+        if finding.kind == SecretKind::Misconfiguration {
+            continue;
+        }
+        let key_file = is_key_file(finding, path);
+        let sensitive = is_sensitive(finding, path);
+        let mut notes = vec![Note {
+            id: "file-access",
+            priority: AuditPriority::Low,
+            text: format!("modo en el índice git {:04o}", mode),
+        }];
+        notes.extend(mode_notes(mode, key_file, sensitive));
+        raise(finding, notes, sensitive);
+    }
+}
+
+fn mode_notes(mode: u32, key_file: bool, sensitive: bool) -> Vec<Note> {
+    let mut notes = Vec::new();
+    if mode & 0o004 != 0 {
+        notes.push(Note {
+            id: "file-world-readable",
+            priority: if key_file {
+                AuditPriority::Critical
+            } else if sensitive {
+                AuditPriority::High
+            } else {
+                AuditPriority::Low
+            },
+            text: format!(
+                "permisos {:04o}: legible por cualquier usuario del sistema (chmod 600)",
+                mode
+            ),
+        });
+    } else if mode & 0o040 != 0 && key_file {
+        notes.push(Note {
+            id: "file-group-readable",
+            priority: AuditPriority::High,
+            text: format!("permisos {:04o}: clave privada legible por el grupo (chmod 600)", mode),
+        });
+    }
+    if mode & 0o002 != 0 {
+        notes.push(Note {
+            id: "file-world-writable",
+            priority: AuditPriority::Critical,
+            text: format!("permisos {:04o}: modificable por cualquier usuario del sistema", mode),
+        });
+    } else if mode & 0o020 != 0 {
+        notes.push(Note {
+            id: "file-group-writable",
+            priority: AuditPriority::High,
+            text: format!("permisos {:04o}: modificable por el grupo", mode),
+        });
+    }
+    notes
+}
+
+fn rwx(perm: u16) -> String {
+    [(0o4, 'r'), (0o2, 'w'), (0o1, 'x')]
+        .iter()
+        .map(|&(bit, c)| if perm & bit != 0 { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finding::{BaselineStatus, Confidence, Secret};
+    use std::env;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process;
+
+    fn finding(path: &Path, kind: SecretKind) -> Finding {
+        Finding {
+            rule_id: "high-entropy-string".to_string(),
+            kind,
+            priority: AuditPriority::Low,
+            confidence: Confidence::Low,
+            location: Location::File {
+                path: path.to_path_buf(),
+                line: 1,
+                column: 1,
+                key: None,
+            },
+            secret: Secret::new("c2VjcmV0b19kZV9wcnVlYmE"),
+            status: BaselineStatus::New,
+            compromised: None,
+            notes: Vec::new(),
+        }
+    }
+
+    fn ids(finding: &Finding) -> Vec<&'static str> {
+        finding.notes.iter().map(|n| n.id).collect()
+    }
+
+    #[test]
+    fn only_key_files_and_stores_are_raised() {
+        let dir = env::temp_dir().join(format!("auditorlocal-permisos-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let plain = dir.join("config.txt");
+        let key = dir.join("servidor.key");
+        for path in [&plain, &key] {
+            fs::write(path, "dato\n").unwrap();
+            fs::set_permissions(path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        let owners = Owners {
+            users: HashMap::new(),
+            groups: HashMap::new(),
+        };
+        let mut findings = vec![
+            finding(&plain, SecretKind::HighEntropy),
+            finding(&key, SecretKind::HighEntropy),
+            finding(&plain, SecretKind::CredentialStore),
+        ];
+        annotate(&mut findings, &owners);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(findings[0].priority, AuditPriority::Low);
+        assert_eq!(ids(&findings[0]), ["file-access", "file-world-readable"]);
+        assert_eq!(findings[1].priority, AuditPriority::Critical);
+        // Los almacenes ya evalúan su modo: solo se añade la nota de dueño y permisos.
+        assert_eq!(findings[2].priority, AuditPriority::Low);
+        assert_eq!(ids(&findings[2]), ["file-access"]);
+    }
+
+    #[test]
+    fn staged_mode_comes_from_the_index() {
+        let mut findings = vec![
+            finding(Path::new("./no-existe/app.env"), SecretKind::HighEntropy),
+            finding(Path::new("./no-existe/id_ed25519"), SecretKind::HighEntropy),
+        ];
+        annotate_staged(&mut findings, 0o100644);
+        assert_eq!(findings[0].priority, AuditPriority::Low);
+        assert_eq!(findings[0].notes[0].text, "modo en el índice git 0644");
+        assert_eq!(findings[1].priority, AuditPriority::Critical);
+        assert_eq!(ids(&findings[1]), ["file-access", "file-world-readable"]);
+    }
+}
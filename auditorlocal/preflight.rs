@@ -0,0 +1,420 @@
+// Papiweb desarrollos informaticos
+// Chequeo previo de alcance: conexiones TCP con timeout a los puertos declarados de cada destino antes de lanzar
+// la auditoría pesada. Solo usa std para poder incluirse con #[path] desde papitest-2.rs.
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Tope de conexiones por ejecución: un alcance con rangos enormes sería un escaneo, no un chequeo.
+const MAX_PROBES: usize = 4096;
+
+// Un destino por línea: "app.example.com open=80,443 closed=22,3306,8000-8100".
+pub struct Target {
+    pub host: String,
+    pub open: Vec<u16>,
+    pub closed: Vec<u16>,
+}
+
+pub struct Scope {
+    pub targets: Vec<Target>,
+}
+
+impl Scope {
+    pub fn load(path: &Path) -> Result<Scope, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Scope::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Scope, String> {
+        let mut targets = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let host = fields.next().unwrap_or_default().to_string();
+            let mut target = Target {
+                host,
+                open: Vec::new(),
+                closed: Vec::new(),
+            };
+            for field in fields {
+                let (name, ports) = field
+                    .split_once('=')
+                    .ok_or_else(|| format!("línea {}: se esperaba open=PUERTOS o closed=PUERTOS", index + 1))?;
+                let ports = parse_ports(ports).map_err(|e| format!("línea {}: {}", index + 1, e))?;
+                match name {
+                    "open" => target.open.extend(ports),
+                    "closed" => target.closed.extend(ports),
+                    other => return Err(format!("línea {}: campo desconocido {}", index + 1, other)),
+                }
+            }
+            if target.open.is_empty() && target.closed.is_empty() {
+                return Err(format!("línea {}: {} no declara puertos", index + 1, target.host));
+            }
+            if let Some(port) = target.open.iter().find(|p| target.closed.contains(p)) {
+                return Err(format!(
+                    "línea {}: el puerto {} figura como abierto y como cerrado",
+                    index + 1,
+                    port
+                ));
+            }
+            targets.push(target);
+        }
+        if targets.is_empty() {
+            return Err("el alcance no declara ningún destino".to_string());
+        }
+        Ok(Scope { targets })
+    }
+}
+
+// "22,80,8000-8100"
+fn parse_ports(list: &str) -> Result<Vec<u16>, String> {
+    let mut ports = Vec::new();
+    for item in list.split(',').filter(|i| !i.is_empty()) {
+        let port = |v: &str| {
+            v.parse::<u16>()
+                .ok()
+                .filter(|&p| p > 0)
+                .ok_or(format!("puerto inválido: {}", v))
+        };
+        match item.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (port(start)?, port(end)?);
+                if start > end {
+                    return Err(format!("rango inválido: {}", item));
+                }
+                ports.extend(start..=end);
+            }
+            None => ports.push(port(item)?),
+        }
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    Ok(ports)
+}
+
+// Cortesía con el destino: pocas conexiones a la vez, timeout corto y una pausa entre intentos de cada hilo.
+pub struct Limits {
+    pub timeout: Duration,
+    pub concurrency: usize,
+    pub pacing: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            timeout: Duration::from_secs(2),
+            concurrency: 8,
+            pacing: Duration::from_millis(50),
+        }
+    }
+}
+
+// Ordenado de mejor a peor para quedarse con el mejor estado entre varias direcciones.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PortState {
+    Open,
+    // El destino respondió con RST: está vivo pero no escucha.
+    Closed,
+    // Sin respuesta dentro del timeout o inalcanzable (firewall que descarta, ruta inexistente).
+    Filtered,
+}
+
+impl PortState {
+    fn as_str(self) -> &'static str {
+        match self {
+            PortState::Open => "abierto",
+            PortState::Closed => "cerrado",
+            PortState::Filtered => "sin respuesta",
+        }
+    }
+}
+
+pub struct Probe {
+    // Posición de la línea del alcance: un mismo host puede declararse en varias con expectativas distintas.
+    pub target: usize,
+    pub host: String,
+    pub addr: SocketAddr,
+    pub expect_open: bool,
+    pub state: PortState,
+    pub elapsed: Duration,
+}
+
+pub enum Problem {
+    Unresolved { host: String, error: String },
+    // Ningún puerto que debía estar abierto respondió: el destino no es alcanzable desde aquí.
+    Unreachable { host: String },
+    Down { host: String, port: u16, state: PortState },
+    Exposed { host: String, addr: SocketAddr },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Unresolved { host, error } => write!(f, "{}: no se pudo resolver ({})", host, error),
+            Problem::Unreachable { host } => {
+                write!(
+                    f,
+                    "{}: ningún puerto esperado abierto respondió, destino inalcanzable",
+                    host
+                )
+            }
+            Problem::Down { host, port, state } => {
+                write!(f, "{}:{} debía estar abierto y está {}", host, port, state.as_str())
+            }
+            Problem::Exposed { host, addr } => write!(f, "{} ({}) debía estar cerrado y acepta conexiones", host, addr),
+        }
+    }
+}
+
+pub struct Report {
+    pub probes: Vec<Probe>,
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn print(&self) {
+        for probe in &self.probes {
+            let ok = (probe.state == PortState::Open) == probe.expect_open;
+            println!(
+                "   {} {:<40} {:<14} (esperado {}) {:.0?}",
+                if ok { "✅" } else { "❌" },
+                format!("{} {}", probe.host, probe.addr),
+                probe.state.as_str(),
+                if probe.expect_open { "abierto" } else { "cerrado" },
+                probe.elapsed
+            );
+        }
+        if self.passed() {
+            println!("\n✅ Alcance verificado: destinos alcanzables y solo los puertos declarados abiertos.");
+        } else {
+            println!(
+                "\n⛔ El alcance no coincide con lo declarado ({} problema(s)):",
+                self.problems.len()
+            );
+            for problem in &self.problems {
+                println!("   - {}", problem);
+            }
+        }
+    }
+}
+
+struct Job<'a> {
+    index: usize,
+    target: &'a Target,
+    addr: SocketAddr,
+    expect_open: bool,
+}
+
+pub fn run(scope: &Scope, limits: &Limits) -> Result<Report, String> {
+    let mut problems = Vec::new();
+    let mut jobs = Vec::new();
+    for (index, target) in scope.targets.iter().enumerate() {
+        // Todas las direcciones del nombre (IPv4 e IPv6): un puerto de más en cualquiera de ellas cuenta.
+        let addrs: Vec<SocketAddr> = match (target.host.as_str(), 0).to_socket_addrs() {
+            Ok(addrs) => addrs.collect(),
+            Err(err) => {
+                problems.push(Problem::Unresolved {
+                    host: target.host.clone(),
+                    error: err.to_string(),
+                });
+                continue;
+            }
+        };
+        let ports = target
+            .open
+            .iter()
+            .map(|&p| (p, true))
+            .chain(target.closed.iter().map(|&p| (p, false)));
+        for (port, expect_open) in ports {
+            for addr in &addrs {
+                let mut addr = *addr;
+                addr.set_port(port);
+                jobs.push(Job {
+                    index,
+                    target,
+                    addr,
+                    expect_open,
+                });
+            }
+        }
+    }
+    if jobs.len() > MAX_PROBES {
+        return Err(format!(
+            "el alcance pide {} conexiones y el máximo es {}: acotar los rangos de puertos",
+            jobs.len(),
+            MAX_PROBES
+        ));
+    }
+
+    let states = probe_all(&jobs, limits);
+    let probes: Vec<Probe> = jobs
+        .iter()
+        .zip(states)
+        .map(|(job, (state, elapsed))| Probe {
+            target: job.index,
+            host: job.target.host.clone(),
+            addr: job.addr,
+            expect_open: job.expect_open,
+            state,
+            elapsed,
+        })
+        .collect();
+    evaluate(&scope.targets, &probes, &mut problems);
+    Ok(Report { probes, problems })
+}
+
+// Compara cada destino con lo declarado. La alcanzabilidad se decide solo con los puertos que debían estar abiertos:
+// un firewall que descarta (sin respuesta) es justo lo esperado en los que debían estar cerrados.
+fn evaluate(targets: &[Target], probes: &[Probe], problems: &mut Vec<Problem>) {
+    for (index, target) in targets.iter().enumerate() {
+        let own: Vec<&Probe> = probes.iter().filter(|p| p.target == index).collect();
+        let mut expected_open = own.iter().filter(|p| p.expect_open).peekable();
+        if expected_open.peek().is_some() && expected_open.all(|p| p.state == PortState::Filtered) {
+            problems.push(Problem::Unreachable {
+                host: target.host.clone(),
+            });
+            continue;
+        }
+        // Abierto en al menos una dirección basta.
+        for &port in &target.open {
+            let states = own.iter().filter(|p| p.addr.port() == port).map(|p| p.state);
+            let best = states.min().unwrap_or(PortState::Filtered);
+            if best != PortState::Open {
+                problems.push(Problem::Down {
+                    host: target.host.clone(),
+                    port,
+                    state: best,
+                });
+            }
+        }
+        for probe in own.iter().filter(|p| !p.expect_open && p.state == PortState::Open) {
+            problems.push(Problem::Exposed {
+                host: target.host.clone(),
+                addr: probe.addr,
+            });
+        }
+    }
+}
+
+// Reparto dinámico entre `concurrency` hilos; cada resultado vuelve a la posición de su trabajo.
+fn probe_all(jobs: &[Job], limits: &Limits) -> Vec<(PortState, Duration)> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![(PortState::Filtered, Duration::ZERO); jobs.len()]);
+    thread::scope(|scope| {
+        for _ in 0..limits.concurrency.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+                let result = connect(job.addr, limits.timeout);
+                results.lock().unwrap_or_else(|e| e.into_inner())[index] = result;
+                thread::sleep(limits.pacing);
+            });
+        }
+    });
+    results.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+// Solo el saludo TCP: la conexión se cierra al soltarla, sin enviar datos.
+fn connect(addr: SocketAddr, timeout: Duration) -> (PortState, Duration) {
+    let start = Instant::now();
+    let state = match TcpStream::connect_timeout(&addr, timeout) {
+        Ok(_) => PortState::Open,
+        Err(err) if err.kind() == ErrorKind::ConnectionRefused => PortState::Closed,
+        Err(_) => PortState::Filtered,
+    };
+    (state, start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(host: &str, port: u16, expect_open: bool, state: PortState) -> Probe {
+        Probe {
+            target: 0,
+            host: host.to_string(),
+            addr: SocketAddr::from(([192, 0, 2, 10], port)),
+            expect_open,
+            state,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn problems(scope: &str, probes: &[Probe]) -> Vec<String> {
+        let scope = Scope::parse(scope).unwrap();
+        let mut problems = Vec::new();
+        evaluate(&scope.targets, probes, &mut problems);
+        problems.iter().map(Problem::to_string).collect()
+    }
+
+    #[test]
+    fn closed_only_targets_behind_a_firewall_are_compliant() {
+        let probes = [
+            probe("db", 22, false, PortState::Filtered),
+            probe("db", 3306, false, PortState::Filtered),
+        ];
+        assert!(problems("db closed=22,3306", &probes).is_empty());
+        let probes = [
+            probe("db", 22, false, PortState::Closed),
+            probe("db", 3306, false, PortState::Open),
+        ];
+        assert_eq!(
+            problems("db closed=22,3306", &probes),
+            ["db (192.0.2.10:3306) debía estar cerrado y acepta conexiones"]
+        );
+    }
+
+    #[test]
+    fn reachability_depends_only_on_ports_expected_open() {
+        let probes = [
+            probe("web", 443, true, PortState::Filtered),
+            probe("web", 22, false, PortState::Closed),
+        ];
+        assert_eq!(
+            problems("web open=443 closed=22", &probes),
+            ["web: ningún puerto esperado abierto respondió, destino inalcanzable"]
+        );
+        let probes = [
+            probe("web", 80, true, PortState::Open),
+            probe("web", 443, true, PortState::Filtered),
+            probe("web", 22, false, PortState::Filtered),
+        ];
+        assert_eq!(
+            problems("web open=80,443 closed=22", &probes),
+            ["web:443 debía estar abierto y está sin respuesta"]
+        );
+    }
+
+    #[test]
+    fn repeated_hosts_are_judged_per_scope_line() {
+        let probes = [
+            Probe {
+                target: 0,
+                ..probe("web", 80, true, PortState::Open)
+            },
+            Probe {
+                target: 1,
+                ..probe("web", 8080, true, PortState::Filtered)
+            },
+        ];
+        assert_eq!(
+            problems("web open=80\nweb open=8080", &probes),
+            ["web: ningún puerto esperado abierto respondió, destino inalcanzable"]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+// Papiweb desarrollos informaticos
+// Vista enmascarada (opcional) y clasificación del valor de un hallazgo para facilitar el triage.
+// Solo se muestran los 2 primeros y 2 últimos caracteres, y nada si el valor es demasiado corto.
+use std::fmt;
+
+use crate::detectors::shannon_entropy;
+use crate::finding::SecretKind;
+
+// Por debajo de esta longitud mostrar 4 caracteres revelaría demasiado del secreto.
+const MIN_PREVIEW_LEN: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Numeric,
+    Hex,
+    Alphanumeric,
+    Base64,
+    Base64Url,
+    Printable,
+    Other,
+}
+
+impl Charset {
+    pub fn of(value: &str) -> Self {
+        let all = |f: fn(u8) -> bool| value.bytes().all(f);
+        if all(|b| b.is_ascii_digit()) {
+            Charset::Numeric
+        } else if all(|b| b.is_ascii_hexdigit()) {
+            Charset::Hex
+        } else if all(|b| b.is_ascii_alphanumeric()) {
+            Charset::Alphanumeric
+        } else if all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=')) {
+            Charset::Base64
+        } else if all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'=')) {
+            Charset::Base64Url
+        } else if all(|b| b.is_ascii_graphic() || b == b' ') {
+            Charset::Printable
+        } else {
+            Charset::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Charset::Numeric => "numérico",
+            Charset::Hex => "hexadecimal",
+            Charset::Alphanumeric => "alfanumérico",
+            Charset::Base64 => "base64",
+            Charset::Base64Url => "base64url",
+            Charset::Printable => "ascii imprimible",
+            Charset::Other => "unicode/binario",
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Charset::Numeric => "numeric",
+            Charset::Hex => "hex",
+            Charset::Alphanumeric => "alphanumeric",
+            Charset::Base64 => "base64",
+            Charset::Base64Url => "base64url",
+            Charset::Printable => "printable",
+            Charset::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub masked: String,
+    pub length: usize,
+    pub charset: Charset,
+    pub entropy: f64,
+}
+
+impl Preview {
+    pub fn of(value: &str) -> Self {
+        let chars: Vec<char> = value.chars().collect();
+        let masked = if chars.len() >= MIN_PREVIEW_LEN {
+            let head: String = chars[..2].iter().collect();
+            let tail: String = chars[chars.len() - 2..].iter().collect();
+            format!("{}…{}", head, tail)
+        } else {
+            "…".to_string()
+        };
+        Preview {
+            masked,
+            length: chars.len(),
+            charset: Charset::of(value),
+            entropy: shannon_entropy(value),
+        }
+    }
+}
+
+impl fmt::Display for Preview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} caracteres, {}, entropía {:.2})",
+            self.masked,
+            self.length,
+            self.charset.as_str(),
+            self.entropy
+        )
+    }
+}
+
+// Tipo probable del valor. Los formatos conocidos ya vienen del detector; para los hallazgos
+// por nombre sensible se infiere a partir del juego de caracteres y la entropía.
+pub fn classify(kind: SecretKind, value: &str) -> &'static str {
+    match kind {
+        SecretKind::AwsAccessKey => "clave AWS",
+        SecretKind::Jwt => "JWT",
+        SecretKind::UrlCredentials => "contraseña en URL",
+        SecretKind::PrivateKey => "clave privada",
+        SecretKind::GithubToken | SecretKind::SlackToken | SecretKind::StripeKey => "token de API",
+        SecretKind::HighEntropy => "alta entropía genérica",
+        SecretKind::CredentialStore => "archivo de credenciales de herramienta",
+        SecretKind::Misconfiguration => "ajuste de configuración",
+        SecretKind::SensitiveName | SecretKind::CommandLine | SecretKind::Custom => {
+            let charset = Charset::of(value);
+            let entropy = shannon_entropy(value);
+            if charset == Charset::Hex && value.len() >= 32 {
+                "hexadecimal (hash o clave)"
+            } else if entropy >= 3.5 && value.len() >= 16 {
+                "alta entropía genérica"
+            } else {
+                "contraseña o valor legible"
+            }
+        }
+    }
+}
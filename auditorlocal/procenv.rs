@@ -0,0 +1,215 @@
+// Papiweb desarrollos informaticos
+// Modo host: entorno de todos los procesos visibles vía /proc/<pid>/environ.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::detectors::Detector;
+use crate::finding::{Finding, Location};
+use crate::system;
+
+// /proc expresa los tiempos en USER_HZ, fijo en 100 para el espacio de usuario en Linux.
+const USER_HZ: u64 = 100;
+
+#[derive(Debug, Default)]
+pub struct HostStats {
+    pub processes_scanned: u64,
+    pub processes_denied: u64,
+}
+
+pub struct ProcessInfo {
+    pub exe: String,
+    pub user: String,
+    pub unit: Option<String>,
+    pub uptime_secs: u64,
+}
+
+pub fn scan_processes(detector: &Detector, stats: &mut HostStats) -> Result<Vec<Finding>, String> {
+    let entries = fs::read_dir("/proc").map_err(|e| format!("/proc: {}", e))?;
+    let users = system::user_names();
+    let boot_uptime = boot_uptime();
+    let own_pid = std::process::id();
+
+    let mut pids: Vec<u32> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| e.file_name().to_str()?.parse().ok())
+        .filter(|&pid| pid != own_pid)
+        .collect();
+    pids.sort_unstable();
+
+    let mut findings = Vec::new();
+    for pid in pids {
+        let dir = Path::new("/proc").join(pid.to_string());
+        let environ = match fs::read(dir.join("environ")) {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                stats.processes_denied += 1;
+                continue;
+            }
+        };
+        // Los hilos del kernel no tienen entorno.
+        if environ.is_empty() {
+            continue;
+        }
+        stats.processes_scanned += 1;
+        let info = process_info(&dir, &users, boot_uptime);
+        findings.extend(environ_findings(detector, pid, &info, &environ));
+    }
+    Ok(findings)
+}
+
+// Contenido de /proc/<pid>/environ: "NOMBRE=valor" separados por NUL.
+fn environ_findings(detector: &Detector, pid: u32, info: &ProcessInfo, environ: &[u8]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for entry in environ.split(|&b| b == 0).filter(|e| !e.is_empty()) {
+        let entry = String::from_utf8_lossy(entry);
+        let Some((name, value)) = entry.split_once('=') else {
+            continue;
+        };
+        for detection in detector.scan_pair(name, value) {
+            let location = Location::Process {
+                pid,
+                exe: info.exe.clone(),
+                user: info.user.clone(),
+                unit: info.unit.clone(),
+                uptime_secs: info.uptime_secs,
+                var: name.to_string(),
+            };
+            findings.push(detection.into_finding(value, location));
+        }
+    }
+    findings
+}
+
+// Segundos desde el arranque, para calcular la antigüedad de cada proceso.
+pub fn boot_uptime() -> u64 {
+    fs::read_to_string("/proc/uptime")
+        .ok()
+        .and_then(|t| t.split_whitespace().next()?.parse::<f64>().ok())
+        .unwrap_or(0.0) as u64
+}
+
+pub fn process_info(dir: &Path, users: &HashMap<u32, String>, boot_uptime: u64) -> ProcessInfo {
+    let exe = fs::read_link(dir.join("exe"))
+        .map(|p| p.display().to_string())
+        .or_else(|_| fs::read_to_string(dir.join("comm")).map(|c| format!("[{}]", c.trim())))
+        .unwrap_or_else(|_| "?".to_string());
+
+    let uid = fs::read_to_string(dir.join("status")).ok().and_then(|status| {
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix("Uid:"))
+            .and_then(|ids| ids.split_whitespace().next()?.parse::<u32>().ok())
+    });
+    let user = match uid {
+        Some(uid) => users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+        None => "?".to_string(),
+    };
+
+    // "0::/system.slice/nginx.service" -> nginx.service
+    let unit = fs::read_to_string(dir.join("cgroup")).ok().and_then(|cgroup| {
+        cgroup.lines().find_map(|line| {
+            let path = line.rsplit(':').next()?;
+            path.split('/')
+                .rev()
+                .find(|part| part.ends_with(".service") || part.ends_with(".scope"))
+                .map(str::to_string)
+        })
+    });
+
+    // Campo 22 de /proc/<pid>/stat: inicio en ticks desde el arranque. Se parsea tras el ')' del nombre.
+    let started = fs::read_to_string(dir.join("stat")).ok().and_then(|stat| {
+        let after = stat.get(stat.rfind(')')? + 2..)?;
+        after.split_whitespace().nth(19)?.parse::<u64>().ok()
+    });
+    let uptime_secs = started
+        .map(|ticks| boot_uptime.saturating_sub(ticks / USER_HZ))
+        .unwrap_or(0);
+
+    ProcessInfo {
+        exe,
+        user,
+        unit,
+        uptime_secs,
+    }
+}
+
+// Duración compacta para reportes: "3d 4h", "2h 10m", "45s".
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names::NameMatcher;
+    use crate::rules::RuleSet;
+    use std::{env, process};
+
+    #[test]
+    fn environ_entries_are_name_value_pairs() {
+        let rules = RuleSet::builtin();
+        let detector = Detector::new(&rules, NameMatcher::new(&rules.keywords, &rules.benign));
+        let info = ProcessInfo {
+            exe: "/usr/bin/app".to_string(),
+            user: "app".to_string(),
+            unit: Some("app.service".to_string()),
+            uptime_secs: 60,
+        };
+        let environ = b"PATH=/usr/bin\0DB_PASSWORD=S3cr3tPass\0SIN_IGUAL\0\0EMPTY_TOKEN=\0API_TOKEN=a=b=c=d=e=f\0";
+        let findings = environ_findings(&detector, 42, &info, environ);
+        let vars: Vec<(&str, &str)> = findings
+            .iter()
+            .map(|finding| match &finding.location {
+                Location::Process { var, pid: 42, .. } => (var.as_str(), finding.secret.expose()),
+                other => panic!("ubicación inesperada: {}", other),
+            })
+            .collect();
+        // Solo el primer '=' separa: el resto es parte del valor.
+        assert_eq!(vars, [("DB_PASSWORD", "S3cr3tPass"), ("API_TOKEN", "a=b=c=d=e=f")]);
+    }
+
+    #[test]
+    fn process_info_from_proc_files() {
+        let dir = env::temp_dir().join(format!("auditorlocal-procenv-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // El nombre del proceso puede contener ')' y espacios: los campos se cuentan desde el último.
+        let fields: Vec<String> = (4..=21).map(|n| n.to_string()).collect();
+        let stat = format!("1234 (mi ) proceso) S {} 50000 0 0\n", fields.join(" "));
+        fs::write(dir.join("stat"), stat).unwrap();
+        fs::write(dir.join("comm"), "mi ) proceso\n").unwrap();
+        fs::write(dir.join("status"), "Name:\tx\nUid:\t33\t33\t33\t33\nGid:\t33\n").unwrap();
+        fs::write(dir.join("cgroup"), "0::/system.slice/nginx.service\n").unwrap();
+        let users = HashMap::from([(33, "www-data".to_string())]);
+        let info = process_info(&dir, &users, 1_000);
+        assert_eq!(info.exe, "[mi ) proceso]");
+        assert_eq!(info.user, "www-data");
+        assert_eq!(info.unit.as_deref(), Some("nginx.service"));
+        assert_eq!(info.uptime_secs, 500);
+
+        // Archivos truncados o raros: valores por defecto, sin pánico.
+        fs::write(dir.join("stat"), "1234 (x)").unwrap();
+        fs::write(dir.join("status"), "Uid:\n").unwrap();
+        fs::write(dir.join("cgroup"), "").unwrap();
+        let info = process_info(&dir, &users, 1_000);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!((info.user.as_str(), info.unit, info.uptime_secs), ("?", None, 0));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(130), "2m");
+        assert_eq!(format_duration(7_800), "2h 10m");
+        assert_eq!(format_duration(273_600), "3d 4h");
+    }
+}
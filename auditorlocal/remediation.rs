@@ -0,0 +1,359 @@
+// Papiweb desarrollos informaticos
+// Plan de remediación por hallazgo: qué credencial es, cómo rotarla y con qué reemplazarla según dónde apareció.
+// Para .env y docker-compose además se genera un parche (diff unificado) que cambia cada valor por ${NOMBRE}.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::finding::{BaselineStatus, Finding, Location, SecretKind};
+use crate::formats::Format;
+use crate::hardening;
+use crate::manifest;
+use crate::preview;
+use crate::rules::RuleSet;
+
+const CONTEXT_LINES: usize = 3;
+const UNIT_EXTENSIONS: &[&str] = &["service", "socket", "timer", "mount", "path", "target"];
+const HISTORY_NAMES: &[&str] = &[
+    ".bash_history",
+    ".zsh_history",
+    ".histfile",
+    ".sh_history",
+    ".ash_history",
+    "fish_history",
+];
+
+pub struct Plan {
+    pub credential: &'static str,
+    // None en las configuraciones inseguras: no hay credencial que rotar, solo el ajuste que corregir.
+    pub rotation: Option<String>,
+    pub replacement: String,
+    // Nota del hallazgo que ya es el consejo de reemplazo (manifiestos, configuración del host): no se imprime dos veces.
+    pub advice_note: Option<&'static str>,
+}
+
+pub fn plan(finding: &Finding, rule_set: &RuleSet) -> Plan {
+    let rotation = (finding.kind != SecretKind::Misconfiguration).then(|| {
+        rule_set
+            .remediation(&finding.rule_id)
+            .unwrap_or_else(|| default_rotation(finding.kind))
+            .to_string()
+    });
+    let advice = finding
+        .notes
+        .iter()
+        .find(|n| manifest::is_advice(n) || n.id == hardening::FIX_NOTE);
+    let (replacement, advice_note) = match advice {
+        Some(note) => (note.text.clone(), Some(note.id)),
+        None => (replacement(finding), None),
+    };
+    Plan {
+        credential: preview::classify(finding.kind, finding.secret.expose()),
+        rotation,
+        replacement,
+        advice_note,
+    }
+}
+
+fn default_rotation(kind: SecretKind) -> &'static str {
+    match kind {
+        SecretKind::PrivateKey => {
+            "generar un par de claves nuevo y retirar la clave pública anterior de authorized_keys y de los servicios"
+        }
+        SecretKind::CredentialStore => {
+            "revocar en cada servicio los tokens y contraseñas guardados en el archivo y emitir credenciales nuevas"
+        }
+        _ => "cambiar el secreto en el servicio que lo emitió e invalidar el valor expuesto",
+    }
+}
+
+fn replacement(finding: &Finding) -> String {
+    match &finding.location {
+        Location::Env { var } => format!(
+            "no exportar {} desde perfiles de shell: cargarlo al usarlo desde un gestor de secretos o un archivo 0600",
+            var
+        ),
+        Location::Process { unit: Some(unit), var, .. } => format!(
+            "en {} cambiar Environment={}= por LoadCredential={}:/etc/credstore/{} (archivo 0600 de root) y leerlo de $CREDENTIALS_DIRECTORY",
+            unit,
+            var,
+            var.to_lowercase(),
+            var.to_lowercase()
+        ),
+        Location::Process { var, .. } => format!(
+            "pasar {} en un archivo 0600 que el proceso lea al arrancar, no en el entorno que heredan sus hijos",
+            var
+        ),
+        Location::Commit { .. } => "quitarlo del código (variable de entorno o gestor de secretos) y, ya rotado, purgar el historial con git filter-repo si el repositorio se compartió".to_string(),
+        Location::Image { .. } => "no copiarlo en la imagen: secretos de build (RUN --mount=type=secret) o inyección en runtime; reconstruir y retirar las etiquetas publicadas".to_string(),
+        Location::Socket { .. } => "escuchar solo en 127.0.0.1 o limitar el acceso por firewall".to_string(),
+        Location::File { path, key, .. } => file_replacement(finding.kind, path, key.as_deref()),
+    }
+}
+
+fn file_replacement(kind: SecretKind, path: &Path, key: Option<&str>) -> String {
+    if kind == SecretKind::CredentialStore {
+        return "dejar el archivo con permisos 0600 y preferir el helper de credenciales de la herramienta (git credential, docker-credential-*, aws sso) a las claves fijas".to_string();
+    }
+    if kind == SecretKind::PrivateKey {
+        return "mantener la clave solo en el host que la usa, 0600 y con contraseña (ssh-keygen -p) o en un agente/HSM; fuera de repositorios y copias compartidas".to_string();
+    }
+    let name = key.and_then(variable_name);
+    match (FileKind::of(path), name) {
+        (FileKind::Unit, _) => "LoadCredential=NOMBRE:/etc/credstore/NOMBRE (archivo 0600 de root) en lugar de Environment= o del valor en ExecStart=; el servicio lo lee de $CREDENTIALS_DIRECTORY".to_string(),
+        (FileKind::History, _) => "borrar la entrada (history -d N o editando el archivo) y pasar la contraseña por un archivo de opciones 0600 (~/.my.cnf, ~/.pgpass, ~/.netrc)".to_string(),
+        (FileKind::Dotenv, Some(name)) => format!(
+            "{}=${{{}}} (--patch genera el cambio) con el valor real en el entorno de despliegue o en un .env 0600 fuera del repositorio",
+            name, name
+        ),
+        (FileKind::Compose, Some(name)) => format!(
+            "${{{}}} en lugar del valor de {} (--patch genera el cambio), interpolado por compose desde un .env 0600 fuera del repositorio o desde el entorno de despliegue",
+            name, name
+        ),
+        _ => "leerlo de una variable de entorno, un gestor de secretos o un archivo 0600 fuera del repositorio".to_string(),
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum FileKind {
+    Dotenv,
+    Compose,
+    Unit,
+    History,
+    Other,
+}
+
+impl FileKind {
+    fn of(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match Format::detect(path) {
+            Format::Dotenv => FileKind::Dotenv,
+            Format::Yaml if name.starts_with("docker-compose") || name.starts_with("compose.") => FileKind::Compose,
+            _ if UNIT_EXTENSIONS.contains(&ext.as_str()) => FileKind::Unit,
+            _ if HISTORY_NAMES.contains(&name.as_str()) => FileKind::History,
+            _ => FileKind::Other,
+        }
+    }
+}
+
+// Nombre de variable al final de la clave ("servicio web environment DB_PASSWORD", "services.web.environment.TOKEN").
+fn variable_name(key: &str) -> Option<&str> {
+    let name = key.rsplit([' ', '.']).next()?;
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(name)
+}
+
+// "export NOMBRE=valor", "- NOMBRE=valor" o "NOMBRE: valor": se conserva hasta el separador y el valor pasa a ${NOMBRE}.
+fn reference_line(line: &str, name: &str) -> Option<String> {
+    line.match_indices(name).find_map(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        if before.is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+        let after = &line[start + name.len()..];
+        let rest = after.trim_start();
+        let separator = rest.chars().next().filter(|c| *c == '=' || *c == ':')?;
+        let end = line.len() - rest.len() + 1;
+        let spacing = if separator == ':' { " " } else { "" };
+        Some(format!("{}{}${{{}}}", &line[..end], spacing, name))
+    })
+}
+
+// Reemplazos por archivo (ruta relativa a la raíz del parche): contenido original y línea nueva por número de línea.
+#[derive(Default)]
+pub struct Patch {
+    files: BTreeMap<PathBuf, (String, BTreeMap<usize, String>)>,
+}
+
+impl Patch {
+    // `root` es desde donde se aplica el parche: los archivos fuera de ella no se pueden expresar y se omiten.
+    pub fn build(findings: &[Finding], root: &Path) -> Patch {
+        // Analizar un solo archivo deja el parche relativo a su directorio.
+        let root = if root.is_file() {
+            root.parent().unwrap_or(Path::new(""))
+        } else {
+            root
+        };
+        let mut patch = Patch::default();
+        for finding in findings {
+            let Location::File {
+                path,
+                line,
+                key: Some(key),
+                ..
+            } = &finding.location
+            else {
+                continue;
+            };
+            if finding.status == BaselineStatus::Accepted
+                || matches!(finding.kind, SecretKind::CredentialStore | SecretKind::Misconfiguration)
+                || !matches!(FileKind::of(path), FileKind::Dotenv | FileKind::Compose)
+            {
+                continue;
+            }
+            let Some(name) = variable_name(key) else {
+                continue;
+            };
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            if !patch.files.contains_key(relative) {
+                let Ok(text) = fs::read_to_string(path) else {
+                    continue;
+                };
+                patch.files.insert(relative.to_path_buf(), (text, BTreeMap::new()));
+            }
+            let (text, changes) = patch.files.get_mut(relative).expect("archivo recién cargado");
+            // Solo si la línea sigue conteniendo el valor: el archivo pudo cambiar desde el análisis.
+            let Some(current) = text.lines().nth(line.saturating_sub(1)) else {
+                continue;
+            };
+            if changes.contains_key(line) || !current.contains(finding.secret.expose()) {
+                continue;
+            }
+            if let Some(new) = reference_line(current, name) {
+                changes.insert(*line, new);
+            }
+        }
+        patch.files.retain(|_, (_, changes)| !changes.is_empty());
+        patch
+    }
+
+    pub fn changes(&self) -> usize {
+        self.files.values().map(|(_, changes)| changes.len()).sum()
+    }
+
+    pub fn files(&self) -> usize {
+        self.files.len()
+    }
+
+    // Diff unificado aplicable con git apply o patch -p1 desde el directorio analizado.
+    pub fn diff(&self) -> String {
+        let mut out = String::new();
+        for (path, (text, changes)) in &self.files {
+            // Cada línea con su fin de línea original ("\r\n" incluido) para que el contexto coincida con el archivo.
+            let lines: Vec<&str> = text.split_inclusive('\n').collect();
+            let display = path.display();
+            let _ = writeln!(out, "--- a/{}\n+++ b/{}", display, display);
+            let numbers: Vec<usize> = changes.keys().copied().collect();
+            let mut index = 0;
+            while index < numbers.len() {
+                // Cambios cercanos comparten hunk para que los contextos no se solapen.
+                let mut last_change = index;
+                while last_change + 1 < numbers.len()
+                    && numbers[last_change + 1] - numbers[last_change] <= 2 * CONTEXT_LINES
+                {
+                    last_change += 1;
+                }
+                let first = numbers[index].saturating_sub(CONTEXT_LINES).max(1);
+                let last = (numbers[last_change] + CONTEXT_LINES).min(lines.len());
+                let count = last - first + 1;
+                let _ = writeln!(out, "@@ -{},{} +{},{} @@", first, count, first, count);
+                for number in first..=last {
+                    let line = lines[number - 1];
+                    match changes.get(&number) {
+                        Some(new) => {
+                            let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                            diff_line(&mut out, '-', line);
+                            diff_line(&mut out, '+', &format!("{}{}", new, ending));
+                        }
+                        None => diff_line(&mut out, ' ', line),
+                    }
+                }
+                index = last_change + 1;
+            }
+        }
+        out
+    }
+}
+
+// `line` trae su fin de línea; sin él es la última del archivo y no termina en salto.
+fn diff_line(out: &mut String, marker: char, line: &str) {
+    out.push(marker);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finding::{AuditPriority, Confidence, Secret};
+    use std::env;
+    use std::process;
+
+    fn finding(path: PathBuf, line: usize, key: &str, value: &str) -> Finding {
+        Finding {
+            rule_id: "sensitive-name".to_string(),
+            kind: SecretKind::SensitiveName,
+            priority: AuditPriority::High,
+            confidence: Confidence::High,
+            location: Location::File {
+                path,
+                line,
+                column: 1,
+                key: Some(key.to_string()),
+            },
+            secret: Secret::new(value),
+            status: BaselineStatus::New,
+            compromised: None,
+            notes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn diff_is_relative_to_the_root_and_keeps_line_endings() {
+        let root = env::temp_dir().join(format!("auditorlocal-parche-{}", process::id()));
+        fs::create_dir_all(root.join("app")).unwrap();
+        let dotenv = root.join("app/.env");
+        let compose = root.join("docker-compose.yml");
+        fs::write(&dotenv, "A=1\r\nDB_PASSWORD=clave_muy_secreta\r\nB=2\r\n").unwrap();
+        fs::write(
+            &compose,
+            "services:\n  web:\n    environment:\n      TOKEN: valor_secreto",
+        )
+        .unwrap();
+        let findings = [
+            finding(dotenv, 2, "DB_PASSWORD", "clave_muy_secreta"),
+            finding(compose, 4, "services.web.environment.TOKEN", "valor_secreto"),
+            finding(PathBuf::from("/otra/raiz/.env"), 1, "TOKEN", "valor_secreto"),
+        ];
+        let patch = Patch::build(&findings, &root);
+        let diff = patch.diff();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(patch.files(), 2);
+        assert_eq!(
+            diff,
+            "--- a/app/.env\n+++ b/app/.env\n@@ -1,3 +1,3 @@\n A=1\r\n-DB_PASSWORD=clave_muy_secreta\r\n\
+             +DB_PASSWORD=${DB_PASSWORD}\r\n B=2\r\n\
+             --- a/docker-compose.yml\n+++ b/docker-compose.yml\n@@ -1,4 +1,4 @@\n services:\n   web:\n     environment:\n\
+             -      TOKEN: valor_secreto\n\\ No newline at end of file\n\
+             +      TOKEN: ${TOKEN}\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn reference_keeps_the_separator() {
+        assert_eq!(
+            reference_line("export API_KEY=abc", "API_KEY").as_deref(),
+            Some("export API_KEY=${API_KEY}")
+        );
+        assert_eq!(
+            reference_line("  - TOKEN: abc", "TOKEN").as_deref(),
+            Some("  - TOKEN: ${TOKEN}")
+        );
+        assert_eq!(reference_line("MY_TOKEN=abc", "TOKEN"), None);
+    }
+}
@@ -0,0 +1,372 @@
+// Papiweb desarrollos informaticos
+// Packs de reglas del escáner: patrones de nombre y valor, umbrales de entropía, severidad y textos.
+// El pack incorporado (default-rules.toml) se compila en el binario; los packs del equipo se aplican
+// encima en orden y cualquier regla se puede desactivar por id.
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::finding::{AuditPriority, Confidence, Finding, SecretKind};
+use crate::json::Value;
+use crate::packfile;
+use crate::pattern::Pattern;
+
+const BUILTIN_PACK: &str = include_str!("default-rules.toml");
+
+// Reglas cuyo motor está en el código: el pack solo ajusta severidad, confianza y textos.
+pub const ENGINE_RULES: &[(&str, SecretKind)] = &[
+    ("sensitive-name", SecretKind::SensitiveName),
+    ("high-entropy-string", SecretKind::HighEntropy),
+    ("cmdline-password", SecretKind::CommandLine),
+];
+
+const PACK_KEYS: &[&str] = &["name", "disable", "names", "entropy", "rules"];
+const NAMES_KEYS: &[&str] = &["keywords", "benign"];
+const ENTROPY_KEYS: &[&str] = &["base64_threshold", "hex_threshold", "min_length"];
+const RULE_KEYS: &[&str] = &[
+    "id",
+    "kind",
+    "pattern",
+    "name_pattern",
+    "min_entropy",
+    "severity",
+    "confidence",
+    "description",
+    "remediation",
+];
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub id: String,
+    pub kind: SecretKind,
+    pub pattern: Option<Pattern>,
+    pub name_pattern: Option<Pattern>,
+    pub min_entropy: Option<f64>,
+    pub severity: AuditPriority,
+    pub confidence: Confidence,
+    pub description: String,
+    pub remediation: Option<String>,
+}
+
+impl Rule {
+    pub fn is_engine(&self) -> bool {
+        ENGINE_RULES.iter().any(|(id, _)| *id == self.id)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Entropy {
+    pub base64_threshold: f64,
+    pub hex_threshold: f64,
+    pub min_length: usize,
+}
+
+impl Default for Entropy {
+    fn default() -> Self {
+        Entropy {
+            base64_threshold: 4.0,
+            hex_threshold: 3.0,
+            min_length: 20,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RulePack {
+    pub name: String,
+    pub rules: Vec<Rule>,
+    pub keywords: Vec<String>,
+    pub benign: Vec<String>,
+    pub disable: Vec<String>,
+    base64_threshold: Option<f64>,
+    hex_threshold: Option<f64>,
+    min_length: Option<usize>,
+}
+
+// Resultado de aplicar un pack sobre el conjunto efectivo (para "rules check").
+#[derive(Debug, Default)]
+pub struct LayerSummary {
+    pub added: usize,
+    pub replaced: usize,
+    pub disabled: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+    pub keywords: Vec<String>,
+    pub benign: Vec<String>,
+    pub entropy: Entropy,
+    disabled: BTreeSet<String>,
+}
+
+impl RuleSet {
+    pub fn builtin() -> Self {
+        let mut set = RuleSet::default();
+        set.layer(builtin_pack());
+        set
+    }
+
+    pub fn layer(&mut self, pack: RulePack) -> LayerSummary {
+        let mut summary = LayerSummary {
+            disabled: pack.disable.len(),
+            ..LayerSummary::default()
+        };
+        for rule in pack.rules {
+            match self.rules.iter_mut().find(|r| r.id == rule.id) {
+                Some(existing) => {
+                    *existing = rule;
+                    summary.replaced += 1;
+                }
+                None => {
+                    self.rules.push(rule);
+                    summary.added += 1;
+                }
+            }
+        }
+        for keyword in pack.keywords {
+            push_unique(&mut self.keywords, keyword.to_uppercase());
+        }
+        for name in pack.benign {
+            push_unique(&mut self.benign, name.to_uppercase());
+        }
+        self.disabled.extend(pack.disable);
+        self.entropy.base64_threshold = pack.base64_threshold.unwrap_or(self.entropy.base64_threshold);
+        self.entropy.hex_threshold = pack.hex_threshold.unwrap_or(self.entropy.hex_threshold);
+        self.entropy.min_length = pack.min_length.unwrap_or(self.entropy.min_length);
+        summary
+    }
+
+    pub fn disable(&mut self, id: &str) {
+        self.disabled.insert(id.to_string());
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.contains(id)
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.iter().filter(|r| self.is_enabled(&r.id))
+    }
+
+    pub fn disabled_count(&self) -> usize {
+        self.disabled.len()
+    }
+
+    // Ids desactivados que no corresponden a ninguna regla del pack ni a las reglas externas conocidas.
+    pub fn unknown_disabled<'a>(&'a self, external: &[String]) -> Vec<&'a str> {
+        self.disabled
+            .iter()
+            .filter(|id| !self.rules.iter().any(|r| r.id == **id) && !external.contains(id))
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn rule(&self, id: &str) -> Option<&Rule> {
+        self.rules.iter().find(|r| r.id == id)
+    }
+
+    // Descripción de la regla que produjo el hallazgo, o la del tipo si la regla no está en el pack.
+    pub fn describe<'a>(&'a self, finding: &Finding) -> &'a str {
+        self.rule(&finding.rule_id)
+            .map_or(finding.kind.as_str(), |r| r.description.as_str())
+    }
+
+    pub fn remediation(&self, rule_id: &str) -> Option<&str> {
+        self.rule(rule_id).and_then(|r| r.remediation.as_deref())
+    }
+}
+
+fn push_unique(list: &mut Vec<String>, item: String) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+pub fn builtin_pack() -> RulePack {
+    packfile::parse_toml(BUILTIN_PACK)
+        .and_then(|value| RulePack::from_value(&value))
+        .expect("pack de reglas interno inválido")
+}
+
+// Pack de un archivo .toml, .yaml o .yml; los errores incluyen la ruta y, si aplica, la línea.
+pub fn load_pack(path: &Path) -> Result<RulePack, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let value = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => packfile::parse_toml(&text),
+        Some("yaml" | "yml") => packfile::parse_yaml(&text),
+        _ => Err("extensión no reconocida (se espera .toml, .yaml o .yml)".to_string()),
+    };
+    value
+        .and_then(|value| RulePack::from_value(&value))
+        .map(|mut pack| {
+            if pack.name.is_empty() {
+                pack.name = path.display().to_string();
+            }
+            pack
+        })
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+impl RulePack {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        check_keys(value, PACK_KEYS, "el pack")?;
+        let names = value.get("names");
+        if let Some(names) = names {
+            check_keys(names, NAMES_KEYS, "names")?;
+        }
+        let entropy = value.get("entropy");
+        if let Some(entropy) = entropy {
+            check_keys(entropy, ENTROPY_KEYS, "entropy")?;
+        }
+        let threshold = |key: &str| -> Result<Option<f64>, String> {
+            entropy
+                .and_then(|e| e.get(key))
+                .map(|v| number_in(v, 0.0, 8.0, &format!("entropy.{}", key)))
+                .transpose()
+        };
+        let min_length = entropy
+            .and_then(|e| e.get("min_length"))
+            .map(|v| number_in(v, 1.0, 4096.0, "entropy.min_length").map(|n| n as usize))
+            .transpose()?;
+
+        let mut rules: Vec<Rule> = Vec::new();
+        let items = match value.get("rules") {
+            None => &[][..],
+            Some(items) => items.as_array().ok_or("'rules' debe ser una lista de reglas")?,
+        };
+        for (index, item) in items.iter().enumerate() {
+            let rule = parse_rule(item).map_err(|e| match item.get("id").and_then(Value::as_str) {
+                Some(id) => format!("regla '{}': {}", id, e),
+                None => format!("regla #{}: {}", index + 1, e),
+            })?;
+            if rules.iter().any(|r| r.id == rule.id) {
+                return Err(format!("regla '{}' definida dos veces en el mismo pack", rule.id));
+            }
+            rules.push(rule);
+        }
+
+        Ok(RulePack {
+            name: optional_string(value, "name")?.unwrap_or_default(),
+            rules,
+            keywords: string_list(names.and_then(|n| n.get("keywords")), "names.keywords")?,
+            benign: string_list(names.and_then(|n| n.get("benign")), "names.benign")?,
+            disable: string_list(value.get("disable"), "disable")?,
+            base64_threshold: threshold("base64_threshold")?,
+            hex_threshold: threshold("hex_threshold")?,
+            min_length,
+        })
+    }
+}
+
+fn parse_rule(item: &Value) -> Result<Rule, String> {
+    check_keys(item, RULE_KEYS, "la regla")?;
+    let id = optional_string(item, "id")?.ok_or("falta 'id'")?;
+    if id.is_empty()
+        || !id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+    {
+        return Err("'id' solo admite letras, dígitos, '-', '_' y '.'".to_string());
+    }
+    let compile = |key: &str| -> Result<Option<Pattern>, String> {
+        optional_string(item, key)?
+            .map(|source| Pattern::new(&source).map_err(|e| format!("'{}' inválido: {}", key, e)))
+            .transpose()
+    };
+    let pattern = compile("pattern")?;
+    let name_pattern = compile("name_pattern")?;
+    let engine = ENGINE_RULES
+        .iter()
+        .find(|(engine_id, _)| *engine_id == id)
+        .map(|(_, kind)| *kind);
+    let kind = match (optional_string(item, "kind")?, engine) {
+        (Some(_), Some(_)) => return Err("es una regla interna: su 'kind' es fijo".to_string()),
+        (Some(kind), None) => SecretKind::parse(&kind).ok_or(format!("'kind' desconocido: {}", kind))?,
+        (None, Some(kind)) => kind,
+        (None, None) => SecretKind::Custom,
+    };
+    if engine.is_some() && (pattern.is_some() || name_pattern.is_some() || item.get("min_entropy").is_some()) {
+        return Err("es una regla interna: solo admite severity, confidence, description y remediation".to_string());
+    }
+    if engine == Some(SecretKind::HighEntropy) && item.get("confidence").is_some() {
+        return Err("la confianza de la regla de entropía se calcula a partir de la entropía".to_string());
+    }
+    if engine.is_none() && pattern.is_none() && name_pattern.is_none() {
+        return Err("necesita 'pattern', 'name_pattern' o ambos".to_string());
+    }
+    let severity = optional_string(item, "severity")?.ok_or("falta 'severity'")?;
+    let confidence = optional_string(item, "confidence")?;
+    Ok(Rule {
+        kind,
+        pattern,
+        name_pattern,
+        min_entropy: item
+            .get("min_entropy")
+            .map(|v| number_in(v, 0.0, 8.0, "min_entropy"))
+            .transpose()?,
+        severity: AuditPriority::parse(&severity).ok_or(format!("'severity' desconocida: {}", severity))?,
+        confidence: match confidence {
+            None => Confidence::Medium,
+            Some(text) => parse_confidence(&text).ok_or(format!("'confidence' desconocida: {}", text))?,
+        },
+        description: optional_string(item, "description")?
+            .filter(|d| !d.trim().is_empty())
+            .ok_or("falta 'description'")?,
+        remediation: optional_string(item, "remediation")?,
+        id,
+    })
+}
+
+fn parse_confidence(text: &str) -> Option<Confidence> {
+    [Confidence::Low, Confidence::Medium, Confidence::High]
+        .into_iter()
+        .find(|c| c.id() == text.to_lowercase())
+}
+
+// Claves desconocidas son un error: una errata ("severty") no debe pasar desapercibida.
+fn check_keys(value: &Value, allowed: &[&str], context: &str) -> Result<(), String> {
+    let Value::Object(fields) = value else {
+        return Err(format!("{} debe ser una tabla/mapa", context));
+    };
+    match fields.iter().find(|(key, _)| !allowed.contains(&key.as_str())) {
+        Some((key, _)) => Err(format!(
+            "clave desconocida '{}' en {} (válidas: {})",
+            key,
+            context,
+            allowed.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+fn optional_string(value: &Value, key: &str) -> Result<Option<String>, String> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(text)) => Ok(Some(text.clone())),
+        Some(_) => Err(format!("'{}' debe ser texto", key)),
+    }
+}
+
+fn string_list(value: Option<&Value>, context: &str) -> Result<Vec<String>, String> {
+    let Some(value) = value else {
+        return Ok(Vec::new());
+    };
+    value
+        .as_array()
+        .ok_or(format!("'{}' debe ser una lista de textos", context))?
+        .iter()
+        .map(|item| {
+            item.as_str()
+                .map(str::to_string)
+                .ok_or(format!("'{}' debe ser una lista de textos", context))
+        })
+        .collect()
+}
+
+fn number_in(value: &Value, min: f64, max: f64, context: &str) -> Result<f64, String> {
+    value
+        .as_f64()
+        .filter(|n| (min..=max).contains(n))
+        .ok_or(format!("'{}' debe ser un número entre {} y {}", context, min, max))
+}
@@ -0,0 +1,66 @@
+// Papiweb desarrollos informaticos
+// Historiales de shell de todos los usuarios: contraseñas tecleadas en la línea de comandos.
+use std::fs;
+
+use crate::cmdline;
+use crate::detectors::Detector;
+use crate::finding::Finding;
+use crate::system;
+
+// Rutas relativas al home: bash, zsh (dos nombres habituales), sh/ash y fish.
+const HISTORY_FILES: &[&str] = &[
+    ".bash_history",
+    ".zsh_history",
+    ".histfile",
+    ".sh_history",
+    ".ash_history",
+    ".local/share/fish/fish_history",
+];
+
+#[derive(Debug, Default)]
+pub struct HistoryStats {
+    pub files_scanned: u64,
+    pub commands_scanned: u64,
+    pub files_denied: u64,
+}
+
+pub fn scan_histories(detector: &Detector, stats: &mut HistoryStats) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for user in system::login_homes() {
+        for relative in HISTORY_FILES {
+            let path = user.home.join(relative);
+            if !path.is_file() {
+                continue;
+            }
+            let Ok(bytes) = fs::read(&path) else {
+                stats.files_denied += 1;
+                continue;
+            };
+            stats.files_scanned += 1;
+            let text = String::from_utf8_lossy(&bytes);
+            for (index, line) in text.lines().enumerate() {
+                let Some(offset) = command_offset(line) else {
+                    continue;
+                };
+                stats.commands_scanned += 1;
+                findings.extend(cmdline::line_findings(detector, &path, index + 1, line, offset));
+            }
+        }
+    }
+    findings
+}
+
+// Inicio del comando dentro de la línea según el formato del historial:
+// zsh extendido ": 1700000000:0;comando", fish "- cmd: comando" (sus líneas "when:"/"paths:" se ignoran).
+fn command_offset(line: &str) -> Option<usize> {
+    if line.starts_with(": ") {
+        return line.find(';').map(|i| i + 1);
+    }
+    if let Some(rest) = line.strip_prefix("- cmd: ") {
+        return Some(line.len() - rest.len());
+    }
+    if line.starts_with("  ") && line.trim_start().starts_with(['w', 'p', '-']) && line.contains(':') {
+        return None;
+    }
+    (!line.trim().is_empty() && !line.starts_with('#')).then_some(0)
+}
@@ -0,0 +1,516 @@
+// Papiweb desarrollos informaticos
+// Auditoría de sshd_config con sus Include (drop-ins de sshd_config.d) y bloques Match: contraseñas, acceso de root,
+// algoritmos débiles, MaxAuthTries y X11Forwarding como hallazgos de configuración.
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::finding::{AuditPriority, Finding, Location};
+use crate::gitignore::glob_to_regex;
+use crate::hardening;
+use crate::pattern::Pattern;
+
+pub const DEFAULT_CONFIG: &str = "/etc/ssh/sshd_config";
+
+const PASSWORD_RULE: &str = "sshd-password-authentication";
+const EMPTY_PASSWORD_RULE: &str = "sshd-permit-empty-passwords";
+const ROOT_LOGIN_RULE: &str = "sshd-permit-root-login";
+const CIPHERS_RULE: &str = "sshd-weak-ciphers";
+const MACS_RULE: &str = "sshd-weak-macs";
+const KEX_RULE: &str = "sshd-weak-kex";
+const MAX_AUTH_TRIES_RULE: &str = "sshd-max-auth-tries";
+const X11_RULE: &str = "sshd-x11-forwarding";
+
+// El mismo tope de anidamiento de Include que aplica sshd.
+const MAX_INCLUDE_DEPTH: usize = 16;
+const DEFAULT_MAX_AUTH_TRIES: u32 = 6;
+const RELOAD: &str = "validar con sshd -t y recargar (systemctl reload ssh)";
+
+// Valores por defecto de OpenSSH para las opciones que se evalúan aunque no aparezcan en la configuración.
+const DEFAULTS: &[(&str, &str)] = &[
+    ("PasswordAuthentication", "yes"),
+    ("PermitEmptyPasswords", "no"),
+    ("PermitRootLogin", "prohibit-password"),
+    ("MaxAuthTries", "6"),
+    ("X11Forwarding", "no"),
+];
+
+// Fragmento del nombre del algoritmo y prioridad; gana la primera coincidencia.
+const WEAK_CIPHERS: &[(&str, AuditPriority)] = &[
+    ("arcfour", AuditPriority::High),
+    ("3des", AuditPriority::High),
+    ("blowfish", AuditPriority::High),
+    ("cast128", AuditPriority::High),
+    ("-cbc", AuditPriority::Medium),
+];
+const WEAK_MACS: &[(&str, AuditPriority)] = &[
+    ("md5", AuditPriority::High),
+    ("sha1", AuditPriority::Medium),
+    ("umac-64", AuditPriority::Medium),
+];
+const WEAK_KEX: &[(&str, AuditPriority)] = &[("group1-sha1", AuditPriority::High), ("sha1", AuditPriority::Medium)];
+
+const STRONG_CIPHERS: &str =
+    "chacha20-poly1305@openssh.com,aes256-gcm@openssh.com,aes128-gcm@openssh.com,aes256-ctr,aes192-ctr,aes128-ctr";
+const STRONG_MACS: &str = "hmac-sha2-512-etm@openssh.com,hmac-sha2-256-etm@openssh.com,umac-128-etm@openssh.com";
+const STRONG_KEX: &str = "sntrup761x25519-sha512@openssh.com,curve25519-sha256,curve25519-sha256@libssh.org,diffie-hellman-group16-sha512,diffie-hellman-group18-sha512";
+
+#[derive(Debug, Default)]
+pub struct SshdStats {
+    pub files_read: u64,
+    pub directives: u64,
+    pub match_blocks: u64,
+    pub files_denied: u64,
+}
+
+pub fn rule_ids() -> Vec<String> {
+    [
+        PASSWORD_RULE,
+        EMPTY_PASSWORD_RULE,
+        ROOT_LOGIN_RULE,
+        CIPHERS_RULE,
+        MACS_RULE,
+        KEX_RULE,
+        MAX_AUTH_TRIES_RULE,
+        X11_RULE,
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+struct Directive {
+    // En minúsculas para comparar: sshd no distingue mayúsculas en las palabras clave.
+    keyword: String,
+    name: String,
+    value: String,
+    path: PathBuf,
+    line: usize,
+    // Criterios del bloque Match que la contiene; None en la sección global.
+    scope: Option<String>,
+    // Sintetizada con el valor por defecto porque la opción no aparece.
+    implicit: bool,
+}
+
+impl Directive {
+    fn label(&self) -> String {
+        let setting = if self.implicit {
+            format!("{} sin definir, por defecto {}", self.name, self.value)
+        } else {
+            format!("{} {}", self.name, self.value)
+        };
+        match &self.scope {
+            Some(criteria) => format!("Match {}: {}", criteria, setting),
+            None => setting,
+        }
+    }
+}
+
+struct Reader<'a> {
+    // Directorio contra el que se resuelven los Include relativos (/etc/ssh para la configuración del sistema).
+    base: PathBuf,
+    directives: Vec<Directive>,
+    stats: &'a mut SshdStats,
+}
+
+pub fn scan_sshd(config: &Path, stats: &mut SshdStats) -> Result<Vec<Finding>, String> {
+    let text = fs::read_to_string(config).map_err(|e| format!("{}: {}", config.display(), e))?;
+    let mut reader = Reader {
+        base: config.parent().unwrap_or(Path::new("/etc/ssh")).to_path_buf(),
+        directives: Vec::new(),
+        stats,
+    };
+    reader.read(config, &text, None, 0);
+    reader.stats.directives = reader.directives.len() as u64;
+    Ok(audit(config, reader.directives))
+}
+
+impl Reader<'_> {
+    // Un Match dura hasta el siguiente Match o el final del archivo donde empezó; un Include dentro de un Match
+    // hereda sus criterios.
+    fn read(&mut self, path: &Path, text: &str, scope: Option<&str>, depth: usize) {
+        self.stats.files_read += 1;
+        let mut current = scope.map(str::to_string);
+        for (index, line) in text.lines().enumerate() {
+            let Some((name, value)) = split_directive(line) else {
+                continue;
+            };
+            let keyword = name.to_ascii_lowercase();
+            match keyword.as_str() {
+                "match" => {
+                    self.stats.match_blocks += 1;
+                    current = (!value.eq_ignore_ascii_case("all")).then(|| value.to_string());
+                }
+                "include" if depth < MAX_INCLUDE_DEPTH => {
+                    for argument in value.split_whitespace() {
+                        for file in expand(&self.base.join(unquote(argument))) {
+                            match fs::read_to_string(&file) {
+                                Ok(text) => self.read(&file, &text, current.as_deref(), depth + 1),
+                                Err(_) => self.stats.files_denied += 1,
+                            }
+                        }
+                    }
+                }
+                "include" => {}
+                _ => self.directives.push(Directive {
+                    keyword,
+                    name: name.to_string(),
+                    value: unquote(value).to_string(),
+                    path: path.to_path_buf(),
+                    line: index + 1,
+                    scope: current.clone(),
+                    implicit: false,
+                }),
+            }
+        }
+    }
+}
+
+// "Clave valor", "Clave=valor" o "Clave = valor".
+fn split_directive(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim();
+    Some((&line[..end], rest))
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+// Include admite comodines en cualquier componente de la ruta; se expanden en orden léxico como glob(3) y los que
+// no coinciden con nada se ignoran, igual que en sshd.
+fn expand(pattern: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let part = match component {
+            Component::Normal(part) => part.to_string_lossy(),
+            other => {
+                paths.iter_mut().for_each(|p| p.push(other));
+                continue;
+            }
+        };
+        if !part.contains(['*', '?', '[']) {
+            paths.iter_mut().for_each(|p| p.push(&*part));
+            continue;
+        }
+        let Ok(matcher) = Pattern::new(&format!("^{}$", glob_to_regex(&part))) else {
+            return Vec::new();
+        };
+        paths = paths
+            .iter()
+            .flat_map(|dir| {
+                let mut names: Vec<String> = fs::read_dir(dir)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .filter(|name| !name.starts_with('.') && matcher.is_match(name))
+                    .collect();
+                names.sort();
+                names.into_iter().map(move |name| dir.join(name))
+            })
+            .collect();
+    }
+    paths.retain(|p| p.is_file());
+    paths
+}
+
+struct Risk {
+    rule_id: &'static str,
+    priority: AuditPriority,
+    text: String,
+    fix: String,
+}
+
+fn audit(config: &Path, mut directives: Vec<Directive>) -> Vec<Finding> {
+    // sshd se queda con el primer valor de cada opción y descarta las repeticiones; en un Match, con el primero del
+    // bloque.
+    let mut seen = HashSet::new();
+    directives.retain(|d| seen.insert((d.scope.clone(), d.keyword.clone())));
+    for (name, value) in DEFAULTS {
+        let keyword = name.to_ascii_lowercase();
+        if !seen.contains(&(None, keyword.clone())) {
+            directives.push(Directive {
+                keyword,
+                name: name.to_string(),
+                value: value.to_string(),
+                path: config.to_path_buf(),
+                line: 1,
+                scope: None,
+                implicit: true,
+            });
+        }
+    }
+    let password_auth = |scope: &Option<String>| {
+        let lookup = |scope: &Option<String>| {
+            directives
+                .iter()
+                .find(|d| &d.scope == scope && d.keyword == "passwordauthentication")
+        };
+        lookup(scope)
+            .or_else(|| lookup(&None))
+            .is_none_or(|d| d.value.eq_ignore_ascii_case("yes"))
+    };
+    directives
+        .iter()
+        .filter_map(|d| {
+            let risk = check(d, password_auth(&d.scope))?;
+            let label = d.label();
+            Some(hardening::finding(
+                risk.rule_id,
+                risk.priority,
+                Location::File {
+                    path: d.path.clone(),
+                    line: d.line,
+                    column: 1,
+                    key: Some(label.clone()),
+                },
+                &label,
+                risk.text,
+                format!("{}; {}", risk.fix, RELOAD),
+            ))
+        })
+        .collect()
+}
+
+fn check(directive: &Directive, password_auth: bool) -> Option<Risk> {
+    let value = directive.value.to_ascii_lowercase();
+    match directive.keyword.as_str() {
+        "passwordauthentication" if value == "yes" => Some(Risk {
+            rule_id: PASSWORD_RULE,
+            priority: AuditPriority::High,
+            text: "sshd acepta contraseñas: queda expuesto a fuerza bruta y a contraseñas reutilizadas o filtradas"
+                .to_string(),
+            fix: "PasswordAuthentication no y KbdInteractiveAuthentication no, con acceso por clave o certificado"
+                .to_string(),
+        }),
+        "permitemptypasswords" if value == "yes" => Some(Risk {
+            rule_id: EMPTY_PASSWORD_RULE,
+            priority: AuditPriority::Critical,
+            text: "sshd deja entrar a cuentas sin contraseña".to_string(),
+            fix: "PermitEmptyPasswords no y bloquear las cuentas sin contraseña (passwd -l)".to_string(),
+        }),
+        "permitrootlogin" if value == "yes" => Some(Risk {
+            rule_id: ROOT_LOGIN_RULE,
+            priority: if password_auth {
+                AuditPriority::Critical
+            } else {
+                AuditPriority::High
+            },
+            text: if password_auth {
+                "root puede entrar por SSH con contraseña: la cuenta más atacada, sin usuario nominal que lo registre"
+                    .to_string()
+            } else {
+                "root puede entrar por SSH directamente (hoy solo con clave porque las contraseñas están desactivadas)"
+                    .to_string()
+            },
+            fix: "PermitRootLogin no y entrar con un usuario propio que use sudo".to_string(),
+        }),
+        "permitrootlogin" if value == "prohibit-password" || value == "without-password" => Some(Risk {
+            rule_id: ROOT_LOGIN_RULE,
+            priority: AuditPriority::Low,
+            text: "root puede entrar por SSH con clave: un acceso directo sin usuario nominal ni sudo que lo registre"
+                .to_string(),
+            fix: "PermitRootLogin no (o forced-commands-only si root solo ejecuta un comando fijo, como backups)"
+                .to_string(),
+        }),
+        "ciphers" => weak_algorithms(&value, WEAK_CIPHERS).map(|(priority, weak)| Risk {
+            rule_id: CIPHERS_RULE,
+            priority,
+            text: format!("cifrados débiles habilitados: {}", weak),
+            fix: format!("Ciphers {}", STRONG_CIPHERS),
+        }),
+        "macs" => weak_algorithms(&value, WEAK_MACS).map(|(priority, weak)| Risk {
+            rule_id: MACS_RULE,
+            priority,
+            text: format!("MAC débiles habilitados: {}", weak),
+            fix: format!("MACs {}", STRONG_MACS),
+        }),
+        "kexalgorithms" => weak_algorithms(&value, WEAK_KEX).map(|(priority, weak)| Risk {
+            rule_id: KEX_RULE,
+            priority,
+            text: format!("intercambio de claves débil habilitado: {}", weak),
+            fix: format!("KexAlgorithms {}", STRONG_KEX),
+        }),
+        "maxauthtries" if directive.implicit => Some(Risk {
+            rule_id: MAX_AUTH_TRIES_RULE,
+            priority: AuditPriority::Low,
+            text: format!(
+                "MaxAuthTries sin definir: sshd admite {} intentos de autenticación por conexión",
+                DEFAULT_MAX_AUTH_TRIES
+            ),
+            fix: "MaxAuthTries 3".to_string(),
+        }),
+        "maxauthtries" => {
+            let tries: u32 = value.parse().ok()?;
+            (tries > DEFAULT_MAX_AUTH_TRIES).then(|| Risk {
+                rule_id: MAX_AUTH_TRIES_RULE,
+                priority: AuditPriority::Medium,
+                text: format!(
+                    "MaxAuthTries {}: más intentos por conexión que el valor por defecto ({})",
+                    tries, DEFAULT_MAX_AUTH_TRIES
+                ),
+                fix: "MaxAuthTries 3".to_string(),
+            })
+        }
+        "x11forwarding" if value == "yes" => Some(Risk {
+            rule_id: X11_RULE,
+            priority: AuditPriority::Medium,
+            text: "X11Forwarding activo: un servidor comprometido puede leer teclado y pantalla de quien se conecta con -X"
+                .to_string(),
+            fix: "X11Forwarding no (y habilitarlo solo en un Match para los usuarios que lo necesiten)".to_string(),
+        }),
+        _ => None,
+    }
+}
+
+// "+alg,alg", "^alg" o la lista completa; "-alg" solo quita algoritmos y no puede añadir uno débil.
+fn weak_algorithms(value: &str, weak: &[(&str, AuditPriority)]) -> Option<(AuditPriority, String)> {
+    if value.starts_with('-') {
+        return None;
+    }
+    let list = value.trim_start_matches(['+', '^']);
+    let found: Vec<(&str, AuditPriority)> = list
+        .split(',')
+        .filter_map(|algorithm| {
+            let (_, priority) = weak.iter().find(|(fragment, _)| algorithm.contains(fragment))?;
+            Some((algorithm, *priority))
+        })
+        .collect();
+    let priority = found.iter().map(|(_, p)| *p).max()?;
+    let names: Vec<&str> = found.iter().map(|(a, _)| *a).collect();
+    Some((priority, names.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    fn audit_text(base: &Path, text: &str, stats: &mut SshdStats) -> Vec<Finding> {
+        let config = base.join("sshd_config");
+        let mut reader = Reader {
+            base: base.to_path_buf(),
+            directives: Vec::new(),
+            stats,
+        };
+        reader.read(&config, text, None, 0);
+        audit(&config, reader.directives)
+    }
+
+    // Archivo, línea, opción con su bloque Match y prioridad de cada hallazgo.
+    fn summary(findings: &[Finding]) -> Vec<(String, usize, String, AuditPriority)> {
+        findings
+            .iter()
+            .map(|finding| match &finding.location {
+                Location::File { path, line, key, .. } => (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    *line,
+                    key.clone().unwrap(),
+                    finding.priority,
+                ),
+                other => panic!("ubicación inesperada: {}", other),
+            })
+            .collect()
+    }
+
+    fn labels(findings: &[Finding]) -> Vec<String> {
+        summary(findings).into_iter().map(|(_, _, label, _)| label).collect()
+    }
+
+    #[test]
+    fn first_value_wins_and_defaults_fill_the_rest() {
+        let base = Path::new("/etc/ssh");
+        let text = "# comentario\nPasswordAuthentication no\npasswordauthentication yes\nMaxAuthTries=10\n\
+                    MaxAuthTries 3\nPermitRootLogin \"no\"\nX11Forwarding yes\nX11Forwarding no\n";
+        let findings = audit_text(base, text, &mut SshdStats::default());
+        assert_eq!(labels(&findings), ["MaxAuthTries 10", "X11Forwarding yes"]);
+        assert_eq!(summary(&findings)[0].1, 4);
+
+        let findings = audit_text(base, "", &mut SshdStats::default());
+        assert_eq!(
+            labels(&findings),
+            [
+                "PasswordAuthentication sin definir, por defecto yes",
+                "PermitRootLogin sin definir, por defecto prohibit-password",
+                "MaxAuthTries sin definir, por defecto 6"
+            ]
+        );
+    }
+
+    #[test]
+    fn match_blocks_are_scoped() {
+        let text = "PasswordAuthentication no\nPermitRootLogin yes\nMatch User deploy\n    PasswordAuthentication yes\n\
+                    \x20   PasswordAuthentication no\n    PermitRootLogin yes\nMatch all\nX11Forwarding yes\nMaxAuthTries 3\n";
+        let findings = audit_text(Path::new("/etc/ssh"), text, &mut SshdStats::default());
+        let summary: Vec<_> = summary(&findings)
+            .into_iter()
+            .map(|(_, line, label, priority)| (line, label, priority))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (2, "PermitRootLogin yes".to_string(), AuditPriority::High),
+                (
+                    4,
+                    "Match User deploy: PasswordAuthentication yes".to_string(),
+                    AuditPriority::High
+                ),
+                // Dentro del bloque las contraseñas están activas: root con contraseña.
+                (
+                    6,
+                    "Match User deploy: PermitRootLogin yes".to_string(),
+                    AuditPriority::Critical
+                ),
+                (8, "X11Forwarding yes".to_string(), AuditPriority::Medium),
+            ]
+        );
+    }
+
+    #[test]
+    fn includes_expand_globs_in_order() {
+        let base = env::temp_dir().join(format!("auditorlocal-sshd-{}", process::id()));
+        let drop_ins = base.join("sshd_config.d");
+        fs::create_dir_all(&drop_ins).unwrap();
+        let files = [
+            ("20-b.conf", "PasswordAuthentication no\nPermitRootLogin no\n"),
+            (
+                "10-a.conf",
+                "PasswordAuthentication yes\nMatch User backup\n  X11Forwarding yes\n",
+            ),
+            (".oculto.conf", "PermitEmptyPasswords yes\n"),
+            ("otro.txt", "PermitEmptyPasswords yes\n"),
+        ];
+        for (name, text) in files {
+            fs::write(drop_ins.join(name), text).unwrap();
+        }
+        // El Match de 10-a.conf termina con su archivo; el Include dentro de un Match hereda sus criterios.
+        let text = "Include sshd_config.d/*.conf /no/existe/*.conf\nPasswordAuthentication no\nX11Forwarding yes\n\
+                    Match User ana\n  Include sshd_config.d/2?-*.conf\n";
+        let mut stats = SshdStats::default();
+        let findings = audit_text(&base, text, &mut stats);
+        fs::remove_dir_all(&base).unwrap();
+        let summary: Vec<_> = summary(&findings)
+            .into_iter()
+            .map(|(file, line, label, _)| (file, line, label))
+            .collect();
+        let expected = [
+            ("10-a.conf", 1, "PasswordAuthentication yes"),
+            ("10-a.conf", 3, "Match User backup: X11Forwarding yes"),
+            ("sshd_config", 3, "X11Forwarding yes"),
+            ("sshd_config", 1, "MaxAuthTries sin definir, por defecto 6"),
+        ];
+        assert_eq!(
+            summary,
+            expected.map(|(file, line, label)| (file.to_string(), line, label.to_string()))
+        );
+        assert_eq!((stats.files_read, stats.match_blocks), (4, 2));
+    }
+}
@@ -0,0 +1,55 @@
+// Papiweb desarrollos informaticos
+// Información del sistema local: usuarios de /etc/passwd y sus homes, grupos de /etc/group.
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct User {
+    pub name: String,
+    pub uid: u32,
+    pub home: PathBuf,
+}
+
+pub fn users() -> Vec<User> {
+    let Ok(text) = fs::read_to_string("/etc/passwd") else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some(User {
+                name: fields.first()?.to_string(),
+                uid: fields.get(2)?.parse().ok()?,
+                home: PathBuf::from(fields.get(5)?),
+            })
+        })
+        .collect()
+}
+
+// Usuarios con un home propio existente (sin "/" ni homes compartidos repetidos).
+pub fn login_homes() -> Vec<User> {
+    let mut seen = HashSet::new();
+    users()
+        .into_iter()
+        .filter(|u| u.home != Path::new("/") && u.home.is_dir() && seen.insert(u.home.clone()))
+        .collect()
+}
+
+pub fn user_names() -> HashMap<u32, String> {
+    users().into_iter().map(|u| (u.uid, u.name)).collect()
+}
+
+pub fn group_names() -> HashMap<u32, String> {
+    let Ok(text) = fs::read_to_string("/etc/group") else {
+        return HashMap::new();
+    };
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some((fields.get(2)?.parse().ok()?, fields.first()?.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,286 @@
+// Papiweb desarrollos informaticos
+// Lector de archivos tar (ustar, nombres largos GNU y cabeceras PAX) para imágenes de contenedor.
+// Solo se indexan las cabeceras; el contenido se lee bajo demanda para no cargar el archivo entero.
+use std::io::{self, Read, Seek, SeekFrom};
+
+const BLOCK: u64 = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Link,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    // Ruta normalizada: sin "./" inicial ni '/' final.
+    pub path: String,
+    pub kind: EntryKind,
+    // Posición y tamaño del contenido dentro del tar.
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl Entry {
+    pub fn read<R: Read + Seek>(&self, reader: &mut R) -> Result<Vec<u8>, String> {
+        let mut data = vec![0; self.size as usize];
+        reader
+            .seek(SeekFrom::Start(self.offset))
+            .and_then(|_| reader.read_exact(&mut data))
+            .map_err(|e| format!("tar: no se pudo leer {}: {}", self.path, e))?;
+        Ok(data)
+    }
+
+    pub fn slice<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        &data[self.offset as usize..(self.offset + self.size) as usize]
+    }
+}
+
+pub fn index<R: Read + Seek>(reader: &mut R) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut long_name: Option<String> = None;
+    let mut pax_path: Option<String> = None;
+    let mut position = 0u64;
+    loop {
+        let mut header = [0u8; BLOCK as usize];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            // Algunos generadores omiten los dos bloques vacíos finales.
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("tar: {}", e)),
+        }
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        if !checksum_ok(&header) {
+            return Err(format!("tar: cabecera corrupta en el byte {}", position));
+        }
+        let size = parse_size(&header[124..136]).ok_or(format!("tar: tamaño inválido en el byte {}", position))?;
+        let offset = position + BLOCK;
+        let kind = header[156];
+        match kind {
+            // Nombre largo GNU o cabecera extendida PAX: aplican a la entrada siguiente.
+            b'L' | b'x' => {
+                let mut data = vec![0; size as usize];
+                reader.read_exact(&mut data).map_err(|e| format!("tar: {}", e))?;
+                if kind == b'L' {
+                    long_name = Some(c_string(&data));
+                } else {
+                    pax_path = pax_records(&data)
+                        .find(|(k, _)| *k == "path")
+                        .map(|(_, v)| v.to_string());
+                }
+            }
+            _ => {
+                let path = pax_path
+                    .take()
+                    .or(long_name.take())
+                    .unwrap_or_else(|| header_name(&header));
+                entries.push(Entry {
+                    path: normalize(&path),
+                    kind: match kind {
+                        b'0' | b'\0' | b'7' => EntryKind::File,
+                        b'5' => EntryKind::Directory,
+                        b'1' | b'2' => EntryKind::Link,
+                        _ => EntryKind::Other,
+                    },
+                    offset,
+                    size,
+                });
+            }
+        }
+        position = offset + size.div_ceil(BLOCK) * BLOCK;
+        reader
+            .seek(SeekFrom::Start(position))
+            .map_err(|e| format!("tar: {}", e))?;
+    }
+    Ok(entries)
+}
+
+fn checksum_ok(header: &[u8]) -> bool {
+    let expected = parse_octal(&header[148..156]);
+    let sum: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| {
+            if (148..156).contains(&i) {
+                u64::from(b' ')
+            } else {
+                u64::from(b)
+            }
+        })
+        .sum();
+    expected == Some(sum)
+}
+
+// ustar: prefijo (345..500) + nombre (0..100).
+fn header_name(header: &[u8]) -> String {
+    let name = c_string(&header[..100]);
+    if &header[257..262] == b"ustar" {
+        let prefix = c_string(&header[345..500]);
+        if !prefix.is_empty() {
+            return format!("{}/{}", prefix, name);
+        }
+    }
+    name
+}
+
+fn normalize(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_start_matches('/')
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let text = c_string(field);
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+// Tamaños grandes usan codificación binaria (GNU): bit alto del primer byte a 1.
+fn parse_size(field: &[u8]) -> Option<u64> {
+    if field[0] & 0x80 != 0 {
+        return Some(
+            field[1..]
+                .iter()
+                .fold(u64::from(field[0] & 0x7f), |n, &b| n << 8 | u64::from(b)),
+        );
+    }
+    parse_octal(field)
+}
+
+// Registros PAX "LONGITUD clave=valor\n".
+fn pax_records(data: &[u8]) -> impl Iterator<Item = (&str, &str)> {
+    let text = std::str::from_utf8(data).unwrap_or_default();
+    text.lines().filter_map(|record| {
+        let (_, pair) = record.split_once(' ')?;
+        pair.split_once('=')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn header(name: &str, kind: u8, size: u64) -> [u8; BLOCK as usize] {
+        let mut header = [0u8; BLOCK as usize];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..108].copy_from_slice(b"0000644\0");
+        header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        header
+    }
+
+    fn push(archive: &mut Vec<u8>, mut header: [u8; BLOCK as usize], data: &[u8]) {
+        header[148..156].copy_from_slice(b"        ");
+        let sum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(data);
+        archive.resize(archive.len().div_ceil(BLOCK as usize) * BLOCK as usize, 0);
+    }
+
+    fn paths(archive: &[u8]) -> Vec<(String, EntryKind)> {
+        index(&mut Cursor::new(archive))
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.path, entry.kind))
+            .collect()
+    }
+
+    #[test]
+    fn contents_are_read_by_offset() {
+        let mut archive = Vec::new();
+        push(&mut archive, header("./app/", b'5', 0), b"");
+        push(&mut archive, header("./app/.env", b'0', 12), b"TOKEN=valor\n");
+        push(&mut archive, header("app/enlace", b'2', 0), b"");
+        archive.extend_from_slice(&[0; 2 * BLOCK as usize]);
+        let entries = index(&mut Cursor::new(&archive)).unwrap();
+        assert_eq!(
+            paths(&archive),
+            [
+                ("app".to_string(), EntryKind::Directory),
+                ("app/.env".to_string(), EntryKind::File),
+                ("app/enlace".to_string(), EntryKind::Link),
+            ]
+        );
+        assert_eq!(entries[1].slice(&archive), b"TOKEN=valor\n");
+        assert_eq!(entries[1].read(&mut Cursor::new(&archive)).unwrap(), b"TOKEN=valor\n");
+    }
+
+    #[test]
+    fn long_names_from_gnu_pax_and_ustar_prefix() {
+        let deep = format!("{}/secreto.env", "directorio-largo".repeat(8));
+        let mut archive = Vec::new();
+        push(
+            &mut archive,
+            header("././@LongLink", b'L', deep.len() as u64 + 1),
+            format!("{}\0", deep).as_bytes(),
+        );
+        push(&mut archive, header("truncado", b'0', 0), b"");
+        let record = format!("{} path=pax/{}\n", deep.len() + 14, deep);
+        push(
+            &mut archive,
+            header("PaxHeader", b'x', record.len() as u64),
+            record.as_bytes(),
+        );
+        push(&mut archive, header("truncado", b'0', 0), b"");
+        let mut prefixed = header("config.json", b'0', 0);
+        prefixed[345..352].copy_from_slice(b"etc/app");
+        push(&mut archive, prefixed, b"");
+        // Las cabeceras extendidas solo aplican a la entrada siguiente.
+        push(&mut archive, header("corto.txt", b'0', 0), b"");
+        let names: Vec<String> = paths(&archive).into_iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            names,
+            [
+                deep.clone(),
+                format!("pax/{}", deep),
+                "etc/app/config.json".into(),
+                "corto.txt".into()
+            ]
+        );
+    }
+
+    #[test]
+    fn numeric_fields_with_spaces_and_binary_sizes() {
+        let mut spaced = header("espacios.txt", b'0', 0);
+        spaced[124..136].copy_from_slice(b"         14 ");
+        let mut binary = header("binario.txt", b'0', 0);
+        binary[124..136].copy_from_slice(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5]);
+        let mut archive = Vec::new();
+        push(&mut archive, spaced, b"hola\nmundo!\n");
+        push(&mut archive, binary, b"corto");
+        // Sin los dos bloques vacíos finales: el índice termina igual.
+        let entries = index(&mut Cursor::new(&archive)).unwrap();
+        assert_eq!(entries.iter().map(|e| e.size).collect::<Vec<_>>(), [12, 5]);
+        assert_eq!(entries[1].slice(&archive), b"corto");
+    }
+
+    #[test]
+    fn whiteouts_are_listed_verbatim_and_corruption_is_reported() {
+        let mut archive = Vec::new();
+        push(&mut archive, header("etc/.wh.secreto.env", b'0', 0), b"");
+        push(&mut archive, header("var/cache/.wh..wh..opq", b'0', 0), b"");
+        let names: Vec<String> = paths(&archive).into_iter().map(|(path, _)| path).collect();
+        assert_eq!(names, ["etc/.wh.secreto.env", "var/cache/.wh..wh..opq"]);
+
+        archive[BLOCK as usize] ^= 1;
+        let error = index(&mut Cursor::new(&archive)).unwrap_err();
+        assert_eq!(error, "tar: cabecera corrupta en el byte 512");
+    }
+}
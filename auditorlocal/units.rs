@@ -0,0 +1,289 @@
+// Papiweb desarrollos informaticos
+// Unidades systemd y sus drop-ins: secretos en Environment=, en las líneas Exec*= y en los EnvironmentFile=
+// referenciados. Environment= es visible para cualquier usuario con "systemctl show".
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::cmdline;
+use crate::detectors::Detector;
+use crate::filescan::FileScanner;
+use crate::finding::{AuditPriority, Finding, Location, Note};
+use crate::formats::Format;
+use crate::system;
+
+// /lib suele ser un enlace a /usr/lib: se deduplica por ruta canónica.
+const UNIT_DIRS: &[&str] = &[
+    "/etc/systemd/system",
+    "/run/systemd/system",
+    "/usr/lib/systemd/system",
+    "/lib/systemd/system",
+    "/etc/systemd/user",
+    "/usr/lib/systemd/user",
+];
+const USER_UNIT_DIR: &str = ".config/systemd/user";
+const UNIT_EXTENSIONS: &[&str] = &["service", "socket", "timer", "mount", "path", "target"];
+
+#[derive(Debug, Default)]
+pub struct UnitStats {
+    pub units_scanned: u64,
+    pub env_files_scanned: u64,
+    pub files_denied: u64,
+}
+
+pub fn scan_units(detector: &Detector, scanner: &FileScanner, stats: &mut UnitStats) -> Vec<Finding> {
+    let mut dirs: Vec<PathBuf> = UNIT_DIRS.iter().map(PathBuf::from).collect();
+    dirs.extend(system::login_homes().into_iter().map(|u| u.home.join(USER_UNIT_DIR)));
+
+    let mut seen_dirs = HashSet::new();
+    let mut unit_files = Vec::new();
+    for dir in dirs {
+        let Ok(canonical) = fs::canonicalize(&dir) else {
+            continue;
+        };
+        if seen_dirs.insert(canonical.clone()) {
+            collect_units(&canonical, &mut unit_files);
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut env_files = HashSet::new();
+    for path in unit_files {
+        let Ok(text) = fs::read_to_string(&path) else {
+            stats.files_denied += 1;
+            continue;
+        };
+        stats.units_scanned += 1;
+        let world_readable = fs::metadata(&path).is_ok_and(|m| m.mode() & 0o004 != 0);
+        let mut unit_findings = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let Some((directive, value_start)) = directive(line) else {
+                continue;
+            };
+            if directive == "Environment" {
+                unit_findings.extend(environment_findings(detector, &path, index + 1, line, value_start));
+            } else if directive.starts_with("Exec") {
+                // Prefijos especiales de systemd: "-/usr/bin/x", "+", "!", "@", ":"
+                let prefix =
+                    line[value_start..].len() - line[value_start..].trim_start_matches(['-', '@', ':', '+', '!']).len();
+                unit_findings.extend(cmdline::line_findings(
+                    detector,
+                    &path,
+                    index + 1,
+                    line,
+                    value_start + prefix,
+                ));
+            } else if directive == "EnvironmentFile" {
+                let env_path = PathBuf::from(line[value_start..].trim().trim_start_matches('-'));
+                if env_files.insert(env_path.clone()) {
+                    findings.extend(scan_env_file(scanner, &env_path, stats));
+                }
+            }
+        }
+        if world_readable {
+            for finding in &mut unit_findings {
+                finding.notes.push(Note {
+                    id: "unit-world-readable",
+                    priority: AuditPriority::Medium,
+                    text: "la unidad es legible por todos los usuarios: usar LoadCredential= o un EnvironmentFile= con permisos 0600".to_string(),
+                });
+            }
+        }
+        findings.extend(unit_findings);
+    }
+    findings
+}
+
+// Archivos de unidad del directorio y los .conf de sus drop-ins (nombre.service.d/*.conf).
+// Los enlaces simbólicos (.wants, unidades enmascaradas o alias) se ignoran: apuntan a unidades ya listadas.
+fn collect_units(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        if file_type.is_file() && UNIT_EXTENSIONS.contains(&extension) {
+            out.push(path);
+        } else if file_type.is_dir() && extension == "d" {
+            let Ok(dropins) = fs::read_dir(&path) else {
+                continue;
+            };
+            let mut dropins: Vec<PathBuf> = dropins
+                .flatten()
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "conf"))
+                .collect();
+            dropins.sort();
+            out.extend(dropins);
+        }
+    }
+}
+
+// "Directiva=valor" -> (directiva, inicio del valor). Secciones y comentarios se ignoran.
+fn directive(line: &str) -> Option<(&str, usize)> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with(['#', ';', '[']) {
+        return None;
+    }
+    let eq = line.find('=')?;
+    let value_start = eq + 1 + (line[eq + 1..].len() - line[eq + 1..].trim_start().len());
+    Some((line[..eq].trim(), value_start))
+}
+
+// Environment="A=1" B=2 ...: cada asignación se evalúa como par nombre/valor.
+fn environment_findings(
+    detector: &Detector,
+    path: &Path,
+    line_no: usize,
+    line: &str,
+    value_start: usize,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (start, end) in cmdline::tokenize(&line[value_start..]) {
+        let (start, end) = cmdline::unquote(line, value_start + start, value_start + end);
+        let assignment = &line[start..end];
+        let Some(eq) = assignment.find('=') else {
+            continue;
+        };
+        let (name, value) = (&assignment[..eq], &assignment[eq + 1..]);
+        for detection in detector.scan_pair(name, value) {
+            let location = Location::File {
+                path: path.to_path_buf(),
+                line: line_no,
+                column: line[..start + eq + 1 + detection.start].chars().count() + 1,
+                key: Some(format!("Environment={}", name)),
+            };
+            findings.push(detection.into_finding(value, location));
+        }
+    }
+    findings
+}
+
+// EnvironmentFile=: mismo formato que un .env; que otros usuarios puedan leerlo agrava el hallazgo.
+fn scan_env_file(scanner: &FileScanner, path: &Path, stats: &mut UnitStats) -> Vec<Finding> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Vec::new();
+    };
+    let Ok(text) = fs::read_to_string(path) else {
+        stats.files_denied += 1;
+        return Vec::new();
+    };
+    stats.env_files_scanned += 1;
+    let mut findings = scanner.scan_text(path, Format::Dotenv, &text);
+    let mode = metadata.mode() & 0o7777;
+    if mode & 0o004 != 0 {
+        for finding in &mut findings {
+            finding.notes.push(Note {
+                id: "envfile-world-readable",
+                priority: AuditPriority::High,
+                text: format!(
+                    "EnvironmentFile con permisos {:04o}: legible por cualquier usuario del sistema",
+                    mode
+                ),
+            });
+            finding.priority = finding.priority.max(AuditPriority::High);
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names::NameMatcher;
+    use crate::rules::RuleSet;
+    use std::os::unix::fs::PermissionsExt;
+    use std::{env, process};
+
+    fn detector() -> Detector {
+        let rules = RuleSet::builtin();
+        Detector::new(&rules, NameMatcher::new(&rules.keywords, &rules.benign))
+    }
+
+    // Clave, columna y valor de cada hallazgo de la línea.
+    fn unit_line(detector: &Detector, line: &str) -> Vec<(String, usize, String)> {
+        let Some((directive, value_start)) = directive(line) else {
+            return Vec::new();
+        };
+        let path = Path::new("/etc/systemd/system/app.service");
+        let findings = match directive {
+            "Environment" => environment_findings(detector, path, 1, line, value_start),
+            _ => cmdline::line_findings(detector, path, 1, line, value_start),
+        };
+        findings
+            .into_iter()
+            .map(|finding| match finding.location {
+                Location::File { column, key, .. } => (key.unwrap(), column, finding.secret.expose().to_string()),
+                other => panic!("ubicación inesperada: {}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn environment_assignments() {
+        let detector = detector();
+        let row = |key: &str, column: usize, value: &str| (key.to_string(), column, value.to_string());
+        let cases = [
+            (
+                "Environment=DB_PASSWORD=S3cr3tPass",
+                vec![row("Environment=DB_PASSWORD", 25, "S3cr3tPass")],
+            ),
+            (
+                "Environment=\"LOG=debug\" \"DB_PASSWORD=S3cr3tPass\" API_TOKEN=Zx9Qw7Er5Ty3",
+                vec![
+                    row("Environment=DB_PASSWORD", 38, "S3cr3tPass"),
+                    row("Environment=API_TOKEN", 60, "Zx9Qw7Er5Ty3"),
+                ],
+            ),
+            (
+                "Environment = 'DB_PASSWORD=S3cr3tPass'",
+                vec![row("Environment=DB_PASSWORD", 28, "S3cr3tPass")],
+            ),
+            ("Environment=DB_PASSWORD=${DB_PASSWORD} LOG=info", vec![]),
+            ("Environment=", vec![]),
+            ("# Environment=DB_PASSWORD=S3cr3tPass", vec![]),
+            ("[Service]", vec![]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(unit_line(&detector, line), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn exec_lines() {
+        let detector = detector();
+        assert_eq!(
+            unit_line(&detector, "ExecStart=/usr/bin/mysqldump -pS3cr3tPass app"),
+            [("mysqldump -p".to_string(), 32, "S3cr3tPass".to_string())]
+        );
+        assert!(unit_line(&detector, "ExecStart=/usr/bin/app --config /etc/app.conf").is_empty());
+    }
+
+    #[test]
+    fn world_readable_environment_file() {
+        let detector = detector();
+        let scanner = FileScanner::new(&detector, 1024 * 1024);
+        let path = env::temp_dir().join(format!("auditorlocal-units-{}.env", process::id()));
+        fs::write(&path, "LOG=info\nDB_PASSWORD=S3cr3tPass\n").unwrap();
+        let scan = |mode: u32| {
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            scan_env_file(&scanner, &path, &mut UnitStats::default())
+        };
+        let private = scan(0o600);
+        let public = scan(0o644);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(private.len(), 1);
+        assert!(private[0].notes.iter().all(|n| n.id != "envfile-world-readable"));
+        assert_eq!(public.len(), 1);
+        assert_eq!(public[0].priority, AuditPriority::High);
+        assert!(public[0].notes.iter().any(|n| n.id == "envfile-world-readable"));
+    }
+}
@@ -1,5 +1,8 @@
 // Papiweb desarrollos informáticos - Professional Audit System
 // VERSIÓN ULTRA RENDIMIENTO CON TOKIO
+#[path = "auditorlocal/preflight.rs"]
+mod preflight;
+
 use tokio::sync::mpsc;
 use tokio::time::{self, Duration, Instant};
 use std::collections::HashMap;
@@ -7,6 +10,8 @@ use tokio::task;
 use futures::future::join_all;
 use rand::Rng;
 use std::sync::Arc;
+use std::path::Path;
+use std::process;
 use tokio::sync::Mutex;
 
 // 1. Definición de tipos de auditoría mejorada
@@ -354,6 +359,36 @@ fn generate_high_frequency_audits(count: u64) -> Vec<AuditType> {
     audits
 }
 
+// Corta el proceso si el alcance no se puede leer o el servidor no coincide con lo declarado.
+async fn preflight_or_exit(scope_path: &str) {
+    let scope = match preflight::Scope::load(Path::new(scope_path)) {
+        Ok(scope) => scope,
+        Err(err) => {
+            eprintln!("❌ Error en el alcance: {}", err);
+            process::exit(2);
+        }
+    };
+    println!("🛫 Chequeo previo de {} destino(s) antes de la carga...", scope.targets.len());
+    // Conexiones bloqueantes con timeout: fuera de los hilos del runtime
+    let report = task::spawn_blocking(move || preflight::run(&scope, &preflight::Limits::default()))
+        .await
+        .map_err(|err| format!("el chequeo previo terminó con un fallo interno: {}", err))
+        .and_then(|report| report);
+    match report {
+        Ok(report) => {
+            report.print();
+            if !report.passed() {
+                eprintln!("⛔ Pruebas de carga y auditoría canceladas: el servidor no coincide con el alcance declarado");
+                process::exit(1);
+            }
+        }
+        Err(err) => {
+            eprintln!("❌ Error en el alcance: {}", err);
+            process::exit(2);
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     println!("🚀 PAPIWEB AUDIT SYSTEM v3.0 - TOKIO ULTRA EDITION");
@@ -366,6 +401,14 @@ async fn main() {
     println!("   🚀 Modo: Máximo rendimiento");
     println!("   💾 Buffer: 100,000 mensajes\n");
     
+    // 0. Chequeo previo: con destinos caídos o con puertos de más no se lanza la carga.
+    // Alcance: primer argumento o PAPIWEB_SCOPE (una línea por destino: host open=80,443 closed=22,3306).
+    // Sin alcance se ejecuta como antes, solo en local y sin chequeo.
+    match std::env::args().nth(1).or_else(|| std::env::var("PAPIWEB_SCOPE").ok()) {
+        Some(scope_path) => preflight_or_exit(&scope_path).await,
+        None => println!("⚠️  Sin alcance declarado (papitest ALCANCE o PAPIWEB_SCOPE): se omite el chequeo previo\n"),
+    }
+    
     // Ejecutar tests de carga rápidos
     run_load_tests(2).await;
     